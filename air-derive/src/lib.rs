@@ -0,0 +1,174 @@
+//! The `AirColumns` derive macro, re-exported by `p3-air`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericParam, Ident, Type};
+
+/// Derives `AirColumns<T>`, along with `Borrow<Self>` and `BorrowMut<Self>` for `[T]`, for a
+/// `#[repr(C)]` struct whose first type parameter `T` is the type of its columns.
+///
+/// Each field must be a `T`, an array of columns, or another struct implementing `AirColumns<T>`.
+#[proc_macro_derive(AirColumns)]
+pub fn derive_air_columns(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match air_columns(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn air_columns(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "AirColumns can only be derived for structs",
+        ));
+    };
+    let is_repr_c = input.attrs.iter().any(|attr| {
+        attr.path().is_ident("repr")
+            && attr
+                .parse_nested_meta(|meta| {
+                    if meta.path.is_ident("C") {
+                        Ok(())
+                    } else {
+                        Err(meta.error("not C"))
+                    }
+                })
+                .is_ok()
+    });
+    if !is_repr_c {
+        return Err(syn::Error::new_spanned(
+            name,
+            "AirColumns requires the struct to be #[repr(C)]",
+        ));
+    }
+    let Some(column_type) = input.generics.params.iter().find_map(|param| match param {
+        GenericParam::Type(param) => Some(param.ident.clone()),
+        _ => None,
+    }) else {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "AirColumns requires a type parameter for the type of the columns",
+        ));
+    };
+
+    let fields: Vec<(String, &Type)> = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| (field.ident.as_ref().unwrap().to_string(), &field.ty))
+            .collect(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (i.to_string(), &field.ty))
+            .collect(),
+        Fields::Unit => vec![],
+    };
+    let num_columns = fields
+        .iter()
+        .map(|(_, ty)| num_columns(ty, &column_type))
+        .collect::<Vec<_>>();
+    let push_names = fields.iter().map(|(field, ty)| {
+        let prefix = format_ident!("prefix");
+        let body = push_names(ty, &column_type, &prefix, 0);
+        quote! {
+            let prefix = ::p3_air::field_column_name(prefix, #field);
+            let prefix = prefix.as_str();
+            #body
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        unsafe impl #impl_generics ::p3_air::AirColumns<#column_type> for #name #ty_generics
+        #where_clause
+        {
+            const NUM_COLUMNS: usize = 0 #(+ #num_columns)*;
+
+            fn push_column_names(
+                prefix: &str,
+                names: &mut ::p3_air::__private::Vec<::p3_air::__private::String>,
+            ) {
+                #({ #push_names })*
+            }
+        }
+
+        impl #impl_generics ::core::borrow::Borrow<#name #ty_generics> for [#column_type]
+        #where_clause
+        {
+            fn borrow(&self) -> &#name #ty_generics {
+                debug_assert_eq!(
+                    self.len(),
+                    <#name #ty_generics as ::p3_air::AirColumns<#column_type>>::NUM_COLUMNS
+                );
+                let (prefix, shorts, suffix) = unsafe { self.align_to::<#name #ty_generics>() };
+                debug_assert!(prefix.is_empty(), "Alignment should match");
+                debug_assert!(suffix.is_empty(), "Alignment should match");
+                debug_assert_eq!(shorts.len(), 1);
+                &shorts[0]
+            }
+        }
+
+        impl #impl_generics ::core::borrow::BorrowMut<#name #ty_generics> for [#column_type]
+        #where_clause
+        {
+            fn borrow_mut(&mut self) -> &mut #name #ty_generics {
+                debug_assert_eq!(
+                    self.len(),
+                    <#name #ty_generics as ::p3_air::AirColumns<#column_type>>::NUM_COLUMNS
+                );
+                let (prefix, shorts, suffix) =
+                    unsafe { self.align_to_mut::<#name #ty_generics>() };
+                debug_assert!(prefix.is_empty(), "Alignment should match");
+                debug_assert!(suffix.is_empty(), "Alignment should match");
+                debug_assert_eq!(shorts.len(), 1);
+                &mut shorts[0]
+            }
+        }
+    })
+}
+
+fn is_column(ty: &Type, column_type: &Ident) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident(column_type))
+}
+
+/// The number of columns in a field of type `ty`, as a constant expression.
+fn num_columns(ty: &Type, column_type: &Ident) -> TokenStream2 {
+    match ty {
+        _ if is_column(ty, column_type) => quote!(1),
+        Type::Array(array) => {
+            let len = &array.len;
+            let inner = num_columns(&array.elem, column_type);
+            quote!((#len) * (#inner))
+        }
+        _ => quote!(<#ty as ::p3_air::AirColumns<#column_type>>::NUM_COLUMNS),
+    }
+}
+
+/// Code pushing the names of the columns in a field of type `ty` to `names`, given the field's
+/// name in the variable `prefix`.
+fn push_names(ty: &Type, column_type: &Ident, prefix: &Ident, depth: usize) -> TokenStream2 {
+    match ty {
+        _ if is_column(ty, column_type) => quote!(names.push(#prefix.into());),
+        Type::Array(array) => {
+            let len = &array.len;
+            let index = format_ident!("i{depth}");
+            let inner_prefix = format_ident!("prefix{depth}");
+            let inner = push_names(&array.elem, column_type, &inner_prefix, depth + 1);
+            quote! {
+                for #index in 0..#len {
+                    let #inner_prefix = ::p3_air::index_column_name(#prefix, #index);
+                    let #inner_prefix = #inner_prefix.as_str();
+                    #inner
+                }
+            }
+        }
+        _ => {
+            quote!(<#ty as ::p3_air::AirColumns<#column_type>>::push_column_names(#prefix, names);)
+        }
+    }
+}
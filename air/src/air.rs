@@ -1,17 +1,83 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::ops::{Add, Mul, Sub};
 
 use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 
+use crate::Padding;
+
 /// An AIR (algebraic intermediate representation).
 pub trait BaseAir<F>: Sync {
     /// The number of columns (a.k.a. registers) in this AIR.
     fn width(&self) -> usize;
 
+    /// The names of the main columns, e.g. from `AirColumns::column_names`, used when displaying
+    /// constraints and traces. Empty if the columns are unnamed.
+    fn column_names(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
         None
     }
+
+    /// Columns whose values repeat cyclically down the trace, such as round constants: row `i` of
+    /// column `j` has the value `periodic_columns()[j][i % period]`. Each period must be a power of
+    /// two no larger than the trace height. Being known to the verifier, these columns are never
+    /// committed to; see `AirBuilderWithPeriodicColumns`.
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![]
+    }
+
+    /// Selectors for sets of rows other than the first, last and transition rows, such as the
+    /// last row of each permutation in a trace of several; see `AirBuilderWithRowSelectors`.
+    /// Unlike selector columns, these are never committed to.
+    fn row_selectors(&self) -> Vec<RowSelector> {
+        vec![]
+    }
+
+    /// How the main trace may be padded to a power-of-two height, e.g. by
+    /// `p3_uni_stark::pad_trace`.
+    fn padding(&self) -> Padding<F> {
+        Padding::Unsupported
+    }
+
+    /// The number of consecutive rows of the main trace that constraints may refer to, i.e. the
+    /// height of the matrix returned by `AirBuilder::main`. Must be at least 2. Preprocessed and
+    /// permutation columns are always limited to the local and next rows.
+    fn window_size(&self) -> usize {
+        2
+    }
+
+    /// The number of extension field challenges sampled once the main trace has been committed.
+    fn num_permutation_challenges(&self) -> usize {
+        0
+    }
+
+    /// The number of extension field columns in the permutation trace.
+    fn permutation_width(&self) -> usize {
+        0
+    }
+
+    /// Builds the permutation trace, a second phase of the trace which may depend on the
+    /// challenges sampled after committing to `main`.
+    ///
+    /// Must return a matrix of width `permutation_width()` and the same height as `main` whenever
+    /// `permutation_width()` is nonzero.
+    fn permutation_trace<EF>(
+        &self,
+        _main: &RowMajorMatrix<F>,
+        _challenges: &[EF],
+    ) -> Option<RowMajorMatrix<EF>>
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+    {
+        None
+    }
 }
 
 /// An AIR that works with a particular `AirBuilder`.
@@ -116,6 +182,68 @@ pub trait AirBuilderWithPublicValues: AirBuilder {
     fn public_values(&self) -> &[Self::PublicVar];
 }
 
+/// A set of rows of the trace, on which a selector is one; see `BaseAir::row_selectors`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RowSelector {
+    /// The row with the given index.
+    Row(usize),
+    /// The rows `offset, offset + step, offset + 2 * step, ...`, up to the end of the trace.
+    Every { step: usize, offset: usize },
+}
+
+impl RowSelector {
+    /// Whether `row` belongs to this set.
+    pub const fn contains(&self, row: usize) -> bool {
+        match *self {
+            Self::Row(r) => row == r,
+            Self::Every { step, offset } => row >= offset && row % step == offset % step,
+        }
+    }
+
+    /// The rows of a trace of the given height which belong to this set, in increasing order.
+    pub fn rows(&self, height: usize) -> Vec<usize> {
+        match *self {
+            Self::Row(row) => {
+                assert!(
+                    row < height,
+                    "row {row} is beyond the trace height {height}"
+                );
+                vec![row]
+            }
+            Self::Every { step, offset } => {
+                assert!(step > 0, "the step of a row selector must be positive");
+                (offset..height).step_by(step).collect()
+            }
+        }
+    }
+}
+
+/// An `AirBuilder` which can read the values of `BaseAir::row_selectors` on the current row.
+pub trait AirBuilderWithRowSelectors: AirBuilder {
+    /// Returns one on the rows selected by `selector`, and zero on the others. The selector must
+    /// be one of those returned by `BaseAir::row_selectors`.
+    fn row_selector(&self, selector: RowSelector) -> Self::Expr;
+
+    /// Returns a sub-builder whose constraints are enforced only on the given row, which must be
+    /// declared as `RowSelector::Row(row)` by `BaseAir::row_selectors`.
+    fn when_row(&mut self, row: usize) -> FilteredAirBuilder<'_, Self> {
+        self.when_selected(RowSelector::Row(row))
+    }
+
+    /// Returns a sub-builder whose constraints are enforced only on the rows selected by
+    /// `selector`.
+    fn when_selected(&mut self, selector: RowSelector) -> FilteredAirBuilder<'_, Self> {
+        self.when(self.row_selector(selector))
+    }
+}
+
+/// An `AirBuilder` which can read the values of `BaseAir::periodic_columns` on the current row.
+pub trait AirBuilderWithPeriodicColumns: AirBuilder {
+    type PeriodicVar: Into<Self::Expr> + Copy;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar];
+}
+
 pub trait PairBuilder: AirBuilder {
     fn preprocessed(&self) -> Self::M;
 }
@@ -157,6 +285,29 @@ pub trait PermutationAirBuilder: ExtensionBuilder {
     fn permutation_randomness(&self) -> &[Self::RandomVar];
 }
 
+/// An `AirBuilder` which can send tuples to, and receive tuples from, buses shared by all rows.
+///
+/// Interactions are balanced by a lookup argument: across the whole trace, every tuple must be
+/// sent over a bus as many times as it is received from it.
+pub trait InteractionBuilder: AirBuilder {
+    /// Sends `values` over `bus`, counted `multiplicity` times.
+    fn send<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>;
+
+    /// Receives `values` from `bus`, counted `multiplicity` times.
+    fn receive<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        self.send(bus, values, -multiplicity.into());
+    }
+}
+
 #[derive(Debug)]
 pub struct FilteredAirBuilder<'a, AB: AirBuilder> {
     pub inner: &'a mut AB,
@@ -222,3 +373,15 @@ impl<'a, AB: PermutationAirBuilder> PermutationAirBuilder for FilteredAirBuilder
         self.inner.permutation_randomness()
     }
 }
+
+impl<'a, AB: InteractionBuilder> InteractionBuilder for FilteredAirBuilder<'a, AB> {
+    fn send<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let multiplicity = self.condition() * multiplicity.into();
+        self.inner.send(bus, values, multiplicity);
+    }
+}
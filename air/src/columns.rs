@@ -0,0 +1,63 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::{size_of, MaybeUninit};
+
+pub use p3_air_derive::AirColumns;
+
+/// A struct of columns holding values of type `T`, such as one row of a trace, which rows can be
+/// borrowed as.
+///
+/// This is usually derived with `#[derive(AirColumns)]` on a `#[repr(C)]` struct whose first type
+/// parameter is `T`, and whose fields are `T`s, arrays, or other such structs. The derive also
+/// implements `Borrow<Self>` and `BorrowMut<Self>` for `[T]`.
+///
+/// # Safety
+///
+/// `Self` must consist of exactly `NUM_COLUMNS` values of type `T` in a row, with no padding.
+pub unsafe trait AirColumns<T>: Sized {
+    /// The number of columns.
+    const NUM_COLUMNS: usize;
+
+    /// Appends the names of the columns, in order, each prefixed by `prefix`.
+    fn push_column_names(prefix: &str, names: &mut Vec<String>);
+
+    /// The names of the columns, in order, like `beginning_full_rounds[2].sbox[5].0[1]`.
+    fn column_names() -> Vec<String> {
+        let mut names = Vec::with_capacity(Self::NUM_COLUMNS);
+        Self::push_column_names("", &mut names);
+        names
+    }
+}
+
+/// Returns the column map of `C`, in which each column holds its own index.
+pub const fn column_map<C: AirColumns<usize>>() -> C {
+    assert!(size_of::<C>() == C::NUM_COLUMNS * size_of::<usize>());
+    let mut map = MaybeUninit::<C>::uninit();
+    let indices = map.as_mut_ptr().cast::<usize>();
+    let mut i = 0;
+    while i < C::NUM_COLUMNS {
+        // SAFETY: `C` consists of `NUM_COLUMNS` `usize`s, so this is in bounds and aligned.
+        unsafe { indices.add(i).write(i) };
+        i += 1;
+    }
+    // SAFETY: every column has been written, and any indices are valid values.
+    unsafe { map.assume_init() }
+}
+
+/// The name of field `field` of the columns named `prefix`. Used by `#[derive(AirColumns)]`.
+#[doc(hidden)]
+pub fn field_column_name(prefix: &str, field: &str) -> String {
+    if prefix.is_empty() {
+        field.into()
+    } else {
+        format!("{prefix}.{field}")
+    }
+}
+
+/// The name of entry `index` of the array of columns named `prefix`. Used by
+/// `#[derive(AirColumns)]`.
+#[doc(hidden)]
+pub fn index_column_name(prefix: &str, index: usize) -> String {
+    format!("{prefix}[{index}]")
+}
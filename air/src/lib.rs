@@ -5,7 +5,17 @@
 extern crate alloc;
 
 mod air;
+mod columns;
+mod padding;
 mod virtual_column;
 
 pub use air::*;
+pub use columns::*;
+pub use padding::*;
 pub use virtual_column::*;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::string::String;
+    pub use alloc::vec::Vec;
+}
@@ -0,0 +1,56 @@
+use alloc::vec::Vec;
+
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+/// How a trace may be extended to a power-of-two height without breaking any constraint; see
+/// `BaseAir::padding`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Padding<F> {
+    /// The trace can't be padded automatically; its height must already be a power of two.
+    Unsupported,
+    /// Append copies of this row.
+    Row(Vec<F>),
+    /// Append copies of the last `n` rows of the trace, in order, cutting the last copy short if
+    /// needed. With `n = 1`, this repeats the last row, which suits AIRs without transition
+    /// constraints, while AIRs processing one item every `n` rows can repeat the last item.
+    RepeatLast(usize),
+}
+
+impl<F: Clone + Send + Sync> Padding<F> {
+    /// Pads `trace` to the next power-of-two height.
+    ///
+    /// Panics if the trace needs padding but this is [`Padding::Unsupported`], or if the padding
+    /// doesn't fit the trace.
+    pub fn pad(&self, trace: &mut RowMajorMatrix<F>) {
+        let width = trace.width();
+        let height = trace.height();
+        let padded_height = height.next_power_of_two();
+        if height == padded_height {
+            return;
+        }
+        let padding_len = (padded_height - height) * width;
+        match self {
+            Self::Unsupported => panic!("the trace height must be a power of two"),
+            Self::Row(row) => {
+                assert_eq!(row.len(), width, "padding row has the wrong width");
+                trace
+                    .values
+                    .extend(row.iter().cloned().cycle().take(padding_len));
+            }
+            Self::RepeatLast(n) => {
+                assert!(
+                    (1..=height).contains(n),
+                    "can't repeat the last {n} rows of a trace of height {height}"
+                );
+                let start = (height - n) * width;
+                let end = padded_height * width;
+                trace.values.reserve(padding_len);
+                while trace.values.len() < end {
+                    let len = (n * width).min(end - trace.values.len());
+                    trace.values.extend_from_within(start..start + len);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use core::borrow::{Borrow, BorrowMut};
+use core::mem::size_of;
+
+use p3_air::{column_map, AirColumns};
+
+#[derive(AirColumns)]
+#[repr(C)]
+pub struct Round<T, const N: usize> {
+    pub flag: T,
+    pub state: [T; N],
+}
+
+#[derive(AirColumns)]
+#[repr(C)]
+pub struct Pair<T>(pub [T; 2]);
+
+#[derive(AirColumns)]
+#[repr(C)]
+pub struct ExampleCols<T> {
+    pub export: T,
+    pub rounds: [Round<T, 2>; 2],
+    pub pair: Pair<T>,
+    pub grid: [[T; 2]; 2],
+}
+
+#[test]
+fn num_columns() {
+    assert_eq!(<Round<u8, 3> as AirColumns<u8>>::NUM_COLUMNS, 4);
+    assert_eq!(<ExampleCols<u8> as AirColumns<u8>>::NUM_COLUMNS, 13);
+    assert_eq!(
+        <ExampleCols<u32> as AirColumns<u32>>::NUM_COLUMNS,
+        size_of::<ExampleCols<u8>>()
+    );
+}
+
+#[test]
+fn column_names() {
+    assert_eq!(
+        ExampleCols::<u8>::column_names(),
+        [
+            "export",
+            "rounds[0].flag",
+            "rounds[0].state[0]",
+            "rounds[0].state[1]",
+            "rounds[1].flag",
+            "rounds[1].state[0]",
+            "rounds[1].state[1]",
+            "pair.0[0]",
+            "pair.0[1]",
+            "grid[0][0]",
+            "grid[0][1]",
+            "grid[1][0]",
+            "grid[1][1]",
+        ]
+    );
+}
+
+#[test]
+fn column_map_and_borrow() {
+    let map: ExampleCols<usize> = column_map();
+    assert_eq!(map.export, 0);
+    assert_eq!(map.rounds[1].state[0], 5);
+    assert_eq!(map.pair.0, [7, 8]);
+    assert_eq!(map.grid[1][1], 12);
+
+    let mut row: Vec<u32> = (0..13).map(|i| 10 * i).collect();
+    let cols: &ExampleCols<u32> = row[..].borrow();
+    assert_eq!(cols.rounds[1].state[0], 50);
+    let cols: &mut ExampleCols<u32> = row[..].borrow_mut();
+    cols.grid[1][1] = 1;
+    assert_eq!(row[map.grid[1][1]], 1);
+}
@@ -2,15 +2,16 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{iterate, Itertools};
-use p3_commit::{LagrangeSelectors, PolynomialSpace};
+use p3_commit::{LagrangeSelectors, PolynomialSpace, RowProgression};
 use p3_field::extension::ComplexExtendable;
-use p3_field::ExtensionField;
+use p3_field::{batch_multiplicative_inverse, ExtensionField};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
 use tracing::instrument;
 
 use crate::point::Point;
+use crate::{CfftView, CircleEvaluations};
 
 /// A twin-coset of the circle group on F. It has a power-of-two size and an arbitrary shift.
 ///
@@ -87,10 +88,6 @@ impl<F: ComplexExtendable> CircleDomain<F> {
     pub(crate) fn s_p<EF: ExtensionField<F>>(&self, p: Point<F>, at: Point<EF>) -> EF {
         self.zeroifier(at) / p.v_tilde_p(at)
     }
-
-    pub(crate) fn s_p_normalized<EF: ExtensionField<F>>(&self, p: Point<F>, at: Point<EF>) -> EF {
-        self.zeroifier(at) / (p.v_tilde_p(at) * p.s_p_at_p(self.log_n))
-    }
 }
 
 impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
@@ -174,13 +171,19 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     fn selectors_at_point<Ext: ExtensionField<Self::Val>>(
         &self,
         point: Ext,
+        rows: &[RowProgression],
     ) -> LagrangeSelectors<Ext> {
         let point = Point::from_projective_line(point);
+        let zeroifier = self.zeroifier(point);
         LagrangeSelectors {
             is_first_row: self.s_p(self.shift, point),
             is_last_row: self.s_p(-self.shift, point),
-            is_transition: Ext::one() - self.s_p_normalized(-self.shift, point),
-            inv_zeroifier: self.zeroifier(point).inverse(),
+            is_transition: (-self.shift).tangent(point),
+            inv_zeroifier: zeroifier.inverse(),
+            rows: rows
+                .iter()
+                .map(|rows| self.rows_selector_at(rows, point, zeroifier))
+                .collect(),
         }
     }
 
@@ -203,17 +206,178 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     // wow, really slow!
     // todo: batch inverses
     #[instrument(skip_all, fields(log_n = %coset.log_n))]
-    fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>> {
+    fn selectors_on_coset(
+        &self,
+        coset: Self,
+        rows: &[RowProgression],
+    ) -> LagrangeSelectors<Vec<Self::Val>> {
         let sels = coset
             .points()
-            .map(|p| self.selectors_at_point(p.to_projective_line().unwrap()))
+            .map(|p| self.selectors_at_point(p.to_projective_line().unwrap(), &[]))
             .collect_vec();
         LagrangeSelectors {
             is_first_row: sels.iter().map(|s| s.is_first_row).collect(),
             is_last_row: sels.iter().map(|s| s.is_last_row).collect(),
             is_transition: sels.iter().map(|s| s.is_transition).collect(),
             inv_zeroifier: sels.iter().map(|s| s.inv_zeroifier).collect(),
+            rows: rows
+                .iter()
+                .map(|rows| self.rows_selector_on_coset(rows, coset))
+                .collect(),
+        }
+    }
+
+    fn periodic_at_point<Ext: ExtensionField<Self::Val>>(
+        &self,
+        values: &[Self::Val],
+        point: Ext,
+    ) -> Ext {
+        if values.len() == 1 {
+            return Ext::from_base(values[0]);
+        }
+        let evals = self.periodic_evals(values);
+        // Doubling log(n / period) times maps the i-th point of this domain to the i-th point of
+        // the standard position coset of size `period`, over which the values are interpolated.
+        let mut point = Point::from_projective_line(point);
+        for _ in evals.domain.log_n..self.log_n {
+            point = point.double();
+        }
+        evals.evaluate_at_point(point)[0]
+    }
+
+    fn periodic_on_coset(&self, values: &[Self::Val], coset: Self) -> Vec<Self::Val> {
+        assert!(coset.is_standard());
+        assert!(coset.log_n >= self.log_n);
+        if values.len() == 1 {
+            return vec![values[0]; 1 << (coset.log_n - self.log_n)];
+        }
+        let evals = self.periodic_evals(values);
+        let log_len = evals.domain.log_n + coset.log_n - self.log_n;
+        let mut periodic = evals
+            .extrapolate(CircleDomain::standard(log_len))
+            .to_natural_order()
+            .to_row_major_matrix()
+            .values;
+        // Drop the repetitions of short periods.
+        periodic.truncate(values.len() << (coset.log_n - self.log_n));
+        periodic
+    }
+}
+
+impl<F: ComplexExtendable> CircleDomain<F> {
+    /// The normalized selector of `rows` at `point`: the sum of the normalized s_p over the selected
+    /// points p.
+    fn rows_selector_at<EF: ExtensionField<F>>(
+        &self,
+        rows: &RowProgression,
+        point: Point<EF>,
+        zeroifier: EF,
+    ) -> EF {
+        if rows.len == 0 {
+            return EF::zero();
+        }
+        assert!(self.is_standard());
+        if rows.is_periodic(self.size()) && self.log_n >= 2 {
+            if rows.step == 1 {
+                return EF::one();
+            }
+            // Doubling maps the i-th point of this domain to the (i mod step)-th point of the
+            // standard position coset of size `step`, whose selectors are summed. Circle domains
+            // need four points, so a step of two selects two of them.
+            let log_step = log2_strict_usize(rows.step).max(2);
+            let domain = CircleDomain::standard(log_step);
+            let mut point = point;
+            for _ in log_step..self.log_n {
+                point = point.double();
+            }
+            let zeroifier = domain.zeroifier(point);
+            let denoms = (rows.offset..1 << log_step)
+                .step_by(rows.step)
+                .map(|i| {
+                    let p = domain.nth_point(i);
+                    p.v_tilde_p(point) * p.s_p_at_p(log_step)
+                })
+                .collect_vec();
+            let sum: EF = batch_multiplicative_inverse(&denoms).into_iter().sum();
+            return sum * zeroifier;
+        }
+        // Points of even and odd index lie on the two halves of the twin coset, so an odd step
+        // is split into two progressions of even step, whose points are `gen * (step / 2)` apart.
+        let progressions = if rows.step.is_multiple_of(2) {
+            vec![*rows]
+        } else {
+            vec![
+                RowProgression {
+                    offset: rows.offset,
+                    step: 2 * rows.step,
+                    len: rows.len.div_ceil(2),
+                },
+                RowProgression {
+                    offset: rows.offset + rows.step,
+                    step: 2 * rows.step,
+                    len: rows.len / 2,
+                },
+            ]
+        };
+        let denoms = progressions
+            .iter()
+            .flat_map(|rows| {
+                let delta = self.gen() * (rows.step / 2);
+                iterate(self.nth_point(rows.offset), move |&p| p + delta).take(rows.len)
+            })
+            .map(|p| p.v_tilde_p(point) * p.s_p_at_p(self.log_n))
+            .collect_vec();
+        let sum: EF = batch_multiplicative_inverse(&denoms).into_iter().sum();
+        sum * zeroifier
+    }
+
+    /// The normalized selector of `rows` over `coset`, in natural order. All the rows of a residue
+    /// class are a periodic column, a few rows are summed pointwise, and any other set is
+    /// extrapolated from its indicator column.
+    fn rows_selector_on_coset(&self, rows: &RowProgression, coset: Self) -> Vec<F> {
+        if rows.is_periodic(self.size()) && self.log_n >= 2 {
+            let mut values = F::zero_vec(rows.step);
+            values[rows.offset] = F::one();
+            let periodic = self.periodic_on_coset(&values, coset);
+            return periodic.into_iter().cycle().take(coset.size()).collect();
+        }
+        if rows.len <= self.log_n {
+            return coset
+                .points()
+                .map(|p| self.rows_selector_at(rows, p, self.zeroifier(p)))
+                .collect();
         }
+        let mut indicator = F::zero_vec(self.size());
+        for i in rows.rows() {
+            indicator[i] = F::one();
+        }
+        CircleEvaluations::from_natural_order(*self, RowMajorMatrix::new_col(indicator))
+            .extrapolate(coset)
+            .to_natural_order()
+            .to_row_major_matrix()
+            .values
+    }
+
+    /// The values of a periodic column, as evaluations over the standard position coset whose
+    /// size is the period. Circle evaluations need at least four points, so shorter periods are
+    /// repeated.
+    fn periodic_evals(&self, values: &[F]) -> CircleEvaluations<F, CfftView<RowMajorMatrix<F>>> {
+        assert!(
+            self.is_standard(),
+            "periodic values need a standard position coset"
+        );
+        let log_period = log2_strict_usize(values.len()).max(2);
+        assert!(log_period <= self.log_n);
+        let values = values
+            .iter()
+            .copied()
+            .cycle()
+            .take(1 << log_period)
+            .collect();
+        CircleEvaluations::from_natural_order(
+            CircleDomain::standard(log_period),
+            RowMajorMatrix::new_col(values),
+        )
     }
 }
 
@@ -235,10 +399,9 @@ mod tests {
     use itertools::izip;
     use p3_field::{batch_multiplicative_inverse, AbstractField};
     use p3_mersenne_31::Mersenne31;
-    use rand::thread_rng;
+    use rand::{thread_rng, Rng};
 
     use super::*;
-    use crate::CircleEvaluations;
 
     fn assert_is_twin_coset<F: ComplexExtendable>(d: CircleDomain<F>) {
         let pts = d.points().collect_vec();
@@ -328,6 +491,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn periodic() {
+        type F = Mersenne31;
+        let log_n = 6;
+        let d = CircleDomain::<F>::standard(log_n);
+        let coset = d.create_disjoint_domain(4 << log_n);
+
+        for log_period in 0..=log_n {
+            let values: Vec<F> = (0..1 << log_period).map(|_| thread_rng().gen()).collect();
+            let periodic = d.periodic_on_coset(&values, coset);
+            assert_eq!(periodic.len() << log_n, values.len() * coset.size());
+
+            // The evaluations match those of the periodic column's low degree extension.
+            let column = values.iter().copied().cycle().take(d.size()).collect_vec();
+            let lde = CircleEvaluations::from_natural_order(d, RowMajorMatrix::new_col(column))
+                .extrapolate(coset)
+                .to_natural_order()
+                .to_row_major_matrix();
+            let mut pt = coset.first_point();
+            for (i, lde_value) in lde.values.into_iter().enumerate() {
+                assert_eq!(periodic[i % periodic.len()], lde_value);
+                assert_eq!(d.periodic_at_point(&values, pt), lde_value);
+                pt = coset.next_point(pt).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn row_selectors() {
+        type F = Mersenne31;
+        let log_n = 5;
+        let d = CircleDomain::<F>::standard(log_n);
+        let coset = d.create_disjoint_domain(4 << log_n);
+        let progression = |offset, step, len| RowProgression { offset, step, len };
+        let rows = vec![
+            RowProgression::single(3),
+            progression(2, 6, 5),
+            progression(1, 3, 11),
+            progression(4, 2, 14),
+            progression(0, 1, 32),
+            progression(1, 2, 16),
+            progression(3, 4, 8),
+            progression(5, 16, 2),
+        ];
+
+        let sels = d.selectors_on_coset(coset, &rows).rows;
+        let mut pt = coset.first_point();
+        for i in 0..coset.size() {
+            let pt_sels = d.selectors_at_point(pt, &rows).rows;
+            assert_eq!(pt_sels, sels.iter().map(|sel| sel[i]).collect_vec());
+            pt = coset.next_point(pt).unwrap();
+        }
+
+        // Over the domain itself, the selectors are one exactly on the selected rows.
+        for (sel, rows) in izip!(sels, &rows) {
+            let selected = rows.rows().collect_vec();
+            let evals = CircleEvaluations::from_natural_order(coset, RowMajorMatrix::new_col(sel));
+            for i in 0..d.size() {
+                let value = evals.evaluate_at_point(d.nth_point(i))[0];
+                assert_eq!(value, F::from_bool(selected.contains(&i)));
+            }
+        }
+    }
+
     #[test]
     fn selectors() {
         type F = Mersenne31;
@@ -336,12 +563,12 @@ mod tests {
 
         let d = CircleDomain::<F>::standard(log_n);
         let coset = d.create_disjoint_domain(n);
-        let sels = d.selectors_on_coset(coset);
+        let sels = d.selectors_on_coset(coset, &[]);
 
         // selectors_on_coset matches selectors_at_point
         let mut pt = coset.first_point();
         for i in 0..coset.size() {
-            let pt_sels = d.selectors_at_point(pt);
+            let pt_sels = d.selectors_at_point(pt, &[]);
             assert_eq!(sels.is_first_row[i], pt_sels.is_first_row);
             assert_eq!(sels.is_last_row[i], pt_sels.is_last_row);
             assert_eq!(sels.is_transition[i], pt_sels.is_transition);
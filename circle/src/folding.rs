@@ -1,3 +1,4 @@
+use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 use core::marker::PhantomData;
@@ -46,6 +47,20 @@ impl<F: ComplexExtendable, EF: ExtensionField<F>, InputProof, InputError: Debug>
     fn fold_matrix<M: Matrix<EF>>(&self, beta: EF, m: M) -> Vec<EF> {
         fold_x(beta, m)
     }
+
+    /// Circle FRI only supports a constant final polynomial, which `CirclePcs` checks.
+    fn interpolate_final_poly(&self, log_blowup: usize, codeword: Vec<EF>) -> Option<Vec<EF>> {
+        assert_eq!(
+            codeword.len(),
+            1 << log_blowup,
+            "circle FRI only supports a constant final polynomial"
+        );
+        codeword.iter().all_equal().then(|| vec![codeword[0]])
+    }
+
+    fn eval_final_poly(&self, final_poly: &[EF], _index: usize, _log_height: usize) -> EF {
+        final_poly[0]
+    }
 }
 
 fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
@@ -5,14 +5,15 @@ use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
+use p3_commit::{Mmcs, OpenedValues, Pcs, PcsProofStats, PolynomialSpace};
 use p3_field::extension::ComplexExtendable;
 use p3_field::{ExtensionField, Field};
 use p3_fri::verifier::FriError;
-use p3_fri::FriConfig;
+use p3_fri::{FriConfig, HighDegreeError};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
+use p3_util::binary::encoded_len;
 use p3_util::log2_strict_usize;
 use serde::{Deserialize, Serialize};
 use tracing::info_span;
@@ -32,6 +33,14 @@ pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
     pub _phantom: PhantomData<Val>,
 }
 
+impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// Circle FRI only supports a folding arity of 2 and a constant final polynomial. The prover
+    /// panics on any other `fri_config`, and the verifier rejects it.
+    fn supports_fri_config(&self) -> bool {
+        self.fri_config.log_folding_arity == 1 && self.fri_config.log_final_poly_len == 0
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
@@ -39,6 +48,15 @@ pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
     pub(crate) opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
 }
 
+impl<Val: Field, InputMmcs: Mmcs<Val>> BatchOpening<Val, InputMmcs> {
+    /// Adds the size of this opening, and the hashes needed to verify it, to `stats`.
+    pub(crate) fn add_stats(&self, mmcs: &InputMmcs, stats: &mut PcsProofStats) {
+        stats.opened_rows += encoded_len(&self.opened_values);
+        stats.opened_row_paths += encoded_len(&self.opening_proof);
+        stats.verifier_hashes += mmcs.num_verifier_hashes(&self.opening_proof);
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CircleInputProof<
@@ -94,6 +112,16 @@ where
     type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
     type Proof = CirclePcsProof<Val, Challenge, InputMmcs, FriMmcs, Challenger::Witness>;
     type Error = FriError<FriMmcs::Error, InputError<InputMmcs::Error, FriMmcs::Error>>;
+    type OpenError = HighDegreeError;
+
+    fn parameters(&self) -> Vec<usize> {
+        self.fri_config.parameters()
+    }
+
+    fn min_degree(&self) -> usize {
+        // See the assertion in `commit`.
+        4
+    }
 
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
         CircleDomain::standard(log2_strict_usize(degree))
@@ -103,6 +131,10 @@ where
         &self,
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
     ) -> (Self::Commitment, Self::ProverData) {
+        assert!(
+            self.supports_fri_config(),
+            "circle FRI only supports a folding arity of 2 and a constant final polynomial"
+        );
         let ldes = evaluations
             .into_iter()
             .map(|(domain, evals)| {
@@ -153,7 +185,11 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::OpenError> {
+        assert!(
+            self.supports_fri_config(),
+            "circle FRI only supports a folding arity of 2 and a constant final polynomial"
+        );
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_ext_element();
 
@@ -301,16 +337,16 @@ where
                 first_layer_siblings,
                 first_layer_proof,
             }
-        });
+        })?;
 
-        (
+        Ok((
             values,
             CirclePcsProof {
                 first_layer_commitment,
                 lambdas,
                 fri_proof,
             },
-        )
+        ))
     }
 
     fn verify(
@@ -334,6 +370,9 @@ where
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
+        if !self.supports_fri_config() {
+            return Err(FriError::InvalidProofShape);
+        }
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_ext_element();
         challenger.observe(proof.first_layer_commitment.clone());
@@ -469,6 +508,23 @@ where
             },
         )
     }
+
+    fn proof_stats(&self, proof: &Self::Proof) -> PcsProofStats {
+        let fri_mmcs = &self.fri_config.mmcs;
+        let mut stats = proof.fri_proof.stats(fri_mmcs, |input_proof, stats| {
+            for batch_opening in &input_proof.input_openings {
+                batch_opening.add_stats(&self.mmcs, stats);
+            }
+            // The first layer is folded like the commit phase layers, but opened with the input.
+            stats.commit_phase_openings += encoded_len(&input_proof.first_layer_siblings);
+            stats.commit_phase_paths += encoded_len(&input_proof.first_layer_proof);
+            stats.verifier_hashes += fri_mmcs.num_verifier_hashes(&input_proof.first_layer_proof);
+        });
+        stats.commitments += encoded_len(&proof.first_layer_commitment);
+        stats.other = 0;
+        stats.other = encoded_len(proof) - stats.total();
+        stats
+    }
 }
 
 #[cfg(test)]
@@ -512,6 +568,8 @@ mod tests {
 
         let fri_config = FriConfig {
             log_blowup: 1,
+            log_final_poly_len: 0,
+            log_folding_arity: 1,
             num_queries: 2,
             proof_of_work_bits: 1,
             mmcs: challenge_mmcs,
@@ -539,7 +597,13 @@ mod tests {
         let zeta: Challenge = rng.gen();
 
         let mut chal = Challenger::from_hasher(vec![], byte_hash);
-        let (values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut chal);
+        let (values, proof) = pcs
+            .open(vec![(&data, vec![vec![zeta]])], &mut chal)
+            .expect("open err");
+
+        let stats = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::proof_stats(&pcs, &proof);
+        assert_eq!(stats.total(), encoded_len(&proof));
+        assert_eq!(stats.num_queries, 2);
 
         let mut chal = Challenger::from_hasher(vec![], byte_hash);
         pcs.verify(
@@ -548,5 +612,72 @@ mod tests {
             &mut chal,
         )
         .expect("verify err");
+
+        // A verifier with a FRI config which circle FRI doesn't support rejects the proof.
+        let pcs = Pcs {
+            mmcs: pcs.mmcs,
+            fri_config: FriConfig {
+                log_folding_arity: 2,
+                ..pcs.fri_config
+            },
+            _phantom: PhantomData,
+        };
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        let result = pcs.verify(
+            vec![(comm, vec![(d, vec![(zeta, values[0][0][0].clone())])])],
+            &proof,
+            &mut chal,
+        );
+        assert!(matches!(result, Err(FriError::InvalidProofShape)));
+    }
+
+    /// Commits to a small matrix with a circle PCS using the given FRI parameters.
+    fn commit_with_fri_config(log_folding_arity: usize, log_final_poly_len: usize) {
+        type Val = Mersenne31;
+        type Challenge = BinomialExtensionField<Mersenne31, 3>;
+        type FieldHash = SerializingHasher32<Keccak256Hash>;
+        type MyCompress = CompressionFunctionFromHasher<Keccak256Hash, 2, 32>;
+        type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+        type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+        type Challenger = SerializingChallenger32<Val, HashChallenger<u8, Keccak256Hash, 32>>;
+        type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+        let val_mmcs = ValMmcs::new(
+            FieldHash::new(Keccak256Hash {}),
+            MyCompress::new(Keccak256Hash {}),
+        );
+        let pcs = Pcs {
+            mmcs: val_mmcs.clone(),
+            fri_config: FriConfig {
+                log_blowup: 1,
+                log_final_poly_len,
+                log_folding_arity,
+                num_queries: 2,
+                proof_of_work_bits: 1,
+                mmcs: ChallengeMmcs::new(val_mmcs),
+            },
+            _phantom: PhantomData,
+        };
+        let evals = RowMajorMatrix::rand(&mut ChaCha8Rng::from_seed([0; 32]), 1 << 4, 1);
+        <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(
+            &pcs,
+            vec![(CircleDomain::standard(4), evals)],
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "circle FRI only supports a folding arity of 2 and a constant final polynomial"
+    )]
+    fn reject_higher_folding_arity() {
+        commit_with_fri_config(2, 0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "circle FRI only supports a folding arity of 2 and a constant final polynomial"
+    )]
+    fn reject_non_constant_final_poly() {
+        commit_with_fri_config(1, 1);
     }
 }
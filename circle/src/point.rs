@@ -90,6 +90,13 @@ impl<F: Field> Point<F> {
         (at - self).to_projective_line().unwrap()
     }
 
+    /// Evaluate the tangent to the circle at `self`, at `at`. Like `v_tilde_p`, this is zero at
+    /// `self` and nonzero elsewhere on the circle, but it's a polynomial of degree 1, which makes it
+    /// a cheap transition selector.
+    pub fn tangent<EF: ExtensionField<F>>(self, at: Point<EF>) -> EF {
+        (at - self).x - EF::one()
+    }
+
     /// The concrete value of the selector s_P = v_n / (v_0 . T_p⁻¹) at P=self, used for normalization.
     /// Circle STARKs, Section 5.1, Remark 16 (page 22 of the first revision PDF)
     pub fn s_p_at_p(self, log_n: usize) -> F {
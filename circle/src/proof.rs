@@ -1,7 +1,8 @@
 use alloc::vec::Vec;
 
-use p3_commit::Mmcs;
+use p3_commit::{Mmcs, PcsProofStats};
 use p3_field::Field;
+use p3_util::binary::encoded_len;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -18,6 +19,39 @@ pub struct CircleFriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub pow_witness: Witness,
 }
 
+impl<F, M, Witness, InputProof> CircleFriProof<F, M, Witness, InputProof>
+where
+    F: Field,
+    M: Mmcs<F>,
+    Witness: Serialize,
+    InputProof: Serialize,
+{
+    /// Breaks down the size of this proof by component, and counts the hashes needed to verify
+    /// it. `input_stats` should add the input proof of a query to the stats.
+    pub fn stats(
+        &self,
+        mmcs: &M,
+        mut input_stats: impl FnMut(&InputProof, &mut PcsProofStats),
+    ) -> PcsProofStats {
+        let mut stats = PcsProofStats {
+            commitments: encoded_len(&self.commit_phase_commits),
+            final_poly: encoded_len(&self.final_poly),
+            num_queries: self.query_proofs.len(),
+            ..Default::default()
+        };
+        for query_proof in &self.query_proofs {
+            input_stats(&query_proof.input_proof, &mut stats);
+            for step in &query_proof.commit_phase_openings {
+                stats.commit_phase_openings += encoded_len(&step.sibling_value);
+                stats.commit_phase_paths += encoded_len(&step.opening_proof);
+                stats.verifier_hashes += mmcs.num_verifier_hashes(&step.opening_proof);
+            }
+        }
+        stats.other = encoded_len(self) - stats.total();
+        stats
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound(
     serialize = "InputProof: Serialize",
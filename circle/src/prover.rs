@@ -6,7 +6,7 @@ use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, FriGenericConfig};
+use p3_fri::{FriConfig, FriGenericConfig, HighDegreeError};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
@@ -20,7 +20,7 @@ pub fn prove<G, Val, Challenge, M, Challenger>(
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize) -> G::InputProof,
-) -> CircleFriProof<Challenge, M, Challenger::Witness, G::InputProof>
+) -> Result<CircleFriProof<Challenge, M, Challenger::Witness, G::InputProof>, HighDegreeError>
 where
     Val: Field,
     Challenge: ExtensionField<Val>,
@@ -36,7 +36,7 @@ where
 
     let log_max_height = log2_strict_usize(inputs[0].len());
 
-    let commit_phase_result = commit_phase(g, config, inputs, challenger);
+    let commit_phase_result = commit_phase(g, config, inputs, challenger)?;
 
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
@@ -54,12 +54,12 @@ where
             .collect()
     });
 
-    CircleFriProof {
+    Ok(CircleFriProof {
         commit_phase_commits: commit_phase_result.commits,
         query_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
-    }
+    })
 }
 
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
@@ -74,7 +74,7 @@ fn commit_phase<G, Val, Challenge, M, Challenger>(
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
-) -> CommitPhaseResult<Challenge, M>
+) -> Result<CommitPhaseResult<Challenge, M>, HighDegreeError>
 where
     Val: Field,
     Challenge: ExtensionField<Val>,
@@ -106,18 +106,16 @@ where
     }
 
     // We should be left with `blowup` evaluations of a constant polynomial.
-    assert_eq!(folded.len(), config.blowup());
-    let final_poly = folded[0];
-    for x in folded {
-        assert_eq!(x, final_poly);
-    }
+    let final_poly = g
+        .interpolate_final_poly(config.log_blowup, folded)
+        .ok_or(HighDegreeError)?[0];
     challenger.observe_ext_element(final_poly);
 
-    CommitPhaseResult {
+    Ok(CommitPhaseResult {
         commits,
         data,
         final_poly,
-    }
+    })
 }
 
 fn answer_query<F, M>(
@@ -86,4 +86,8 @@ where
         self.inner
             .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
     }
+
+    fn num_verifier_hashes(&self, proof: &Self::Proof) -> usize {
+        self.inner.num_verifier_hashes(proof)
+    }
 }
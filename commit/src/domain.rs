@@ -1,6 +1,7 @@
 use alloc::vec::Vec;
 
-use itertools::Itertools;
+use itertools::{izip, Itertools};
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::{
     batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, ExtensionField, Field,
     TwoAdicField,
@@ -15,6 +16,42 @@ pub struct LagrangeSelectors<T> {
     pub is_last_row: T,
     pub is_transition: T,
     pub inv_zeroifier: T,
+    /// The selector of each set of rows passed in: the polynomial of least degree which is one on
+    /// the points of the domain with these indices, and zero on the others. Unlike the selectors
+    /// above, these are normalized.
+    pub rows: Vec<T>,
+}
+
+/// The rows `offset + i * step` of a domain, for `i < len`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RowProgression {
+    pub offset: usize,
+    pub step: usize,
+    pub len: usize,
+}
+
+impl RowProgression {
+    pub const fn single(row: usize) -> Self {
+        Self {
+            offset: row,
+            step: 1,
+            len: 1,
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = usize> {
+        let Self { offset, step, len } = *self;
+        (0..len).map(move |i| offset + i * step)
+    }
+
+    /// Whether these are all the rows congruent to `offset` modulo `step`, in a domain of size `n`.
+    /// Their selector then has a closed form.
+    pub const fn is_periodic(&self, n: usize) -> bool {
+        self.step > 0
+            && n.is_multiple_of(self.step)
+            && self.offset < self.step
+            && self.len == n / self.step
+    }
 }
 
 pub trait PolynomialSpace: Copy {
@@ -44,14 +81,32 @@ pub trait PolynomialSpace: Copy {
 
     fn zp_at_point<Ext: ExtensionField<Self::Val>>(&self, point: Ext) -> Ext;
 
-    // Unnormalized
+    // Unnormalized, except for the selectors of `rows`
     fn selectors_at_point<Ext: ExtensionField<Self::Val>>(
         &self,
         point: Ext,
+        rows: &[RowProgression],
     ) -> LagrangeSelectors<Ext>;
 
-    // Unnormalized
-    fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>>;
+    // Unnormalized, except for the selectors of `rows`
+    fn selectors_on_coset(
+        &self,
+        coset: Self,
+        rows: &[RowProgression],
+    ) -> LagrangeSelectors<Vec<Self::Val>>;
+
+    /// Evaluates at `point` the polynomial of least degree which takes the value
+    /// `values[i % values.len()]` on the `i`-th point of this domain. The period `values.len()`
+    /// must be a power of two no larger than the domain.
+    fn periodic_at_point<Ext: ExtensionField<Self::Val>>(
+        &self,
+        values: &[Self::Val],
+        point: Ext,
+    ) -> Ext;
+
+    /// Evaluates the polynomial of `periodic_at_point` over `coset`. The evaluations are periodic
+    /// too, so only the first `values.len() * coset.size() / self.size()` of them are returned.
+    fn periodic_on_coset(&self, values: &[Self::Val], coset: Self) -> Vec<Self::Val>;
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -64,6 +119,22 @@ impl<Val: TwoAdicField> TwoAdicMultiplicativeCoset<Val> {
     fn gen(&self) -> Val {
         Val::two_adic_generator(self.log_n)
     }
+
+    /// The points of the subgroup with the given indices.
+    fn row_points(&self, rows: &RowProgression) -> impl Iterator<Item = Val> {
+        let gen = self.gen();
+        gen.exp_u64(rows.step as u64)
+            .shifted_powers(gen.exp_u64(rows.offset as u64))
+            .take(rows.len)
+    }
+
+    /// For periodic `rows`, the constant `c` such that the rows are the roots of `x^m - c`, with
+    /// `m = n / step`. Their selector is then `z_h(x) c / (step (x^m - c))`.
+    fn periodic_root(&self, rows: &RowProgression) -> (usize, Val) {
+        let log_step = log2_strict_usize(rows.step);
+        let c = Val::two_adic_generator(log_step).exp_u64(rows.offset as u64);
+        (self.log_n - log_step, c)
+    }
 }
 
 impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
@@ -115,18 +186,45 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
         (point * self.shift.inverse()).exp_power_of_2(self.log_n) - Ext::one()
     }
 
-    fn selectors_at_point<Ext: ExtensionField<Val>>(&self, point: Ext) -> LagrangeSelectors<Ext> {
+    fn selectors_at_point<Ext: ExtensionField<Val>>(
+        &self,
+        point: Ext,
+        rows: &[RowProgression],
+    ) -> LagrangeSelectors<Ext> {
         let unshifted_point = point * self.shift.inverse();
         let z_h = unshifted_point.exp_power_of_2(self.log_n) - Ext::one();
+        let gen = self.gen();
+        let n_inv = Val::from_canonical_usize(self.size()).inverse();
         LagrangeSelectors {
             is_first_row: z_h / (unshifted_point - Ext::one()),
-            is_last_row: z_h / (unshifted_point - self.gen().inverse()),
-            is_transition: unshifted_point - self.gen().inverse(),
+            is_last_row: z_h / (unshifted_point - gen.inverse()),
+            is_transition: unshifted_point - gen.inverse(),
             inv_zeroifier: z_h.inverse(),
+            rows: rows
+                .iter()
+                .map(|rows| {
+                    if rows.is_periodic(self.size()) {
+                        let (log_m, c) = self.periodic_root(rows);
+                        let step_inv = Val::from_canonical_usize(rows.step).inverse();
+                        return z_h * (c * step_inv) / (unshifted_point.exp_power_of_2(log_m) - c);
+                    }
+                    // L_i(x) = (x^n - 1) g^i / (n (x - g^i)), summed over the selected rows.
+                    let xs = self.row_points(rows).collect_vec();
+                    let diffs = xs.iter().map(|&x| unshifted_point - x).collect_vec();
+                    let sum: Ext = izip!(batch_multiplicative_inverse(&diffs), xs)
+                        .map(|(diff_inv, x)| diff_inv * x)
+                        .sum();
+                    sum * z_h * n_inv
+                })
+                .collect(),
         }
     }
 
-    fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Val>> {
+    fn selectors_on_coset(
+        &self,
+        coset: Self,
+        rows: &[RowProgression],
+    ) -> LagrangeSelectors<Vec<Val>> {
         assert_eq!(self.shift, Val::one());
         assert_ne!(coset.shift, Val::one());
         assert!(coset.log_n >= self.log_n);
@@ -157,9 +255,51 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
 
         let subgroup_last = self.gen().inverse();
 
+        // The normalized selector of a set of rows is the sum of their Lagrange polynomials,
+        // L_i(x) = (x^n - 1) g^i / (n (x - g^i)). A few rows are summed directly, all the rows
+        // of a residue class have a closed form, and any other set is the low-degree extension of
+        // its indicator column.
+        let n_inv = Val::from_canonical_usize(self.size()).inverse();
+        let rows_selector = |rows: &RowProgression| {
+            if rows.is_periodic(self.size()) {
+                // x^m is periodic over the coset, with period `step << rate_bits`.
+                let (log_m, c) = self.periodic_root(rows);
+                let denoms = cyclic_subgroup_coset_known_order(
+                    Val::two_adic_generator(coset.log_n - log_m),
+                    coset.shift.exp_power_of_2(log_m),
+                    rows.step << rate_bits,
+                )
+                .map(|y| y - c)
+                .collect_vec();
+                let scale = c * Val::from_canonical_usize(rows.step).inverse();
+                let sel = izip!(batch_multiplicative_inverse(&denoms), evals.iter().cycle())
+                    .map(|(inv, &z_h)| z_h * inv * scale)
+                    .collect_vec();
+                sel.into_iter().cycle().take(xs.len()).collect()
+            } else if rows.len <= self.log_n {
+                let mut sum = Val::zero_vec(xs.len());
+                for row_point in self.row_points(rows) {
+                    let denoms = xs.iter().map(|&x| x - row_point).collect_vec();
+                    for (acc, inv) in sum.iter_mut().zip(batch_multiplicative_inverse(&denoms)) {
+                        *acc += inv * row_point;
+                    }
+                }
+                izip!(sum, evals.iter().cycle())
+                    .map(|(acc, &z_h)| acc * z_h * n_inv)
+                    .collect_vec()
+            } else {
+                let mut indicator = Val::zero_vec(self.size());
+                for i in rows.rows() {
+                    indicator[i] = Val::one();
+                }
+                Radix2Dit::default().coset_lde(indicator, rate_bits, coset.shift)
+            }
+        };
+
         LagrangeSelectors {
             is_first_row: single_point_selector(0),
             is_last_row: single_point_selector((1 << self.log_n) - 1),
+            rows: rows.iter().map(rows_selector).collect(),
             is_transition: xs.into_iter().map(|x| x - subgroup_last).collect(),
             inv_zeroifier: batch_multiplicative_inverse(&evals)
                 .into_iter()
@@ -168,4 +308,48 @@ impl<Val: TwoAdicField> PolynomialSpace for TwoAdicMultiplicativeCoset<Val> {
                 .collect(),
         }
     }
+
+    fn periodic_at_point<Ext: ExtensionField<Val>>(&self, values: &[Val], point: Ext) -> Ext {
+        let log_period = log2_strict_usize(values.len());
+        assert!(log_period <= self.log_n);
+        // x -> (x / shift)^(n / period) maps the i-th point of this domain to the i-th point of
+        // the subgroup of order `period`, over which the values are interpolated.
+        let point = (point * self.shift.inverse()).exp_power_of_2(self.log_n - log_period);
+        interpolate_subgroup_at(values, point)
+    }
+
+    fn periodic_on_coset(&self, values: &[Val], coset: Self) -> Vec<Val> {
+        let log_period = log2_strict_usize(values.len());
+        assert!(log_period <= self.log_n);
+        assert!(coset.log_n >= self.log_n);
+        let log_len = log_period + coset.log_n - self.log_n;
+        let shift = (coset.shift * self.shift.inverse()).exp_power_of_2(self.log_n - log_period);
+        cyclic_subgroup_coset_known_order(Val::two_adic_generator(log_len), shift, 1 << log_len)
+            .map(|point| interpolate_subgroup_at(values, point))
+            .collect()
+    }
+}
+
+/// Evaluates at `point` the polynomial which takes the value `values[i]` on `g^i`, where `g`
+/// generates the subgroup of order `values.len()`.
+fn interpolate_subgroup_at<F: TwoAdicField, Ext: ExtensionField<F>>(
+    values: &[F],
+    point: Ext,
+) -> Ext {
+    let log_n = log2_strict_usize(values.len());
+    let subgroup = F::two_adic_generator(log_n)
+        .powers()
+        .take(values.len())
+        .collect_vec();
+    if let Some(i) = subgroup.iter().position(|&x| point == Ext::from_base(x)) {
+        return Ext::from_base(values[i]);
+    }
+
+    // The barycentric formula: p(x) = (x^n - 1) / n * sum_i values[i] g^i / (x - g^i).
+    let diffs = subgroup.iter().map(|&x| point - x).collect_vec();
+    let sum: Ext = izip!(batch_multiplicative_inverse(&diffs), &subgroup, values)
+        .map(|(diff_inv, &x, &value)| diff_inv * (x * value))
+        .sum();
+    let z_h = point.exp_power_of_2(log_n) - Ext::one();
+    sum * z_h * F::from_canonical_usize(values.len()).inverse()
 }
@@ -76,4 +76,14 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         opened_values: &[Vec<T>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error>;
+
+    /// The number of hash and compression calls `verify_batch` makes to check `proof`.
+    ///
+    /// This is exact for batches of matrices of a single height. Batches of matrices of several
+    /// heights may need a few more calls per additional height, which can't be told from the proof.
+    ///
+    /// Defaults to zero, for implementations which don't report it.
+    fn num_verifier_hashes(&self, _proof: &Self::Proof) -> usize {
+        0
+    }
 }
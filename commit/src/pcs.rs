@@ -6,6 +6,7 @@ use core::fmt::Debug;
 use p3_field::ExtensionField;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
+use p3_util::binary::encoded_len;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -32,6 +33,34 @@ where
 
     type Error: Debug;
 
+    /// The error returned by `open` when the committed polynomials, or the combinations of them
+    /// which the opening argument tests, aren't of low enough degree.
+    type OpenError: Debug;
+
+    /// Whether this PCS hides the committed polynomials. A hiding PCS randomizes each committed
+    /// polynomial by adding a random multiple of its domain's vanishing polynomial, which doubles
+    /// its degree but leaves its values on the domain unchanged.
+    const ZK: bool = false;
+
+    /// The number of random polynomials which a hiding PCS appends to the quotient chunks in
+    /// `commit_quotient`, and which are opened along with them.
+    fn num_random_codewords(&self) -> usize {
+        0
+    }
+
+    /// The parameters which the shape and soundness of this PCS's proofs depend on, such as the
+    /// blowup and number of queries of a FRI configuration. Proof formats include them in a
+    /// fingerprint of the configuration, so that proofs aren't decoded with the wrong one.
+    fn parameters(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// The smallest degree of a domain which this PCS can commit to and open, such as the length
+    /// of the final polynomial of a FRI configuration. Provers check their traces against it.
+    fn min_degree(&self) -> usize {
+        1
+    }
+
     /// This should return a coset domain (s.t. Domain::next_point returns Some)
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain;
 
@@ -41,6 +70,19 @@ where
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
     ) -> (Self::Commitment, Self::ProverData);
 
+    /// Commits to the chunks of a quotient polynomial, as split by `PolynomialSpace::split_evals`.
+    ///
+    /// A hiding PCS randomizes the chunks such that the randomness cancels out when they are
+    /// recombined, and appends one more matrix of random polynomials, on the domain of the first
+    /// chunk, which should be opened along with the chunks to mask the batched opening.
+    #[allow(clippy::type_complexity)]
+    fn commit_quotient(
+        &self,
+        chunks: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        self.commit(chunks)
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -60,7 +102,7 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof);
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::OpenError>;
 
     #[allow(clippy::type_complexity)]
     fn verify(
@@ -84,6 +126,64 @@ where
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error>;
+
+    /// Breaks down the size of `proof` by component, and counts the hashes needed to verify it.
+    ///
+    /// By default, the whole proof is counted as [`PcsProofStats::other`].
+    fn proof_stats(&self, proof: &Self::Proof) -> PcsProofStats {
+        PcsProofStats {
+            other: encoded_len(proof),
+            ..Default::default()
+        }
+    }
+}
+
+/// The size of a PCS opening proof, broken down by component, and the work needed to verify it.
+///
+/// Sizes are in bytes of the canonical encoding of `p3_util::binary`. The sizes of all components
+/// add up to that of the whole proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PcsProofStats {
+    /// Commitments made while opening, such as those of the FRI commit phase.
+    pub commitments: usize,
+    /// The rows of the committed matrices opened by all queries.
+    pub opened_rows: usize,
+    /// The Merkle authentication paths of the opened rows, for all queries.
+    pub opened_row_paths: usize,
+    /// The sibling values opened in the FRI commit phase, for all queries.
+    pub commit_phase_openings: usize,
+    /// The Merkle authentication paths of the commit phase openings, for all queries.
+    pub commit_phase_paths: usize,
+    /// The final polynomial.
+    pub final_poly: usize,
+    /// Everything else: the proof-of-work witness, length prefixes, and so on.
+    pub other: usize,
+    /// The number of queries.
+    pub num_queries: usize,
+    /// The number of hash and compression calls the verifier makes to check Merkle paths, as
+    /// estimated by `Mmcs::num_verifier_hashes`, or zero if the MMCS doesn't report it. This
+    /// excludes the Fiat-Shamir transcript.
+    pub verifier_hashes: usize,
+}
+
+impl PcsProofStats {
+    /// The size of the whole proof, in bytes.
+    pub const fn total(&self) -> usize {
+        self.commitments
+            + self.opened_rows
+            + self.opened_row_paths
+            + self.commit_phase_openings
+            + self.commit_phase_paths
+            + self.final_poly
+            + self.other
+    }
+
+    /// The size of the Merkle authentication paths needed by each query, in bytes.
+    pub fn paths_per_query(&self) -> usize {
+        (self.opened_row_paths + self.commit_phase_paths)
+            .checked_div(self.num_queries)
+            .unwrap_or(0)
+    }
 }
 
 pub type OpenedValues<F> = Vec<OpenedValuesForRound<F>>;
@@ -51,6 +51,11 @@ where
     type ProverData = Vec<RowMajorMatrix<Val>>;
     type Proof = ();
     type Error = ();
+    type OpenError = ();
+
+    fn parameters(&self) -> Vec<usize> {
+        vec![self.log_n]
+    }
 
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
         TwoAdicMultiplicativeCoset {
@@ -116,8 +121,8 @@ where
             >,
         )>,
         _challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
-        (
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::OpenError> {
+        Ok((
             rounds
                 .into_iter()
                 .map(|(coeffs_for_round, points_for_round)| {
@@ -134,7 +139,7 @@ where
                 })
                 .collect(),
             (),
-        )
+        ))
     }
 
     fn verify(
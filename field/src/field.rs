@@ -408,6 +408,20 @@ pub trait ExtensionField<Base: Field>: Field + AbstractExtensionField<Base> {
 
         core::iter::successors(Some(current), move |&current| Some(current * multiplier))
     }
+
+    /// Packs the first `Base::Packing::WIDTH` elements of `exts`, so that each coefficient of the
+    /// result holds the corresponding coefficients of the elements.
+    fn pack_ext_slice(exts: &[Self]) -> Self::ExtensionPacking {
+        Self::ExtensionPacking::from_base_fn(|i| {
+            Base::Packing::from_fn(|j| exts[j].as_base_slice()[i])
+        })
+    }
+
+    /// The inverse of `pack_ext_slice`, yielding `Base::Packing::WIDTH` elements.
+    fn unpack_ext(packed: Self::ExtensionPacking) -> impl Iterator<Item = Self> {
+        (0..Base::Packing::WIDTH)
+            .map(move |j| Self::from_base_fn(|i| packed.as_base_slice()[i].as_slice()[j]))
+    }
 }
 
 impl<F: Field> ExtensionField<F> for F {
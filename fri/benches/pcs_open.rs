@@ -0,0 +1,83 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::thread_rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel<Val>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+/// Benchmarks opening a single committed matrix, which includes reducing its rows into the FRI
+/// input and folding it.
+fn bench_pcs_open(c: &mut Criterion) {
+    let mut rng = thread_rng();
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut rng,
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+
+    const WIDTH: usize = 64;
+
+    for log_folding_arity in [1, 3] {
+        let mut group = c.benchmark_group(format!(
+            "open::<BabyBear, arity {}, width {WIDTH}>",
+            1 << log_folding_arity
+        ));
+        group.sample_size(10);
+
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            log_folding_arity,
+            num_queries: 100,
+            proof_of_work_bits: 0,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
+        let pcs = MyPcs::new(Dft::default(), val_mmcs.clone(), fri_config);
+
+        for log_degree in [14, 16, 18] {
+            let degree = 1 << log_degree;
+            let domain =
+                <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, degree);
+            let evals = RowMajorMatrix::<Val>::rand(&mut rng, degree, WIDTH);
+            let (commit, data) =
+                <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+            let mut challenger = Challenger::new(perm.clone());
+            challenger.observe(commit);
+            let zeta: Challenge = challenger.sample_ext_element();
+
+            group.bench_function(BenchmarkId::from_parameter(degree), |b| {
+                b.iter(|| {
+                    pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger.clone())
+                        .unwrap();
+                })
+            });
+        }
+    }
+}
+
+criterion_group!(benches, bench_pcs_open);
+criterion_main!(benches);
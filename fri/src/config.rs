@@ -1,3 +1,4 @@
+use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
@@ -7,6 +8,15 @@ use p3_matrix::Matrix;
 #[derive(Debug)]
 pub struct FriConfig<M> {
     pub log_blowup: usize,
+    /// The log of the number of coefficients of the final polynomial, which the prover sends
+    /// instead of folding further. Every input must be at least `2^(log_blowup +
+    /// log_final_poly_len)` long, so a PCS using this config can only commit to matrices with at
+    /// least `2^log_final_poly_len` rows, and STARK traces must be at least that tall.
+    pub log_final_poly_len: usize,
+    /// The log of the largest number of codeword values folded into one in a commit phase round.
+    /// A round folds by less when needed to reach the height of the next input or of the final
+    /// polynomial.
+    pub log_folding_arity: usize,
     pub num_queries: usize,
     pub proof_of_work_bits: usize,
     pub mmcs: M,
@@ -17,11 +27,55 @@ impl<M> FriConfig<M> {
         1 << self.log_blowup
     }
 
+    pub const fn final_poly_len(&self) -> usize {
+        1 << self.log_final_poly_len
+    }
+
+    /// The parameters which FRI proofs depend on, i.e. all but the MMCS.
+    pub fn parameters(&self) -> Vec<usize> {
+        vec![
+            self.log_blowup,
+            self.log_final_poly_len,
+            self.log_folding_arity,
+            self.num_queries,
+            self.proof_of_work_bits,
+        ]
+    }
+
+    /// The log of the folding arity of each commit phase round, when folding inputs with the given
+    /// log heights, which must be sorted in descending order.
+    ///
+    /// Each round folds by `2^log_folding_arity`, unless that would skip past the next input or
+    /// the final polynomial. Panics if `log_folding_arity` is zero.
+    pub fn log_arities(&self, log_input_heights: &[usize]) -> Vec<usize> {
+        assert!(
+            self.log_folding_arity > 0,
+            "the folding arity must be at least 2"
+        );
+        let log_final_height = self.log_blowup + self.log_final_poly_len;
+        let mut log_heights = log_input_heights.iter().copied().peekable();
+        let mut log_arities = vec![];
+        let Some(mut log_height) = log_heights.next() else {
+            return log_arities;
+        };
+        while log_height > log_final_height {
+            // Inputs of the current height are folded in already.
+            while log_heights.next_if(|&h| h >= log_height).is_some() {}
+            let log_min_height = log_heights
+                .peek()
+                .map_or(log_final_height, |&h| h.max(log_final_height));
+            let log_arity = self.log_folding_arity.min(log_height - log_min_height);
+            log_arities.push(log_arity);
+            log_height -= log_arity;
+        }
+        log_arities
+    }
+
     /// Returns the soundness bits of this FRI instance based on the
     /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
     ///
-    /// Certain users may instead want to look at proven soundness, a more complex calculation which
-    /// isn't currently supported by this crate.
+    /// Certain users may instead want to look at proven soundness, see
+    /// [`FriConfig::proven_soundness`].
     pub fn conjectured_soundness_bits(&self) -> usize {
         self.log_blowup * self.num_queries + self.proof_of_work_bits
     }
@@ -37,9 +91,9 @@ pub trait FriGenericConfig<F: Field> {
     /// They will be passed to our callbacks, but ignored (shifted off) by FRI.
     fn extra_query_index_bits(&self) -> usize;
 
-    /// Fold a row, returning a single column.
-    /// Right now the input row will always be 2 columns wide,
-    /// but we may support higher folding arity in the future.
+    /// Fold a row of a commit phase codeword, returning a single value. The row has `2^k` columns
+    /// for a folding arity of `2^k`, `index` is its index and `log_height` is the log of the height
+    /// of the folded codeword.
     fn fold_row(
         &self,
         index: usize,
@@ -48,6 +102,16 @@ pub trait FriGenericConfig<F: Field> {
         evals: impl Iterator<Item = F>,
     ) -> F;
 
-    /// Same as applying fold_row to every row, possibly faster.
+    /// Same as applying fold_row to every row, possibly faster. The width of `m` is the folding
+    /// arity.
     fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F>;
+
+    /// Interpolates the final codeword, in bit-reversed order, into the coefficients of the final
+    /// polynomial, starting with the constant term. The codeword is `2^log_blowup` times as long
+    /// as the polynomial; returns `None` if it isn't a codeword of such a polynomial.
+    fn interpolate_final_poly(&self, log_blowup: usize, codeword: Vec<F>) -> Option<Vec<F>>;
+
+    /// Evaluates the final polynomial at the point of the final codeword with the given index,
+    /// where the codeword has height `2^log_height`.
+    fn eval_final_poly(&self, final_poly: &[F], index: usize, log_height: usize) -> F;
 }
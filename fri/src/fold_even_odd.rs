@@ -1,7 +1,7 @@
 use alloc::vec::Vec;
 
 use itertools::Itertools;
-use p3_field::TwoAdicField;
+use p3_field::{ExtensionField, Field, PackedValue, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
@@ -34,8 +34,6 @@ pub fn fold_even_odd<F: TwoAdicField>(poly: Vec<F>, beta: F) -> Vec<F> {
     let one_half = F::two().inverse();
     let half_beta = beta * one_half;
 
-    // TODO: vectorize this (after we have packed extension fields)
-
     // beta/2 times successive powers of g_inv
     let mut powers = g_inv
         .shifted_powers(half_beta)
@@ -52,24 +50,104 @@ pub fn fold_even_odd<F: TwoAdicField>(poly: Vec<F>, beta: F) -> Vec<F> {
         .collect()
 }
 
+/// Like [`fold_even_odd`], but over an extension `EF` of `F`, with the arithmetic vectorized with
+/// the packing of `F`.
+#[instrument(skip_all, level = "debug")]
+pub fn fold_even_odd_packed<F: Field, EF: ExtensionField<F> + TwoAdicField>(
+    poly: Vec<EF>,
+    beta: EF,
+) -> Vec<EF> {
+    fold_pairs::<F, EF, _>(beta, RowMajorMatrix::new(poly, 2))
+}
+
+/// Like `fold_even_odd`, but folds each pair of consecutive evaluations in the rows of `m`, which
+/// together hold the bit-reversed evaluations.
+pub(crate) fn fold_pairs<F, EF, M>(beta: EF, m: M) -> Vec<EF>
+where
+    F: Field,
+    EF: ExtensionField<F> + TwoAdicField,
+    M: Matrix<EF>,
+{
+    // We use the fact that
+    //     p_e(x^2) = (p(x) + p(-x)) / 2
+    //     p_o(x^2) = (p(x) - p(-x)) / (2 x)
+    // that is,
+    //     p_e(g^(2i)) = (p(g^i) + p(g^(n/2 + i))) / 2
+    //     p_o(g^(2i)) = (p(g^i) - p(g^(n/2 + i))) / (2 g^i)
+    // so
+    //     result(g^(2i)) = p_e(g^(2i)) + beta p_o(g^(2i))
+    //                    = (p(g^i) + p(g^(n/2 + i))) / 2
+    //                    + beta/2 g_inv^i (p(g^i) - p(g^(n/2 + i)))
+    let pairs_per_row = m.width() / 2;
+    let num_pairs = m.height() * pairs_per_row;
+    let g_inv = EF::two_adic_generator(log2_strict_usize(num_pairs) + 1).inverse();
+    let one_half = F::one().halve();
+    let half_beta = beta * one_half;
+
+    // beta/2 times successive powers of g_inv
+    let mut powers = g_inv
+        .shifted_powers(half_beta)
+        .take(num_pairs)
+        .collect_vec();
+    reverse_slice_index_bits(&mut powers);
+
+    // The evaluation at `x` (`k = 0`) or `-x` (`k = 1`) of pair `i`.
+    let eval = |i: usize, k: usize| m.get(i / pairs_per_row, 2 * (i % pairs_per_row) + k);
+
+    // Fold `F::Packing::WIDTH` pairs at a time, and the remaining ones one by one.
+    let width = F::Packing::WIDTH;
+    let mut folded = EF::zero_vec(num_pairs);
+    let (packed_folded, rest) = folded.split_at_mut(num_pairs - num_pairs % width);
+    let packed_one_half = F::Packing::from(one_half);
+    packed_folded
+        .par_chunks_exact_mut(width)
+        .zip(powers.par_chunks_exact(width))
+        .enumerate()
+        .for_each(|(chunk, (folded, powers))| {
+            let start = chunk * width;
+            // Gather the chunk's evaluations once, using the output chunk as scratch space.
+            let mut gather = |k| {
+                for (j, x) in folded.iter_mut().enumerate() {
+                    *x = eval(start + j, k);
+                }
+                EF::pack_ext_slice(folded)
+            };
+            let lo = gather(0);
+            let hi = gather(1);
+            let power = EF::pack_ext_slice(powers);
+            let result = (lo + hi) * packed_one_half + power * (lo - hi);
+            folded
+                .iter_mut()
+                .zip(EF::unpack_ext(result))
+                .for_each(|(x, y)| *x = y);
+        });
+    let start = packed_folded.len();
+    for (i, x) in rest.iter_mut().enumerate() {
+        let (lo, hi) = (eval(start + i, 0), eval(start + i, 1));
+        *x = (lo + hi) * one_half + powers[start + i] * (lo - hi);
+    }
+    folded
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::izip;
     use p3_baby_bear::BabyBear;
     use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+    use p3_field::extension::BinomialExtensionField;
+    use rand::distributions::{Distribution, Standard};
     use rand::{thread_rng, Rng};
 
     use super::*;
 
-    #[test]
-    fn test_fold_even_odd() {
-        type F = BabyBear;
-
+    fn do_test_fold_even_odd<F: Field, EF: ExtensionField<F> + TwoAdicField>(log_n: usize)
+    where
+        Standard: Distribution<EF>,
+    {
         let mut rng = thread_rng();
 
-        let log_n = 10;
         let n = 1 << log_n;
-        let coeffs = (0..n).map(|_| rng.gen::<F>()).collect::<Vec<_>>();
+        let coeffs = (0..n).map(|_| rng.gen::<EF>()).collect::<Vec<_>>();
 
         let dft = Radix2Dit::default();
         let evals = dft.dft(coeffs.clone());
@@ -80,17 +158,33 @@ mod tests {
         let odd_coeffs = coeffs.iter().cloned().skip(1).step_by(2).collect_vec();
         let odd_evals = dft.dft(odd_coeffs);
 
-        let beta = rng.gen::<F>();
+        let beta = rng.gen::<EF>();
         let expected = izip!(even_evals, odd_evals)
             .map(|(even, odd)| even + beta * odd)
             .collect::<Vec<_>>();
 
         // fold_even_odd takes and returns in bitrev order.
-        let mut folded = evals;
-        reverse_slice_index_bits(&mut folded);
-        folded = fold_even_odd(folded, beta);
+        let mut evals = evals;
+        reverse_slice_index_bits(&mut evals);
+        let mut folded = fold_even_odd(evals.clone(), beta);
         reverse_slice_index_bits(&mut folded);
+        assert_eq!(expected, folded);
 
+        let mut folded = fold_even_odd_packed::<F, EF>(evals, beta);
+        reverse_slice_index_bits(&mut folded);
         assert_eq!(expected, folded);
     }
+
+    #[test]
+    fn test_fold_even_odd() {
+        do_test_fold_even_odd::<BabyBear, BabyBear>(10);
+    }
+
+    #[test]
+    fn test_fold_even_odd_extension() {
+        // Small sizes are folded without packing, in part or in full.
+        for log_n in 1..10 {
+            do_test_fold_even_odd::<BabyBear, BinomialExtensionField<BabyBear, 4>>(log_n);
+        }
+    }
 }
@@ -0,0 +1,273 @@
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use itertools::Itertools;
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{
+    Mmcs, OpenedValues, Pcs, PcsProofStats, PolynomialSpace, TwoAdicMultiplicativeCoset,
+};
+use p3_dft::TwoAdicSubgroupDft;
+use p3_field::{ExtensionField, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use tracing::instrument;
+
+use crate::{FriConfig, HighDegreeError, TwoAdicFriPcs};
+
+/// A hiding variant of [`TwoAdicFriPcs`], for zero-knowledge proofs.
+///
+/// Every polynomial `p` committed on a domain `H` of size `n` is replaced by `p + Z_H r`, for a
+/// random `r` of degree `< n`. The result agrees with `p` on `H`, while any `n` of its
+/// evaluations outside `H` are uniformly random. So the number of opening points plus the number
+/// of FRI queries must stay below the smallest committed height for the openings to hide `p`.
+///
+/// Quotient chunks are randomized such that the randomness cancels out when they are recombined,
+/// and `num_random_codewords` random polynomials are committed alongside them to mask the batched
+/// polynomial sent through FRI.
+///
+/// This should be used with a hiding MMCS, such as `MerkleTreeHidingMmcs`, so that the opened
+/// rows aren't leaked through their Merkle hashes either.
+///
+/// `R` should be an appropriately seeded cryptographically secure pseudorandom number generator
+/// (CSPRNG).
+#[derive(Debug)]
+pub struct HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R> {
+    inner: TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>,
+    num_random_codewords: usize,
+    rng: RefCell<R>,
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, R> HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R> {
+    pub fn new(
+        dft: Dft,
+        mmcs: InputMmcs,
+        fri: FriConfig<FriMmcs>,
+        num_random_codewords: usize,
+        rng: R,
+    ) -> Self {
+        assert!(
+            num_random_codewords > 0,
+            "at least one random codeword is needed to mask the batched opening"
+        );
+        Self {
+            inner: TwoAdicFriPcs::new(dft, mmcs, fri),
+            num_random_codewords,
+            rng: rng.into(),
+        }
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, R> HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R>
+where
+    Val: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<Val>,
+{
+    /// Adds `(X^n - 1) r(X)` to the polynomial `p(s X)`, where `s` is the shift of the domain of
+    /// `evals` and `r` is given by its coefficients `randomizer`. Returns the evaluations of the
+    /// result over a subgroup twice as large, i.e. of the randomized `p` over the doubled domain.
+    fn randomize(
+        &self,
+        evals: RowMajorMatrix<Val>,
+        randomizer: RowMajorMatrix<Val>,
+    ) -> RowMajorMatrix<Val> {
+        let mut coeffs = self.inner.dft.idft_batch(evals);
+        for (c, &r) in coeffs.values.iter_mut().zip(&randomizer.values) {
+            *c -= r;
+        }
+        coeffs.values.extend(randomizer.values);
+        self.inner.dft.dft_batch(coeffs).to_row_major_matrix()
+    }
+}
+
+/// The domain over which a randomized polynomial, of twice the degree, is committed.
+const fn doubled<Val: TwoAdicField>(
+    domain: TwoAdicMultiplicativeCoset<Val>,
+) -> TwoAdicMultiplicativeCoset<Val> {
+    TwoAdicMultiplicativeCoset {
+        log_n: domain.log_n + 1,
+        shift: domain.shift,
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, R, Challenge, Challenger> Pcs<Challenge, Challenger>
+    for HidingFriPcs<Val, Dft, InputMmcs, FriMmcs, R>
+where
+    Val: TwoAdicField,
+    Standard: Distribution<Val>,
+    Dft: TwoAdicSubgroupDft<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    R: Rng,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
+    type Proof = <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::Proof;
+    type Error = <TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> as Pcs<Challenge, Challenger>>::Error;
+    type OpenError = HighDegreeError;
+
+    const ZK: bool = true;
+
+    fn num_random_codewords(&self) -> usize {
+        self.num_random_codewords
+    }
+
+    fn parameters(&self) -> Vec<usize> {
+        let mut parameters = Pcs::<Challenge, Challenger>::parameters(&self.inner);
+        parameters.push(self.num_random_codewords);
+        parameters
+    }
+
+    fn min_degree(&self) -> usize {
+        Pcs::<Challenge, Challenger>::min_degree(&self.inner)
+    }
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        Pcs::<Challenge, Challenger>::natural_domain_for_degree(&self.inner, degree)
+    }
+
+    #[instrument(name = "commit with randomization", skip_all)]
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        let randomized = evaluations
+            .into_iter()
+            .map(|(domain, evals)| {
+                let randomizer = RowMajorMatrix::rand(
+                    &mut *self.rng.borrow_mut(),
+                    evals.height(),
+                    evals.width(),
+                );
+                (doubled(domain), self.randomize(evals, randomizer))
+            })
+            .collect();
+        Pcs::<Challenge, Challenger>::commit(&self.inner, randomized)
+    }
+
+    #[instrument(name = "commit to randomized quotient chunks", skip_all)]
+    fn commit_quotient(
+        &self,
+        chunks: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        // Chunk i is recombined with the weight prod_{j != i} Z_j(X) / Z_j(s_i), where Z_j is the
+        // vanishing polynomial of the domain of chunk j and s_i is the shift of chunk i. Adding
+        // Z_i(X) r_i(X / s_i) to each chunk thus changes the recombined quotient by
+        //     prod_j Z_j(X) * sum_i r_i(X / s_i) / prod_{j != i} Z_j(s_i),
+        // so we pick all but the last r_i at random, and the last such that the sum vanishes,
+        // coefficient by coefficient.
+        let (domains, chunks): (Vec<_>, Vec<_>) = chunks.into_iter().unzip();
+        let (height, width) = (chunks[0].height(), chunks[0].width());
+        let weights = domains
+            .iter()
+            .enumerate()
+            .map(|(i, domain)| {
+                let normalizer: Val = domains
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, other_domain)| other_domain.zp_at_point(domain.first_point()))
+                    .product();
+                // The weight of coefficient k of r_i is s_i^(-k) / normalizer.
+                domain
+                    .shift
+                    .inverse()
+                    .shifted_powers(normalizer.inverse())
+                    .take(height)
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        let mut randomizers = (1..chunks.len())
+            .map(|_| RowMajorMatrix::rand(&mut *self.rng.borrow_mut(), height, width))
+            .collect_vec();
+        let mut last = RowMajorMatrix::new(Val::zero_vec(height * width), width);
+        for (randomizer, weights) in randomizers.iter().zip(&weights) {
+            for (k, (last_row, row)) in last.rows_mut().zip(randomizer.rows()).enumerate() {
+                for (l, r) in last_row.iter_mut().zip(row) {
+                    *l -= r * weights[k];
+                }
+            }
+        }
+        let last_weights = weights.last().unwrap();
+        for (k, last_row) in last.rows_mut().enumerate() {
+            let inverse_weight = last_weights[k].inverse();
+            for l in last_row {
+                *l *= inverse_weight;
+            }
+        }
+        randomizers.push(last);
+
+        let random_codewords = RowMajorMatrix::rand(
+            &mut *self.rng.borrow_mut(),
+            2 * height,
+            self.num_random_codewords,
+        );
+        let first_domain = doubled(domains[0]);
+
+        let randomized = domains
+            .into_iter()
+            .zip(chunks)
+            .zip(randomizers)
+            .map(|((domain, evals), randomizer)| {
+                (doubled(domain), self.randomize(evals, randomizer))
+            })
+            .chain([(first_domain, random_codewords)])
+            .collect();
+        Pcs::<Challenge, Challenger>::commit(&self.inner, randomized)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val> + 'a {
+        Pcs::<Challenge, Challenger>::get_evaluations_on_domain(
+            &self.inner,
+            prover_data,
+            idx,
+            domain,
+        )
+    }
+
+    fn open(
+        &self,
+        rounds: Vec<(&Self::ProverData, Vec<Vec<Challenge>>)>,
+        challenger: &mut Challenger,
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::OpenError> {
+        self.inner.open(rounds, challenger)
+    }
+
+    fn verify(
+        &self,
+        rounds: Vec<(
+            Self::Commitment,
+            Vec<(Self::Domain, Vec<(Challenge, Vec<Challenge>)>)>,
+        )>,
+        proof: &Self::Proof,
+        challenger: &mut Challenger,
+    ) -> Result<(), Self::Error> {
+        // Every polynomial was committed with twice the degree of its domain.
+        let rounds = rounds
+            .into_iter()
+            .map(|(commit, mats)| {
+                let mats = mats
+                    .into_iter()
+                    .map(|(domain, points)| (doubled(domain), points))
+                    .collect();
+                (commit, mats)
+            })
+            .collect();
+        self.inner.verify(rounds, proof, challenger)
+    }
+
+    fn proof_stats(&self, proof: &Self::Proof) -> PcsProofStats {
+        Pcs::<Challenge, Challenger>::proof_stats(&self.inner, proof)
+    }
+}
@@ -0,0 +1,134 @@
+use alloc::vec;
+use core::marker::PhantomData;
+
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, PcsProofStats};
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::{Dimensions, Matrix};
+use p3_util::binary::encoded_len;
+use serde::{Deserialize, Serialize};
+
+use crate::prover::{self, HighDegreeError};
+use crate::verifier::{self, FriError};
+use crate::{FriConfig, FriGenericConfig, FriProof, TwoAdicFriGenericConfig};
+
+/// A proof from [`prove_low_degree`].
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound(
+    serialize = "Witness: Serialize",
+    deserialize = "Witness: Deserialize<'de>"
+))]
+pub struct LowDegreeProof<F: Field, M: Mmcs<F>, Witness> {
+    pub fri_proof: FriProof<F, M, Witness, CodewordOpening<F, M>>,
+}
+
+/// The opening of the tested codeword at a queried location.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct CodewordOpening<F: Field, M: Mmcs<F>> {
+    pub value: F,
+    pub opening_proof: M::Proof,
+}
+
+impl<F, M, Witness> LowDegreeProof<F, M, Witness>
+where
+    F: Field,
+    M: Mmcs<F>,
+    Witness: Serialize,
+{
+    /// Breaks down the size of this proof by component, and counts the hashes needed to verify
+    /// it.
+    pub fn stats(&self, mmcs: &M) -> PcsProofStats {
+        self.fri_proof.stats(mmcs, |opening, stats| {
+            stats.opened_rows += encoded_len(&opening.value);
+            stats.opened_row_paths += encoded_len(&opening.opening_proof);
+            stats.verifier_hashes += mmcs.num_verifier_hashes(&opening.opening_proof);
+        })
+    }
+}
+
+/// Proves that a codeword is close to a polynomial of degree less than its length divided by
+/// `config.blowup()`.
+///
+/// The codeword must be committed with `config.mmcs`, as a single column such as by
+/// `Mmcs::commit_vec`. It holds the evaluations of the polynomial over a coset of a two-adic
+/// subgroup, in bit-reversed order. The caller must observe its commitment into `challenger`
+/// beforehand, since the proof doesn't bind it to the transcript otherwise.
+///
+/// Returns an error, leaving `challenger` untouched, if the codeword isn't exactly the evaluations
+/// of such a polynomial.
+pub fn prove_low_degree<Val, Challenge, M, Challenger>(
+    config: &FriConfig<M>,
+    codeword: &M::ProverData<RowMajorMatrix<Challenge>>,
+    challenger: &mut Challenger,
+) -> Result<LowDegreeProof<Challenge, M, Challenger::Witness>, HighDegreeError>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let matrices = config.mmcs.get_matrices(codeword);
+    assert_eq!(matrices.len(), 1, "expected a single committed codeword");
+    assert_eq!(matrices[0].width(), 1, "expected a single column");
+    let input = matrices[0].values.clone();
+
+    let g = TwoAdicFriGenericConfig::<Val, CodewordOpening<Challenge, M>, M::Error>(PhantomData);
+    if g.interpolate_final_poly(config.log_blowup, input.clone())
+        .is_none()
+    {
+        return Err(HighDegreeError);
+    }
+    let fri_proof = prover::prove(&g, config, vec![input], challenger, |index| {
+        let (mut opened_values, opening_proof) = config.mmcs.open_batch(index, codeword);
+        CodewordOpening {
+            value: opened_values.pop().unwrap()[0],
+            opening_proof,
+        }
+    })?;
+    Ok(LowDegreeProof { fri_proof })
+}
+
+/// Verifies a proof from [`prove_low_degree`] that the codeword with the given commitment has
+/// `2^log_height` values, and is close to a polynomial of degree less than
+/// `2^log_height / config.blowup()`.
+///
+/// As for the prover, the caller must observe the commitment into `challenger` beforehand, in the
+/// same transcript position.
+pub fn verify_low_degree<Val, Challenge, M, Challenger>(
+    config: &FriConfig<M>,
+    commitment: &M::Commitment,
+    log_height: usize,
+    proof: &LowDegreeProof<Challenge, M, Challenger::Witness>,
+    challenger: &mut Challenger,
+) -> Result<(), FriError<M::Error, M::Error>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+{
+    let g = TwoAdicFriGenericConfig::<Val, CodewordOpening<Challenge, M>, M::Error>(PhantomData);
+    verifier::verify(
+        &g,
+        config,
+        &[log_height],
+        &proof.fri_proof,
+        challenger,
+        |index, opening| {
+            let dims = [Dimensions {
+                width: 1,
+                height: 1 << log_height,
+            }];
+            config.mmcs.verify_batch(
+                commitment,
+                &dims,
+                index,
+                &[vec![opening.value]],
+                &opening.opening_proof,
+            )?;
+            Ok(vec![(log_height, opening.value)])
+        },
+    )
+}
@@ -6,12 +6,19 @@ extern crate alloc;
 
 mod config;
 mod fold_even_odd;
+mod hiding_pcs;
+mod ldt;
 mod proof;
 pub mod prover;
+mod soundness;
 mod two_adic_pcs;
 pub mod verifier;
 
 pub use config::*;
 pub use fold_even_odd::*;
+pub use hiding_pcs::*;
+pub use ldt::*;
 pub use proof::*;
+pub use prover::HighDegreeError;
+pub use soundness::*;
 pub use two_adic_pcs::*;
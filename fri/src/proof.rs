@@ -1,7 +1,8 @@
 use alloc::vec::Vec;
 
-use p3_commit::Mmcs;
+use p3_commit::{Mmcs, PcsProofStats};
 use p3_field::Field;
+use p3_util::binary::encoded_len;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -12,12 +13,44 @@ use serde::{Deserialize, Serialize};
 pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     pub commit_phase_commits: Vec<M::Commitment>,
     pub query_proofs: Vec<QueryProof<F, M, InputProof>>,
-    // This could become Vec<FC::Challenge> if this library was generalized to support non-constant
-    // final polynomials.
-    pub final_poly: F,
+    /// The coefficients of the final polynomial, starting with the constant term.
+    pub final_poly: Vec<F>,
     pub pow_witness: Witness,
 }
 
+impl<F, M, Witness, InputProof> FriProof<F, M, Witness, InputProof>
+where
+    F: Field,
+    M: Mmcs<F>,
+    Witness: Serialize,
+    InputProof: Serialize,
+{
+    /// Breaks down the size of this proof by component, and counts the hashes needed to verify
+    /// it. `input_stats` should add the input proof of a query to the stats.
+    pub fn stats(
+        &self,
+        mmcs: &M,
+        mut input_stats: impl FnMut(&InputProof, &mut PcsProofStats),
+    ) -> PcsProofStats {
+        let mut stats = PcsProofStats {
+            commitments: encoded_len(&self.commit_phase_commits),
+            final_poly: encoded_len(&self.final_poly),
+            num_queries: self.query_proofs.len(),
+            ..Default::default()
+        };
+        for query_proof in &self.query_proofs {
+            input_stats(&query_proof.input_proof, &mut stats);
+            for step in &query_proof.commit_phase_openings {
+                stats.commit_phase_openings += encoded_len(&step.sibling_values);
+                stats.commit_phase_paths += encoded_len(&step.opening_proof);
+                stats.verifier_hashes += mmcs.num_verifier_hashes(&step.opening_proof);
+            }
+        }
+        stats.other = encoded_len(self) - stats.total();
+        stats
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound(
     serialize = "InputProof: Serialize",
@@ -33,10 +66,9 @@ pub struct QueryProof<F: Field, M: Mmcs<F>, InputProof> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The openings of the commit phase codeword at the sibling locations, i.e. the row of the
+    /// queried location without the queried value itself.
+    pub sibling_values: Vec<F>,
 
     pub opening_proof: M::Proof,
 }
@@ -12,6 +12,13 @@ use tracing::{info_span, instrument};
 
 use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, QueryProof};
 
+/// The error returned by the FRI prover when its inputs aren't the evaluations of polynomials of
+/// low enough degree, so that folding them doesn't end in the final polynomial.
+#[derive(Debug, PartialEq, Eq)]
+pub struct HighDegreeError;
+
+/// Returns an error if the inputs aren't of low degree. The challenger has then observed part of
+/// the commit phase, so the transcript can't be reused.
 #[instrument(name = "FRI prover", skip_all)]
 pub fn prove<G, Val, Challenge, M, Challenger>(
     g: &G,
@@ -19,7 +26,7 @@ pub fn prove<G, Val, Challenge, M, Challenger>(
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize) -> G::InputProof,
-) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
+) -> Result<FriProof<Challenge, M, Challenger::Witness, G::InputProof>, HighDegreeError>
 where
     Val: Field,
     Challenge: ExtensionField<Val>,
@@ -27,15 +34,24 @@ where
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
-    // check sorted descending
+    // check sorted strictly descending
     assert!(inputs
         .iter()
         .tuple_windows()
-        .all(|(l, r)| l.len() >= r.len()));
+        .all(|(l, r)| l.len() > r.len()));
+    assert!(
+        inputs.last().unwrap().len() >= config.blowup() * config.final_poly_len(),
+        "FRI inputs must be at least as long as the final codeword"
+    );
 
-    let log_max_height = log2_strict_usize(inputs[0].len());
+    let log_input_heights = inputs
+        .iter()
+        .map(|input| log2_strict_usize(input.len()))
+        .collect_vec();
+    let log_max_height = log_input_heights[0];
+    let log_arities = config.log_arities(&log_input_heights);
 
-    let commit_phase_result = commit_phase(g, config, inputs, challenger);
+    let commit_phase_result = commit_phase(g, config, inputs, &log_arities, challenger)?;
 
     let pow_witness = challenger.grind(config.proof_of_work_bits);
 
@@ -47,24 +63,25 @@ where
                 commit_phase_openings: answer_query(
                     config,
                     &commit_phase_result.data,
+                    &log_arities,
                     index >> g.extra_query_index_bits(),
                 ),
             })
             .collect()
     });
 
-    FriProof {
+    Ok(FriProof {
         commit_phase_commits: commit_phase_result.commits,
         query_proofs,
         final_poly: commit_phase_result.final_poly,
         pow_witness,
-    }
+    })
 }
 
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
-    final_poly: F,
+    final_poly: Vec<F>,
 }
 
 #[instrument(name = "commit phase", skip_all)]
@@ -72,8 +89,9 @@ fn commit_phase<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
     inputs: Vec<Vec<Challenge>>,
+    log_arities: &[usize],
     challenger: &mut Challenger,
-) -> CommitPhaseResult<Challenge, M>
+) -> Result<CommitPhaseResult<Challenge, M>, HighDegreeError>
 where
     Val: Field,
     Challenge: ExtensionField<Val>,
@@ -86,8 +104,8 @@ where
     let mut commits = vec![];
     let mut data = vec![];
 
-    while folded.len() > config.blowup() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+    for &log_arity in log_arities {
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
@@ -104,25 +122,28 @@ where
         }
     }
 
-    // We should be left with `blowup` evaluations of a constant polynomial.
-    assert_eq!(folded.len(), config.blowup());
-    let final_poly = folded[0];
-    for x in folded {
-        assert_eq!(x, final_poly);
+    // We should be left with `blowup` times `final_poly_len` evaluations of a polynomial with
+    // `final_poly_len` coefficients, in bit-reversed order.
+    assert_eq!(folded.len(), config.blowup() * config.final_poly_len());
+    let final_poly = g
+        .interpolate_final_poly(config.log_blowup, folded)
+        .ok_or(HighDegreeError)?;
+    for &coeff in &final_poly {
+        challenger.observe_ext_element(coeff);
     }
-    challenger.observe_ext_element(final_poly);
 
-    CommitPhaseResult {
+    Ok(CommitPhaseResult {
         commits,
         data,
         final_poly,
-    }
+    })
 }
 
 fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CommitPhaseProofStep<F, M>>
 where
     F: Field,
@@ -130,20 +151,23 @@ where
 {
     commit_phase_commits
         .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
+        .zip(log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_in_row = index % (1 << log_arity);
+            index >>= log_arity;
 
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut sibling_values = opened_rows.pop().unwrap();
+            assert_eq!(
+                sibling_values.len(),
+                1 << log_arity,
+                "Committed data should be in rows of the folding arity"
+            );
+            sibling_values.remove(index_in_row);
 
             CommitPhaseProofStep {
-                sibling_value,
+                sibling_values,
                 opening_proof,
             }
         })
@@ -0,0 +1,186 @@
+use p3_field::Field;
+
+use crate::FriConfig;
+
+/// How far from the code FRI is shown to reject codewords, which determines the bounds used by
+/// [`FriConfig::proven_soundness`]. Both regimes follow
+/// [BCIKS20](https://eprint.iacr.org/2020/654).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoundnessRegime {
+    /// Up to the unique decoding radius `(1 - rate) / 2`.
+    UniqueDecoding,
+    /// Up to `1 - sqrt(rate) (1 + 1 / (2m))`, just short of the Johnson bound. A larger `m`
+    /// lets each query reject more, at the cost of a larger commit phase error. `m` must be at
+    /// least 3.
+    Johnson { m: usize },
+}
+
+impl SoundnessRegime {
+    /// The largest fraction of positions on which a rejected codeword may agree with the code,
+    /// i.e. the probability that a single query misses a cheating prover.
+    fn agreement(self, rate: f64) -> f64 {
+        match self {
+            Self::UniqueDecoding => (1.0 + rate) / 2.0,
+            Self::Johnson { m } => libm::sqrt(rate) * (1.0 + 1.0 / (2.0 * m as f64)),
+        }
+    }
+
+    /// The number of codewords which may agree with a word that much.
+    fn list_size(self, rate: f64) -> f64 {
+        match self {
+            Self::UniqueDecoding => 1.0,
+            Self::Johnson { m } => m as f64 / rate,
+        }
+    }
+
+    /// The number of bad challenges for combining two words of length `n` along a line, which
+    /// bounds the error of a random linear combination.
+    fn bad_line_challenges(self, rate: f64, n: f64) -> f64 {
+        match self {
+            Self::UniqueDecoding => n,
+            Self::Johnson { m } => {
+                libm::pow(m as f64 + 0.5, 7.0) / (3.0 * libm::pow(rate, 1.5)) * n * n
+            }
+        }
+    }
+}
+
+/// What FRI's proven soundness depends on besides its [`FriConfig`].
+#[derive(Copy, Clone, Debug)]
+pub struct SoundnessParameters {
+    /// The log of the size of the field which challenges are drawn from, usually an extension
+    /// field.
+    pub field_bits: f64,
+    /// The log of the height of the largest input codeword, i.e. of the largest LDE.
+    pub log_max_height: usize,
+    /// The number of polynomials which are batched into FRI's inputs with powers of a random
+    /// challenge.
+    pub num_polynomials: usize,
+    /// The degree of the numerators of the DEEP quotients, e.g. the trace length times the
+    /// constraint degree. A wrong polynomial agrees with a claimed opening at this many
+    /// out-of-domain points at most. Zero leaves the DEEP error out.
+    pub deep_degree: usize,
+}
+
+impl SoundnessParameters {
+    /// Parameters for challenges drawn from `F`.
+    pub fn new<F: Field>(
+        log_max_height: usize,
+        num_polynomials: usize,
+        deep_degree: usize,
+    ) -> Self {
+        // Keep the top 53 bits of the order, which an `f64` represents exactly.
+        let order = F::order();
+        let shift = order.bits().saturating_sub(53);
+        let top = (order >> shift).to_u64_digits()[0];
+        Self {
+            field_bits: libm::log2(top as f64) + shift as f64,
+            log_max_height,
+            num_polynomials,
+            deep_degree,
+        }
+    }
+}
+
+/// FRI's proven soundness, as returned by [`FriConfig::proven_soundness`]. Each component is
+/// `-log2` of an upper bound on the probability that a cheating prover succeeds in that way.
+#[derive(Copy, Clone, Debug)]
+pub struct ProvenSoundness {
+    /// Bits of security against a lucky batching or folding challenge.
+    pub commit_phase_bits: f64,
+    /// Bits of security against a lucky out-of-domain point.
+    pub deep_bits: f64,
+    /// Bits of security against lucky queries, including proof of work.
+    pub query_phase_bits: f64,
+}
+
+impl ProvenSoundness {
+    /// The overall bits of security, a bit less than the smallest component.
+    pub fn bits(&self) -> f64 {
+        -libm::log2(
+            libm::exp2(-self.commit_phase_bits)
+                + libm::exp2(-self.deep_bits)
+                + libm::exp2(-self.query_phase_bits),
+        )
+    }
+}
+
+impl<M> FriConfig<M> {
+    /// Returns the proven soundness of this FRI instance, under the given regime.
+    ///
+    /// The commit phase error accounts for batching the input polynomials, and for each folding
+    /// round, both of which combine words with powers of a challenge. Combining `a` words this way
+    /// is bounded by `a - 1` times the error of combining two along a line. Rounds are assumed to
+    /// fold by the full arity, which is the worst case.
+    pub fn proven_soundness(
+        &self,
+        regime: SoundnessRegime,
+        params: &SoundnessParameters,
+    ) -> ProvenSoundness {
+        let (commit_phase_bits, deep_bits) = self.commit_phase_and_deep_bits(regime, params);
+        let agreement = regime.agreement(self.rate());
+        ProvenSoundness {
+            commit_phase_bits,
+            deep_bits,
+            query_phase_bits: -libm::log2(agreement) * self.num_queries as f64
+                + self.proof_of_work_bits as f64,
+        }
+    }
+
+    /// Returns the fewest queries for which [`FriConfig::proven_soundness`] reaches `target_bits`
+    /// of security, ignoring this config's `num_queries`, or `None` if the commit phase or DEEP
+    /// errors alone are too large.
+    pub fn num_queries_for_proven_soundness(
+        &self,
+        regime: SoundnessRegime,
+        params: &SoundnessParameters,
+        target_bits: f64,
+    ) -> Option<usize> {
+        let (commit_phase_bits, deep_bits) = self.commit_phase_and_deep_bits(regime, params);
+        let query_error =
+            libm::exp2(-target_bits) - libm::exp2(-commit_phase_bits) - libm::exp2(-deep_bits);
+        if query_error <= 0.0 {
+            return None;
+        }
+        let bits_per_query = -libm::log2(regime.agreement(self.rate()));
+        let query_bits = -libm::log2(query_error) - self.proof_of_work_bits as f64;
+        Some(libm::ceil(query_bits.max(0.0) / bits_per_query) as usize)
+    }
+
+    fn rate(&self) -> f64 {
+        1.0 / self.blowup() as f64
+    }
+
+    fn commit_phase_and_deep_bits(
+        &self,
+        regime: SoundnessRegime,
+        params: &SoundnessParameters,
+    ) -> (f64, f64) {
+        if let SoundnessRegime::Johnson { m } = regime {
+            assert!(m >= 3, "the Johnson regime needs m >= 3");
+        }
+        assert!(
+            self.log_folding_arity > 0,
+            "the folding arity must be at least 2"
+        );
+        let rate = self.rate();
+
+        // The number of bad line challenges each combination counts for.
+        let log_folded = params
+            .log_max_height
+            .saturating_sub(self.log_blowup + self.log_final_poly_len);
+        let full_rounds = log_folded / self.log_folding_arity;
+        let last_round = log_folded % self.log_folding_arity;
+        let combinations = params.num_polynomials.saturating_sub(1)
+            + full_rounds * ((1 << self.log_folding_arity) - 1)
+            + ((1 << last_round) - 1);
+
+        let n = libm::exp2(params.log_max_height as f64);
+        let commit_phase_error = combinations as f64 * regime.bad_line_challenges(rate, n);
+        let deep_error = regime.list_size(rate) * params.deep_degree as f64;
+        (
+            params.field_bits - libm::log2(commit_phase_error),
+            params.field_bits - libm::log2(deep_error),
+        )
+    }
+}
@@ -1,33 +1,38 @@
 use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::cmp::Reverse;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
-use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
-use p3_dft::TwoAdicSubgroupDft;
+use p3_commit::{
+    Mmcs, OpenedValues, Pcs, PcsProofStats, PolynomialSpace, TwoAdicMultiplicativeCoset,
+};
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::{
-    batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, ExtensionField,
-    Field, TwoAdicField,
+    batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, eval_poly,
+    ExtensionField, Field, TwoAdicField,
 };
 use p3_interpolation::interpolate_coset;
 use p3_matrix::bitrev::{BitReversableMatrix, BitReversalPerm};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
+use p3_util::binary::encoded_len;
 use p3_util::linear_map::LinearMap;
 use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits, VecExt};
 use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
+use crate::fold_even_odd::fold_pairs;
 use crate::verifier::{self, FriError};
-use crate::{prover, FriConfig, FriGenericConfig, FriProof};
+use crate::{prover, FriConfig, FriGenericConfig, FriProof, HighDegreeError};
 
 #[derive(Debug)]
 pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
-    dft: Dft,
+    pub(crate) dft: Dft,
     mmcs: InputMmcs,
     fri: FriConfig<FriMmcs>,
     _phantom: PhantomData<Val>,
@@ -51,15 +56,29 @@ pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
     pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
 }
 
-pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
-    pub PhantomData<(InputProof, InputError)>,
+impl<Val: Field, InputMmcs: Mmcs<Val>> BatchOpening<Val, InputMmcs> {
+    /// Adds the size of this opening, and the hashes needed to verify it, to `stats`.
+    pub(crate) fn add_stats(&self, mmcs: &InputMmcs, stats: &mut PcsProofStats) {
+        stats.opened_rows += encoded_len(&self.opened_values);
+        stats.opened_row_paths += encoded_len(&self.opening_proof);
+        stats.verifier_hashes += mmcs.num_verifier_hashes(&self.opening_proof);
+    }
+}
+
+/// The FRI configuration for codewords over two-adic subgroups. `Val` is the base field of the
+/// challenge field, whose packing vectorizes the folding.
+pub struct TwoAdicFriGenericConfig<Val, InputProof, InputError>(
+    pub PhantomData<(Val, InputProof, InputError)>,
 );
 
 pub type TwoAdicFriGenericConfigForMmcs<F, M> =
-    TwoAdicFriGenericConfig<Vec<BatchOpening<F, M>>, <M as Mmcs<F>>::Error>;
+    TwoAdicFriGenericConfig<F, Vec<BatchOpening<F, M>>, <M as Mmcs<F>>::Error>;
 
-impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
-    for TwoAdicFriGenericConfig<InputProof, InputError>
+impl<Val, F, InputProof, InputError: Debug> FriGenericConfig<F>
+    for TwoAdicFriGenericConfig<Val, InputProof, InputError>
+where
+    Val: Field,
+    F: ExtensionField<Val> + TwoAdicField,
 {
     type InputProof = InputProof;
     type InputError = InputError;
@@ -75,60 +94,60 @@ impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
         beta: F,
         evals: impl Iterator<Item = F>,
     ) -> F {
-        let arity = 2;
-        let log_arity = 1;
-        let (e0, e1) = evals
-            .collect_tuple()
-            .expect("TwoAdicFriFolder only supports arity=2");
-        // If performance critical, make this API stateful to avoid this
-        // This is a bit more math than is necessary, but leaving it here
-        // in case we want higher arity in the future
-        let subgroup_start = F::two_adic_generator(log_height + log_arity)
-            .exp_u64(reverse_bits_len(index, log_height) as u64);
-        let mut xs = F::two_adic_generator(log_arity)
-            .shifted_powers(subgroup_start)
-            .take(arity)
-            .collect_vec();
-        reverse_slice_index_bits(&mut xs);
-        assert_eq!(log_arity, 1, "can only interpolate two points for now");
-        // interpolate and evaluate at beta
-        e0 + (beta - xs[0]) * (e1 - e0) / (xs[1] - xs[0])
+        // Folding by 2^k is the same as folding by 2 k times, with the challenges beta, beta^2,
+        // beta^4, etc.
+        let mut evals = evals.collect_vec();
+        let log_arity = log2_strict_usize(evals.len());
+        let mut beta = beta;
+        for i in (0..log_arity).rev() {
+            evals = evals
+                .iter()
+                .tuples()
+                .enumerate()
+                .map(|(j, (&e0, &e1))| fold_pair((index << i) + j, log_height + i, beta, e0, e1))
+                .collect();
+            beta = beta.square();
+        }
+        evals[0]
     }
 
     fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F> {
-        // We use the fact that
-        //     p_e(x^2) = (p(x) + p(-x)) / 2
-        //     p_o(x^2) = (p(x) - p(-x)) / (2 x)
-        // that is,
-        //     p_e(g^(2i)) = (p(g^i) + p(g^(n/2 + i))) / 2
-        //     p_o(g^(2i)) = (p(g^i) - p(g^(n/2 + i))) / (2 g^i)
-        // so
-        //     result(g^(2i)) = p_e(g^(2i)) + beta p_o(g^(2i))
-        //                    = (1/2 + beta/2 g_inv^i) p(g^i)
-        //                    + (1/2 - beta/2 g_inv^i) p(g^(n/2 + i))
-        let g_inv = F::two_adic_generator(log2_strict_usize(m.height()) + 1).inverse();
-        let one_half = F::one().halve();
-        let half_beta = beta * one_half;
-
-        // TODO: vectorize this (after we have packed extension fields)
-
-        // beta/2 times successive powers of g_inv
-        let mut powers = g_inv
-            .shifted_powers(half_beta)
-            .take(m.height())
-            .collect_vec();
-        reverse_slice_index_bits(&mut powers);
+        // As in `fold_row`, we fold by 2 repeatedly.
+        let log_arity = log2_strict_usize(m.width());
+        let mut folded = fold_pairs::<Val, F, _>(beta, m);
+        let mut beta = beta;
+        for _ in 1..log_arity {
+            beta = beta.square();
+            folded = fold_pairs::<Val, F, _>(beta, RowMajorMatrix::new(folded, 2));
+        }
+        folded
+    }
 
-        m.par_rows()
-            .zip(powers)
-            .map(|(mut row, power)| {
-                let (lo, hi) = row.next_tuple().unwrap();
-                (one_half + power) * lo + (one_half - power) * hi
-            })
-            .collect()
+    fn interpolate_final_poly(&self, log_blowup: usize, mut codeword: Vec<F>) -> Option<Vec<F>> {
+        reverse_slice_index_bits(&mut codeword);
+        let mut coeffs = Radix2Dit::default().idft(codeword);
+        let high_coeffs = coeffs.split_off(coeffs.len() >> log_blowup);
+        high_coeffs.iter().all(|c| c.is_zero()).then_some(coeffs)
+    }
+
+    fn eval_final_poly(&self, final_poly: &[F], index: usize, log_height: usize) -> F {
+        // The final codeword is in bit-reversed order.
+        let x =
+            F::two_adic_generator(log_height).exp_u64(reverse_bits_len(index, log_height) as u64);
+        eval_poly(final_poly, x)
     }
 }
 
+/// Folds the evaluations `e0` and `e1` of pair `index` of a codeword, into the value at `index` of
+/// the folded codeword of height `2^log_height`.
+fn fold_pair<F: TwoAdicField>(index: usize, log_height: usize, beta: F, e0: F, e1: F) -> F {
+    // The pair holds evaluations at x and -x.
+    let x =
+        F::two_adic_generator(log_height + 1).exp_u64(reverse_bits_len(index, log_height) as u64);
+    // interpolate and evaluate at beta
+    e0 + (beta - x) * (e1 - e0) / (-x.double())
+}
+
 impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
     for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
 where
@@ -145,6 +164,15 @@ where
     type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
     type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
     type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+    type OpenError = HighDegreeError;
+
+    fn parameters(&self) -> Vec<usize> {
+        self.fri.parameters()
+    }
+
+    fn min_degree(&self) -> usize {
+        self.fri.final_poly_len()
+    }
 
     fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
         let log_n = log2_strict_usize(degree);
@@ -199,7 +227,7 @@ where
             >,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+    ) -> Result<(OpenedValues<Challenge>, Self::Proof), Self::OpenError> {
         /*
 
         A quick rundown of the optimizations in this function:
@@ -334,9 +362,9 @@ where
                     }
                 })
                 .collect()
-        });
+        })?;
 
-        (all_opened_values, fri_proof)
+        Ok((all_opened_values, fri_proof))
     }
 
     fn verify(
@@ -363,77 +391,97 @@ where
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_ext_element();
 
-        let log_global_max_height = proof.commit_phase_commits.len() + self.fri.log_blowup;
+        // The heights of the FRI inputs, one per distinct height of the opened matrices.
+        let log_input_heights = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| log2_strict_usize(domain.size()) + self.fri.log_blowup)
+            .sorted_by_key(|&log_height| Reverse(log_height))
+            .dedup()
+            .collect_vec();
+        let log_global_max_height = *log_input_heights.first().expect("Nothing to open?");
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
-        verifier::verify(&g, &self.fri, proof, challenger, |index, input_proof| {
-            // TODO: separate this out into functions
-
-            // log_height -> (alpha_pow, reduced_opening)
-            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
-
-            for (batch_opening, (batch_commit, mats)) in izip!(input_proof, &rounds) {
-                let batch_heights = mats
-                    .iter()
-                    .map(|(domain, _)| domain.size() << self.fri.log_blowup)
-                    .collect_vec();
-                let batch_dims = batch_heights
-                    .iter()
-                    // TODO: MMCS doesn't really need width; we put 0 for now.
-                    .map(|&height| Dimensions { width: 0, height })
-                    .collect_vec();
-
-                let batch_max_height = batch_heights.iter().max().expect("Empty batch?");
-                let log_batch_max_height = log2_strict_usize(*batch_max_height);
-                let bits_reduced = log_global_max_height - log_batch_max_height;
-                let reduced_index = index >> bits_reduced;
-
-                self.mmcs.verify_batch(
-                    batch_commit,
-                    &batch_dims,
-                    reduced_index,
-                    &batch_opening.opened_values,
-                    &batch_opening.opening_proof,
-                )?;
-                for (mat_opening, (mat_domain, mat_points_and_values)) in
-                    izip!(&batch_opening.opened_values, mats)
-                {
-                    let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
-
-                    let bits_reduced = log_global_max_height - log_height;
-                    let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
-
-                    // todo: this can be nicer with domain methods?
-
-                    let x = Val::generator()
-                        * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
-
-                    let (alpha_pow, ro) = reduced_openings
-                        .entry(log_height)
-                        .or_insert((Challenge::one(), Challenge::zero()));
-
-                    for (z, ps_at_z) in mat_points_and_values {
-                        for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
-                            let quotient = (-p_at_z + p_at_x) / (-*z + x);
-                            *ro += *alpha_pow * quotient;
-                            *alpha_pow *= alpha;
+        verifier::verify(
+            &g,
+            &self.fri,
+            &log_input_heights,
+            proof,
+            challenger,
+            |index, input_proof| {
+                // TODO: separate this out into functions
+
+                // log_height -> (alpha_pow, reduced_opening)
+                let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
+
+                for (batch_opening, (batch_commit, mats)) in izip!(input_proof, &rounds) {
+                    let batch_heights = mats
+                        .iter()
+                        .map(|(domain, _)| domain.size() << self.fri.log_blowup)
+                        .collect_vec();
+                    let batch_dims = batch_heights
+                        .iter()
+                        // TODO: MMCS doesn't really need width; we put 0 for now.
+                        .map(|&height| Dimensions { width: 0, height })
+                        .collect_vec();
+
+                    let batch_max_height = batch_heights.iter().max().expect("Empty batch?");
+                    let log_batch_max_height = log2_strict_usize(*batch_max_height);
+                    let bits_reduced = log_global_max_height - log_batch_max_height;
+                    let reduced_index = index >> bits_reduced;
+
+                    self.mmcs.verify_batch(
+                        batch_commit,
+                        &batch_dims,
+                        reduced_index,
+                        &batch_opening.opened_values,
+                        &batch_opening.opening_proof,
+                    )?;
+                    for (mat_opening, (mat_domain, mat_points_and_values)) in
+                        izip!(&batch_opening.opened_values, mats)
+                    {
+                        let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
+
+                        let bits_reduced = log_global_max_height - log_height;
+                        let rev_reduced_index = reverse_bits_len(index >> bits_reduced, log_height);
+
+                        // todo: this can be nicer with domain methods?
+
+                        let x = Val::generator()
+                            * Val::two_adic_generator(log_height).exp_u64(rev_reduced_index as u64);
+
+                        let (alpha_pow, ro) = reduced_openings
+                            .entry(log_height)
+                            .or_insert((Challenge::one(), Challenge::zero()));
+
+                        for (z, ps_at_z) in mat_points_and_values {
+                            for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
+                                let quotient = (-p_at_z + p_at_x) / (-*z + x);
+                                *ro += *alpha_pow * quotient;
+                                *alpha_pow *= alpha;
+                            }
                         }
                     }
                 }
-            }
 
-            // Return reduced openings descending by log_height.
-            Ok(reduced_openings
-                .into_iter()
-                .rev()
-                .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
-                .collect())
-        })
-        .expect("fri err");
+                // Return reduced openings descending by log_height.
+                Ok(reduced_openings
+                    .into_iter()
+                    .rev()
+                    .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
+                    .collect())
+            },
+        )
+    }
 
-        Ok(())
+    fn proof_stats(&self, proof: &Self::Proof) -> PcsProofStats {
+        proof.stats(&self.fri.mmcs, |input_proof, stats| {
+            for batch_opening in input_proof {
+                batch_opening.add_stats(&self.mmcs, stats);
+            }
+        })
     }
 }
 
@@ -1,4 +1,3 @@
-use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
@@ -18,9 +17,12 @@ pub enum FriError<CommitMmcsErr, InputError> {
     InvalidPowWitness,
 }
 
+/// Verifies a FRI proof for inputs with the given log heights, sorted in descending order. The
+/// folding schedule follows from them and `config`, as in [`FriConfig::log_arities`].
 pub fn verify<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    log_input_heights: &[usize],
     proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
@@ -32,6 +34,23 @@ where
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    if config.log_folding_arity == 0
+        || log_input_heights.is_empty()
+        || !log_input_heights.iter().tuple_windows().all(|(l, r)| l > r)
+        || log_input_heights.last() < Some(&log_final_height)
+    {
+        return Err(FriError::InvalidProofShape);
+    }
+    let log_arities = config.log_arities(log_input_heights);
+    let log_max_height = log_input_heights[0];
+
+    if proof.final_poly.len() != config.final_poly_len()
+        || proof.commit_phase_commits.len() != log_arities.len()
+    {
+        return Err(FriError::InvalidProofShape);
+    }
+
     let betas: Vec<Challenge> = proof
         .commit_phase_commits
         .iter()
@@ -40,9 +59,16 @@ where
             challenger.sample_ext_element()
         })
         .collect();
-    challenger.observe_ext_element(proof.final_poly);
+    for &coeff in &proof.final_poly {
+        challenger.observe_ext_element(coeff);
+    }
 
-    if proof.query_proofs.len() != config.num_queries {
+    if proof.query_proofs.len() != config.num_queries
+        || proof
+            .query_proofs
+            .iter()
+            .any(|qp| qp.commit_phase_openings.len() != proof.commit_phase_commits.len())
+    {
         return Err(FriError::InvalidProofShape);
     }
 
@@ -51,8 +77,6 @@ where
         return Err(FriError::InvalidPowWitness);
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
-
     for qp in &proof.query_proofs {
         let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
         let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
@@ -62,22 +86,20 @@ where
             "reduced openings sorted by height descending"
         );
 
-        let folded_eval = verify_query(
+        verify_query(
             g,
             config,
             index >> g.extra_query_index_bits(),
             izip!(
                 &betas,
+                &log_arities,
                 &proof.commit_phase_commits,
                 &qp.commit_phase_openings
             ),
             ro,
             log_max_height,
+            &proof.final_poly,
         )?;
-
-        if folded_eval != proof.final_poly {
-            return Err(FriError::FinalPolyMismatch);
-        }
     }
 
     Ok(())
@@ -85,6 +107,7 @@ where
 
 type CommitStep<'a, F, M> = (
     &'a F,
+    &'a usize,
     &'a <M as Mmcs<F>>::Commitment,
     &'a CommitPhaseProofStep<F, M>,
 );
@@ -96,7 +119,8 @@ fn verify_query<'a, G, F, M>(
     steps: impl Iterator<Item = CommitStep<'a, F, M>>,
     reduced_openings: Vec<(usize, F)>,
     log_max_height: usize,
-) -> Result<F, FriError<M::Error, G::InputError>>
+    final_poly: &[F],
+) -> Result<(), FriError<M::Error, G::InputError>>
 where
     F: Field,
     M: Mmcs<F> + 'a,
@@ -104,20 +128,26 @@ where
 {
     let mut folded_eval = F::zero();
     let mut ro_iter = reduced_openings.into_iter().peekable();
+    let mut log_height = log_max_height;
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+    for (&beta, &log_arity, comm, opening) in steps {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
+        let arity = 1 << log_arity;
+        if opening.sibling_values.len() != arity - 1 {
+            return Err(FriError::InvalidProofShape);
+        }
+        let index_in_row = index % arity;
+        let index_row = index >> log_arity;
+        let log_folded_height = log_height - log_arity;
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        let mut evals = opening.sibling_values.clone();
+        evals.insert(index_in_row, folded_eval);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: arity,
             height: 1 << log_folded_height,
         }];
         config
@@ -125,22 +155,34 @@ where
             .verify_batch(
                 comm,
                 dims,
-                index_pair,
+                index_row,
                 &[evals.clone()],
                 &opening.opening_proof,
             )
             .map_err(FriError::CommitPhaseMmcsError)?;
 
-        index = index_pair;
+        index = index_row;
+        log_height = log_folded_height;
 
         folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
     }
 
-    debug_assert!(index < config.blowup(), "index was {}", index);
+    if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
+        folded_eval += ro;
+    }
+    // A reduced opening which wasn't folded in had a height skipped by a round.
+    if ro_iter.next().is_some() {
+        return Err(FriError::InvalidProofShape);
+    }
+
     debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
+        index < config.blowup() * config.final_poly_len(),
+        "index was {}",
+        index
     );
+    if folded_eval != g.eval_final_poly(final_poly, index, log_height) {
+        return Err(FriError::FinalPolyMismatch);
+    }
 
-    Ok(folded_eval)
+    Ok(())
 }
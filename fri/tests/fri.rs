@@ -7,7 +7,7 @@ use p3_commit::ExtensionMmcs;
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field};
-use p3_fri::{prover, verifier, FriConfig, TwoAdicFriGenericConfig};
+use p3_fri::{prover, verifier, FriConfig, FriProof, TwoAdicFriGenericConfig};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
@@ -30,7 +30,11 @@ type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type MyFriConfig = FriConfig<ChallengeMmcs>;
 
-fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
+fn get_ldt_for_testing<R: Rng>(
+    rng: &mut R,
+    log_folding_arity: usize,
+    log_final_poly_len: usize,
+) -> (Perm, MyFriConfig) {
     let perm = Perm::new_from_rng_128(
         Poseidon2ExternalMatrixGeneral,
         DiffusionMatrixBabyBear::default(),
@@ -41,6 +45,8 @@ fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
     let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len,
+        log_folding_arity,
         num_queries: 10,
         proof_of_work_bits: 8,
         mmcs,
@@ -48,14 +54,22 @@ fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
     (perm, fri_config)
 }
 
-fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
-    let (perm, fc) = get_ldt_for_testing(rng);
+type MyFriProof = FriProof<Challenge, ChallengeMmcs, Val, Vec<(usize, Challenge)>>;
+
+fn do_test_fri_ldt<R: Rng>(
+    rng: &mut R,
+    log_folding_arity: usize,
+    log_final_poly_len: usize,
+    degree_bits: &[usize],
+) -> MyFriProof {
+    let (perm, fc) = get_ldt_for_testing(rng, log_folding_arity, log_final_poly_len);
     let dft = Radix2Dit::default();
 
     let shift = Val::generator();
 
-    let ldes: Vec<RowMajorMatrix<Val>> = (3..10)
-        .map(|deg_bits| {
+    let ldes: Vec<RowMajorMatrix<Val>> = degree_bits
+        .iter()
+        .map(|&deg_bits| {
             let evals = RowMajorMatrix::<Val>::rand_nonzero(rng, 1 << deg_bits, 16);
             let mut lde = dft.coset_lde_batch(evals, 1, shift);
             reverse_matrix_index_bits(&mut lde);
@@ -94,7 +108,7 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         let log_max_height = log2_strict_usize(input[0].len());
 
         let proof = prover::prove(
-            &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
+            &TwoAdicFriGenericConfig::<Val, Vec<(usize, Challenge)>, ()>(PhantomData),
             &fc,
             input.clone(),
             &mut chal,
@@ -108,7 +122,8 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
                 ro.sort_by_key(|(lh, _)| Reverse(*lh));
                 ro
             },
-        );
+        )
+        .unwrap();
 
         (proof, chal.sample_bits(8))
     };
@@ -116,8 +131,13 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
     let mut v_challenger = Challenger::new(perm);
     let _alpha: Challenge = v_challenger.sample_ext_element();
     verifier::verify(
-        &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
+        &TwoAdicFriGenericConfig::<Val, Vec<(usize, Challenge)>, ()>(PhantomData),
         &fc,
+        &degree_bits
+            .iter()
+            .rev()
+            .map(|&deg_bits| deg_bits + fc.log_blowup)
+            .collect::<Vec<_>>(),
         &proof,
         &mut v_challenger,
         |_index, proof| Ok(proof.clone()),
@@ -129,6 +149,8 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         v_challenger.sample_bits(8),
         "prover and verifier transcript have same state after FRI"
     );
+
+    proof
 }
 
 #[test]
@@ -136,6 +158,36 @@ fn test_fri_ldt() {
     // FRI is kind of flaky depending on indexing luck
     for i in 0..4 {
         let mut rng = ChaCha20Rng::seed_from_u64(i);
-        do_test_fri_ldt(&mut rng);
+        do_test_fri_ldt(&mut rng, 1, 0, &(3..10).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_fri_ldt_higher_arity() {
+    for log_folding_arity in 2..=4 {
+        let mut rng = ChaCha20Rng::seed_from_u64(log_folding_arity as u64);
+        // Leave gaps between the input heights, so that some rounds fold by the full arity.
+        let proof = do_test_fri_ldt(&mut rng, log_folding_arity, 0, &[3, 4, 9]);
+        let sibling_lens = proof.query_proofs[0]
+            .commit_phase_openings
+            .iter()
+            .map(|step| step.sibling_values.len())
+            .collect::<Vec<_>>();
+        assert!(sibling_lens.contains(&((1 << log_folding_arity) - 1)));
+        assert!(sibling_lens.iter().all(|&len| len < 1 << log_folding_arity));
+    }
+}
+
+#[test]
+fn test_fri_ldt_final_poly() {
+    for log_final_poly_len in 1..=3 {
+        let mut rng = ChaCha20Rng::seed_from_u64(log_final_poly_len as u64);
+        let proof = do_test_fri_ldt(
+            &mut rng,
+            2,
+            log_final_poly_len,
+            &(3..10).collect::<Vec<_>>(),
+        );
+        assert_eq!(proof.final_poly.len(), 1 << log_final_poly_len);
     }
 }
@@ -0,0 +1,125 @@
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{CanObserve, CanSampleBits, DuplexChallenger};
+use p3_commit::{ExtensionMmcs, Mmcs};
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::verifier::FriError;
+use p3_fri::{prove_low_degree, verify_low_degree, FriConfig, HighDegreeError};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_util::reverse_slice_index_bits;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type MmcsError = <ChallengeMmcs as Mmcs<Challenge>>::Error;
+
+const LOG_BLOWUP: usize = 1;
+
+fn setup() -> (Perm, FriConfig<ChallengeMmcs>) {
+    let perm = Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut ChaCha20Rng::seed_from_u64(0),
+    );
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
+    let config = FriConfig {
+        log_blowup: LOG_BLOWUP,
+        log_final_poly_len: 1,
+        log_folding_arity: 2,
+        num_queries: 10,
+        proof_of_work_bits: 8,
+        mmcs,
+    };
+    (perm, config)
+}
+
+/// The bit-reversed evaluations of a random polynomial with `degree` coefficients, over a coset of
+/// size `2^log_height`.
+fn codeword(degree: usize, log_height: usize) -> Vec<Challenge> {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    let mut coeffs: Vec<Challenge> = (0..degree).map(|_| rng.gen()).collect();
+    coeffs.resize(1 << log_height, Challenge::zero());
+    let mut evals = Radix2Dit::default().coset_dft(coeffs, Challenge::generator());
+    reverse_slice_index_bits(&mut evals);
+    evals
+}
+
+/// Proves that the codeword is of low degree, and verifies the proof against `log_height` and the
+/// commitment of the codeword, or of `verifier_codeword` if given.
+fn prove_and_verify(
+    codeword: Vec<Challenge>,
+    verifier_codeword: Option<Vec<Challenge>>,
+    log_height: usize,
+) -> Result<(), FriError<MmcsError, MmcsError>> {
+    let (perm, config) = setup();
+
+    let (commitment, prover_data) = config.mmcs.commit_vec(codeword);
+    let mut challenger = Challenger::new(perm.clone());
+    challenger.observe(commitment);
+    let proof = prove_low_degree(&config, &prover_data, &mut challenger).unwrap();
+    let prover_sample = challenger.sample_bits(8);
+
+    let mut challenger = Challenger::new(perm);
+    challenger.observe(commitment);
+    // Only check the openings against the other commitment, so that the transcripts still agree.
+    let commitment = verifier_codeword.map_or(commitment, |other| config.mmcs.commit_vec(other).0);
+    verify_low_degree(&config, &commitment, log_height, &proof, &mut challenger)?;
+    assert_eq!(
+        prover_sample,
+        challenger.sample_bits(8),
+        "prover and verifier transcript have same state after FRI"
+    );
+    Ok(())
+}
+
+#[test]
+fn low_degree() {
+    for log_degree in 2..8 {
+        let log_height = log_degree + LOG_BLOWUP;
+        let codeword = codeword(1 << log_degree, log_height);
+        prove_and_verify(codeword, None, log_height).unwrap();
+    }
+}
+
+#[test]
+fn wrong_height() {
+    // The folding schedule follows from the claimed height, so the openings don't match it.
+    let codeword = codeword(1 << 6, 7);
+    assert!(prove_and_verify(codeword, None, 8).is_err());
+}
+
+#[test]
+fn wrong_commitment() {
+    let other = codeword(1 << 5, 7);
+    let codeword = codeword(1 << 6, 7);
+    assert!(matches!(
+        prove_and_verify(codeword, Some(other), 7),
+        Err(FriError::InputError(_))
+    ));
+}
+
+#[test]
+fn high_degree() {
+    let (perm, config) = setup();
+    let (commitment, prover_data) = config.mmcs.commit_vec(codeword(1 << 7, 7));
+    let mut challenger = Challenger::new(perm);
+    challenger.observe(commitment);
+    assert_eq!(
+        prove_low_degree(&config, &prover_data, &mut challenger).err(),
+        Some(HighDegreeError)
+    );
+}
@@ -1,15 +1,16 @@
 use itertools::{izip, Itertools};
 use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
 use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
-use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
+use p3_commit::{ExtensionMmcs, Mmcs, Pcs, PolynomialSpace};
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, HighDegreeError, TwoAdicFriPcs};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_util::binary::encoded_len;
 use rand::distributions::{Distribution, Standard};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -67,8 +68,11 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
         .map(|log_degrees| vec![vec![zeta]; log_degrees.len()])
         .collect_vec();
     let data_and_points = data_by_round.iter().zip(points_by_round).collect();
-    let (opening_by_round, proof) = pcs.open(data_and_points, &mut p_challenger);
+    let (opening_by_round, proof) = pcs
+        .open(data_and_points, &mut p_challenger)
+        .expect("open err");
     assert_eq!(opening_by_round.len(), num_rounds);
+    assert_eq!(pcs.proof_stats(&proof).total(), encoded_len(&proof));
 
     // Verify the proof.
     let mut v_challenger = challenger.clone();
@@ -169,7 +173,11 @@ mod babybear_fri_pcs {
     type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
-    fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+    fn get_pcs(
+        log_blowup: usize,
+        log_folding_arity: usize,
+        log_final_poly_len: usize,
+    ) -> (MyPcs, Challenger) {
         let perm = Perm::new_from_rng_128(
             Poseidon2ExternalMatrixGeneral,
             DiffusionMatrixBabyBear::default(),
@@ -183,6 +191,8 @@ mod babybear_fri_pcs {
 
         let fri_config = FriConfig {
             log_blowup,
+            log_final_poly_len,
+            log_folding_arity,
             num_queries: 10,
             proof_of_work_bits: 8,
             mmcs: challenge_mmcs,
@@ -193,10 +203,41 @@ mod babybear_fri_pcs {
     }
 
     mod blowup_1 {
-        make_tests_for_pcs!(super::get_pcs(1));
+        make_tests_for_pcs!(super::get_pcs(1, 1, 0));
     }
     mod blowup_2 {
-        make_tests_for_pcs!(super::get_pcs(2));
+        make_tests_for_pcs!(super::get_pcs(2, 1, 0));
+    }
+    mod arity_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 0));
+    }
+    mod arity_8 {
+        make_tests_for_pcs!(super::get_pcs(2, 3, 0));
+    }
+    mod final_poly_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 1, 2));
+    }
+    mod arity_4_final_poly_4 {
+        make_tests_for_pcs!(super::get_pcs(1, 2, 2));
+    }
+
+    #[test]
+    fn open_high_degree() {
+        let (pcs, mut challenger) = get_pcs(1, 1, 0);
+        let perm = Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut seeded_rng(),
+        );
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+
+        // Committing random values directly, rather than their low-degree extension through the
+        // PCS, leaves FRI with inputs it can't fold down to the final polynomial.
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 6, 2);
+        let (_, data) = val_mmcs.commit_matrix(evals);
+        let zeta: Challenge = challenger.sample_ext_element();
+        let result = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger);
+        assert_eq!(result.err(), Some(HighDegreeError));
     }
 }
 
@@ -235,6 +276,8 @@ mod m31_fri_pcs {
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
         let fri_config = FriConfig {
             log_blowup,
+            log_final_poly_len: 0,
+            log_folding_arity: 1,
             num_queries: 10,
             proof_of_work_bits: 8,
             mmcs: challenge_mmcs,
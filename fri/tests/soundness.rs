@@ -0,0 +1,104 @@
+use p3_baby_bear::BabyBear;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::{FriConfig, SoundnessParameters, SoundnessRegime};
+use p3_goldilocks::Goldilocks;
+
+type Challenge = BinomialExtensionField<BabyBear, 4>;
+
+fn config(log_blowup: usize, log_folding_arity: usize, num_queries: usize) -> FriConfig<()> {
+    FriConfig {
+        log_blowup,
+        log_final_poly_len: 0,
+        log_folding_arity,
+        num_queries,
+        proof_of_work_bits: 16,
+        mmcs: (),
+    }
+}
+
+fn params() -> SoundnessParameters {
+    SoundnessParameters::new::<Challenge>(21, 100, 3 << 20)
+}
+
+/// Smaller traces, whose commit phase error leaves room for the Johnson regime.
+fn small_params() -> SoundnessParameters {
+    SoundnessParameters::new::<Challenge>(16, 100, 3 << 15)
+}
+
+#[test]
+fn field_bits() {
+    let bits = SoundnessParameters::new::<Challenge>(21, 100, 0).field_bits;
+    assert!((123.5..124.0).contains(&bits), "{bits}");
+    let bits = SoundnessParameters::new::<Goldilocks>(21, 100, 0).field_bits;
+    assert!((63.99..64.0).contains(&bits), "{bits}");
+}
+
+#[test]
+fn unique_decoding() {
+    // Each query misses with probability (1 + 1/2) / 2 = 3/4.
+    let soundness = config(1, 1, 100).proven_soundness(SoundnessRegime::UniqueDecoding, &params());
+    let query_phase_bits = 100.0 * (4.0f64 / 3.0).log2() + 16.0;
+    assert!((soundness.query_phase_bits - query_phase_bits).abs() < 1e-9);
+    assert!(soundness.commit_phase_bits > 90.0);
+    assert!(soundness.bits() < query_phase_bits);
+}
+
+#[test]
+fn higher_arity_costs_commit_phase_bits() {
+    let regime = SoundnessRegime::UniqueDecoding;
+    let arity_2 = config(1, 1, 100).proven_soundness(regime, &params());
+    let arity_16 = config(1, 4, 100).proven_soundness(regime, &params());
+    assert!(arity_16.commit_phase_bits < arity_2.commit_phase_bits);
+    assert_eq!(arity_16.query_phase_bits, arity_2.query_phase_bits);
+}
+
+#[test]
+fn num_queries_for_target() {
+    for regime in [
+        SoundnessRegime::UniqueDecoding,
+        SoundnessRegime::Johnson { m: 3 },
+    ] {
+        for log_blowup in 1..4 {
+            let config = config(log_blowup, 2, 0);
+            let num_queries = config
+                .num_queries_for_proven_soundness(regime, &small_params(), 60.0)
+                .unwrap();
+
+            let enough = FriConfig {
+                num_queries,
+                ..config
+            };
+            assert!(enough.proven_soundness(regime, &small_params()).bits() >= 60.0);
+            let too_few = FriConfig {
+                num_queries: num_queries - 1,
+                ..enough
+            };
+            assert!(too_few.proven_soundness(regime, &small_params()).bits() < 60.0);
+        }
+    }
+}
+
+#[test]
+fn johnson_needs_fewer_queries() {
+    let config = config(3, 1, 0);
+    let unique_decoding = config
+        .num_queries_for_proven_soundness(SoundnessRegime::UniqueDecoding, &small_params(), 60.0)
+        .unwrap();
+    let johnson = config
+        .num_queries_for_proven_soundness(SoundnessRegime::Johnson { m: 3 }, &small_params(), 60.0)
+        .unwrap();
+    assert!(johnson < unique_decoding);
+}
+
+#[test]
+fn unreachable_target() {
+    // Quartic BabyBear has about 124 bits, which the commit phase error eats into.
+    assert_eq!(
+        config(1, 1, 0).num_queries_for_proven_soundness(
+            SoundnessRegime::UniqueDecoding,
+            &params(),
+            120.0
+        ),
+        None
+    );
+}
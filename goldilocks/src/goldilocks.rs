@@ -15,13 +15,13 @@ use p3_field::{
 use p3_util::{assume, branch_hint};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Goldilocks prime
 const P: u64 = 0xFFFF_FFFF_0000_0001;
 
 /// The prime field known as Goldilocks, defined as `F_p` where `p = 2^64 - 2^32 + 1`.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
 #[repr(transparent)] // Packed field implementations rely on this!
 pub struct Goldilocks {
     /// Not necessarily canonical.
@@ -65,6 +65,39 @@ impl PartialOrd for Goldilocks {
     }
 }
 
+/// The serialized form of a `Goldilocks`, a struct with a single `value` field as in the derived
+/// implementations, so that previously serialized canonical elements still deserialize.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Goldilocks")]
+struct SerializedGoldilocks {
+    value: u64,
+}
+
+impl Serialize for Goldilocks {
+    /// Always writes the canonical representative.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedGoldilocks {
+            value: self.as_canonical_u64(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Goldilocks {
+    /// Only accepts canonical representatives, so that each element has a unique encoding.
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = SerializedGoldilocks::deserialize(d)?.value;
+        if val < P {
+            Ok(Self::new(val))
+        } else {
+            Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(val),
+                &"a canonical field element",
+            ))
+        }
+    }
+}
+
 impl Display for Goldilocks {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         Display::fmt(&self.value, f)
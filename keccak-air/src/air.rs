@@ -1,6 +1,8 @@
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::borrow::Borrow;
 
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, AirColumns, BaseAir, Padding};
 use p3_field::AbstractField;
 use p3_matrix::Matrix;
 
@@ -18,6 +20,17 @@ impl<F> BaseAir<F> for KeccakAir {
     fn width(&self) -> usize {
         NUM_KECCAK_COLS
     }
+
+    fn column_names(&self) -> Vec<String> {
+        KeccakCols::<F>::column_names()
+    }
+
+    /// Each permutation takes `NUM_ROUNDS` rows, so a trace of whole permutations can be padded by
+    /// repeating the last one. [`generate_trace_rows`](crate::generate_trace_rows) doesn't rely on
+    /// this: it pads with permutations of zero inputs instead.
+    fn padding(&self) -> Padding<F> {
+        Padding::RepeatLast(NUM_ROUNDS)
+    }
 }
 
 impl<AB: AirBuilder> Air<AB> for KeccakAir {
@@ -1,6 +1,6 @@
-use core::borrow::{Borrow, BorrowMut};
-use core::mem::{size_of, transmute};
+use core::mem::transmute;
 
+use p3_air::AirColumns;
 use p3_util::indices_arr;
 
 use crate::constants::R;
@@ -13,7 +13,7 @@ use crate::{NUM_ROUNDS, RATE_LIMBS, U64_LIMBS};
 /// Thus, for example, `a_prime` is stored in `y, x, z` order. This departs from the more common
 /// convention of `x, y, z` order, but it has the benefit that input lists map to AIR columns in a
 /// nicer way.
-#[derive(Debug)]
+#[derive(Debug, AirColumns)]
 #[repr(C)]
 pub struct KeccakCols<T> {
     /// The `i`th value is set to 1 if we are in the `i`th round, otherwise 0.
@@ -117,32 +117,11 @@ pub fn output_limb(i: usize) -> usize {
     KECCAK_COL_MAP.a_prime_prime_prime(y, x, limb_index)
 }
 
-pub const NUM_KECCAK_COLS: usize = size_of::<KeccakCols<u8>>();
+pub const NUM_KECCAK_COLS: usize = <KeccakCols<u8> as AirColumns<u8>>::NUM_COLUMNS;
 pub(crate) const KECCAK_COL_MAP: KeccakCols<usize> = make_col_map();
 
+/// A `const` equivalent of `p3_air::column_map`.
 const fn make_col_map() -> KeccakCols<usize> {
     let indices_arr = indices_arr::<NUM_KECCAK_COLS>();
     unsafe { transmute::<[usize; NUM_KECCAK_COLS], KeccakCols<usize>>(indices_arr) }
 }
-
-impl<T> Borrow<KeccakCols<T>> for [T] {
-    fn borrow(&self) -> &KeccakCols<T> {
-        debug_assert_eq!(self.len(), NUM_KECCAK_COLS);
-        let (prefix, shorts, suffix) = unsafe { self.align_to::<KeccakCols<T>>() };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &shorts[0]
-    }
-}
-
-impl<T> BorrowMut<KeccakCols<T>> for [T] {
-    fn borrow_mut(&mut self) -> &mut KeccakCols<T> {
-        debug_assert_eq!(self.len(), NUM_KECCAK_COLS);
-        let (prefix, shorts, suffix) = unsafe { self.align_to_mut::<KeccakCols<T>>() };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &mut shorts[0]
-    }
-}
@@ -137,6 +137,10 @@ where
         self.inner
             .verify_batch(commit, dimensions, index, &opened_salted_values, siblings)
     }
+
+    fn num_verifier_hashes(&self, proof: &Self::Proof) -> usize {
+        self.inner.num_verifier_hashes(&proof.1)
+    }
 }
 
 #[cfg(test)]
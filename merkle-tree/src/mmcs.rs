@@ -192,6 +192,12 @@ where
             Err(RootMismatch)
         }
     }
+
+    /// One hash of the opened rows, and one compression per sibling on the path to the root. Each
+    /// additional matrix height adds another hash and compression.
+    fn num_verifier_hashes(&self, proof: &Self::Proof) -> usize {
+        1 + proof.len()
+    }
 }
 
 #[cfg(test)]
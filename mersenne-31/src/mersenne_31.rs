@@ -14,13 +14,13 @@ use p3_field::{
 };
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// The Mersenne31 prime
 const P: u32 = (1 << 31) - 1;
 
 /// The prime field `F_p` where `p = 2^31 - 1`.
-#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default)]
 #[repr(transparent)] // Packed field implementations rely on this!
 pub struct Mersenne31 {
     /// Not necessarily canonical, but must fit in 31 bits.
@@ -66,6 +66,39 @@ impl PartialOrd for Mersenne31 {
     }
 }
 
+/// The serialized form of a `Mersenne31`, a struct with a single `value` field as in the derived
+/// implementations, so that previously serialized canonical elements still deserialize.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Mersenne31")]
+struct SerializedMersenne31 {
+    value: u32,
+}
+
+impl Serialize for Mersenne31 {
+    /// Always writes the canonical representative.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedMersenne31 {
+            value: self.as_canonical_u32(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mersenne31 {
+    /// Only accepts canonical representatives, so that each element has a unique encoding.
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let val = SerializedMersenne31::deserialize(d)?.value;
+        if val < P {
+            Ok(Self::new(val))
+        } else {
+            Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(val.into()),
+                &"a canonical field element",
+            ))
+        }
+    }
+}
+
 impl Display for Mersenne31 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         Display::fmt(&self.value, f)
@@ -140,9 +140,17 @@ impl<FP: FieldParameters> Serialize for MontyField31<FP> {
 }
 
 impl<'de, FP: FieldParameters> Deserialize<'de> for MontyField31<FP> {
+    /// Only accepts canonical representatives, so that each element has a unique encoding.
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
-        Ok(MontyField31::from_canonical_u32(val))
+        if val < FP::PRIME {
+            Ok(MontyField31::from_canonical_u32(val))
+        } else {
+            Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(val.into()),
+                &"a canonical field element",
+            ))
+        }
     }
 }
 
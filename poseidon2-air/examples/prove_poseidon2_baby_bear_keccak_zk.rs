@@ -4,14 +4,14 @@ use p3_baby_bear::{BabyBear, BabyBearDiffusionMatrixParameters, BabyBearParamete
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_commit::ExtensionMmcs;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::{FriConfig, HidingFriPcs};
 use p3_keccak::{Keccak256Hash, KeccakF};
 use p3_merkle_tree::MerkleTreeHidingMmcs;
 use p3_monty_31::GenericDiffusionMatrixMontyField31;
 use p3_poseidon2::Poseidon2ExternalMatrixGeneral;
 use p3_poseidon2_air::{generate_vectorized_trace_rows, RoundConstants, VectorizedPoseidon2Air};
 use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32To64};
-use p3_uni_stark::{prove, verify, StarkConfig};
+use p3_uni_stark::{prove, setup, verify, StarkConfig};
 use rand::rngs::ThreadRng;
 use rand::{random, thread_rng};
 #[cfg(not(target_env = "msvc"))]
@@ -119,21 +119,26 @@ fn main() -> Result<(), impl Debug> {
 
     let dft = Dft::default();
 
+    // In ZK mode the trace polynomials have twice the degree, so the degree 3 constraints of this
+    // AIR need a blowup of 4.
     let fri_config = FriConfig {
-        log_blowup: 1,
-        num_queries: 100,
+        log_blowup: 2,
+        log_final_poly_len: 0,
+        log_folding_arity: 1,
+        num_queries: 50,
         proof_of_work_bits: 16,
         mmcs: challenge_mmcs,
     };
-    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
-    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    type Pcs = HidingFriPcs<Val, Dft, ValMmcs, ChallengeMmcs, ThreadRng>;
+    let pcs = Pcs::new(dft, val_mmcs, fri_config, 4, thread_rng());
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger, ByteHash>;
+    let config = MyConfig::new(pcs, byte_hash);
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
     let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
 
     let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    verify(&config, &air, &mut challenger, &proof, &vec![])
+    let (_, vk) = setup(&config, &air, 0);
+    verify(&config, &vk, &air, &mut challenger, &proof, &vec![])
 }
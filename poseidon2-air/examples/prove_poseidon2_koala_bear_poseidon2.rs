@@ -6,6 +6,7 @@ use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::Field;
 use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_keccak::Keccak256Hash;
 use p3_koala_bear::{
     DiffusionMatrixKoalaBear, KoalaBear, KoalaBearDiffusionMatrixParameters, KoalaBearParameters,
 };
@@ -14,7 +15,7 @@ use p3_monty_31::GenericDiffusionMatrixMontyField31;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_poseidon2_air::{generate_trace_rows, Poseidon2Air, RoundConstants};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark::{prove, verify, StarkConfig};
+use p3_uni_stark::{prove, setup, verify, StarkConfig};
 use rand::{random, thread_rng};
 #[cfg(not(target_env = "msvc"))]
 use tikv_jemallocator::Jemalloc;
@@ -112,6 +113,8 @@ fn main() -> Result<(), impl Debug> {
 
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len: 0,
+        log_folding_arity: 1,
         num_queries: 100,
         proof_of_work_bits: 16,
         mmcs: challenge_mmcs,
@@ -119,12 +122,13 @@ fn main() -> Result<(), impl Debug> {
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
+    let config = MyConfig::new(pcs, Keccak256Hash);
 
     let mut challenger = Challenger::new(perm.clone());
     let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
 
     let mut challenger = Challenger::new(perm);
-    verify(&config, &air, &mut challenger, &proof, &vec![])
+    let (_, vk) = setup(&config, &air, 0);
+    verify(&config, &vk, &air, &mut challenger, &proof, &vec![])
 }
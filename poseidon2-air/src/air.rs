@@ -1,6 +1,8 @@
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::borrow::Borrow;
 
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, AirColumns, BaseAir, Padding};
 use p3_field::{AbstractField, Field};
 use p3_matrix::Matrix;
 use p3_poseidon2::{DiffusionPermutation, MdsLightPermutation};
@@ -84,6 +86,22 @@ impl<
     fn width(&self) -> usize {
         num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>()
     }
+
+    fn column_names(&self) -> Vec<String> {
+        Poseidon2Cols::<
+            F,
+            WIDTH,
+            SBOX_DEGREE,
+            SBOX_REGISTERS,
+            HALF_FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+        >::column_names()
+    }
+
+    /// Rows are independent permutations, so the last one can be repeated.
+    fn padding(&self) -> Padding<F> {
+        Padding::RepeatLast(1)
+    }
 }
 
 pub(crate) fn eval<
@@ -1,5 +1,4 @@
-use core::borrow::{Borrow, BorrowMut};
-use core::mem::size_of;
+use p3_air::{column_map, AirColumns};
 
 /// Columns for a Poseidon2 AIR which computes one permutation per row.
 ///
@@ -8,6 +7,7 @@ use core::mem::size_of;
 /// rounds we store an [`SBox`] columnset for each state variable, and for the partial rounds we
 /// store only for the first state variable. Because the matrix multiplications are linear
 /// functions, we need only keep auxiliary columns for the S-box computations.
+#[derive(AirColumns)]
 #[repr(C)]
 pub struct Poseidon2Cols<
     T,
@@ -32,6 +32,7 @@ pub struct Poseidon2Cols<
 }
 
 /// Full round columns.
+#[derive(AirColumns)]
 #[repr(C)]
 pub struct FullRound<T, const WIDTH: usize, const SBOX_DEGREE: usize, const SBOX_REGISTERS: usize> {
     /// Possible intermediate results within each S-box.
@@ -41,6 +42,7 @@ pub struct FullRound<T, const WIDTH: usize, const SBOX_DEGREE: usize, const SBOX
 }
 
 /// Partial round columns.
+#[derive(AirColumns)]
 #[repr(C)]
 pub struct PartialRound<
     T,
@@ -60,6 +62,7 @@ pub struct PartialRound<
 /// (not counting the final output). The S-box is checked to ensure that `REGISTERS` is the optimal
 /// number of registers for the given `DEGREE` for the degrees given in the Poseidon2 paper:
 /// `3`, `5`, `7`, and `11`. See `eval_sbox` for more information.
+#[derive(AirColumns)]
 #[repr(C)]
 pub struct SBox<T, const DEGREE: usize, const REGISTERS: usize>(pub [T; REGISTERS]);
 
@@ -70,8 +73,14 @@ pub const fn num_cols<
     const HALF_FULL_ROUNDS: usize,
     const PARTIAL_ROUNDS: usize,
 >() -> usize {
-    size_of::<Poseidon2Cols<u8, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>>(
-    )
+    Poseidon2Cols::<
+        u8,
+        WIDTH,
+        SBOX_DEGREE,
+        SBOX_REGISTERS,
+        HALF_FULL_ROUNDS,
+        PARTIAL_ROUNDS,
+    >::NUM_COLUMNS
 }
 
 pub const fn make_col_map<
@@ -81,88 +90,5 @@ pub const fn make_col_map<
     const HALF_FULL_ROUNDS: usize,
     const PARTIAL_ROUNDS: usize,
 >() -> Poseidon2Cols<usize, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS> {
-    todo!()
-    // let indices_arr = indices_arr::<
-    //     { num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>() },
-    // >();
-    // unsafe {
-    //     transmute::<
-    //         [usize;
-    //             num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>()],
-    //         Poseidon2Cols<
-    //             usize,
-    //             WIDTH,
-    //             SBOX_DEGREE,
-    //             SBOX_REGISTERS,
-    //             HALF_FULL_ROUNDS,
-    //             PARTIAL_ROUNDS,
-    //         >,
-    //     >(indices_arr)
-    // }
-}
-
-impl<
-        T,
-        const WIDTH: usize,
-        const SBOX_DEGREE: usize,
-        const SBOX_REGISTERS: usize,
-        const HALF_FULL_ROUNDS: usize,
-        const PARTIAL_ROUNDS: usize,
-    > Borrow<Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>>
-    for [T]
-{
-    fn borrow(
-        &self,
-    ) -> &Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>
-    {
-        // debug_assert_eq!(self.len(), NUM_COLS);
-        let (prefix, shorts, suffix) = unsafe {
-            self.align_to::<Poseidon2Cols<
-                T,
-                WIDTH,
-                SBOX_DEGREE,
-                SBOX_REGISTERS,
-                HALF_FULL_ROUNDS,
-                PARTIAL_ROUNDS,
-            >>()
-        };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &shorts[0]
-    }
-}
-
-impl<
-        T,
-        const WIDTH: usize,
-        const SBOX_DEGREE: usize,
-        const SBOX_REGISTERS: usize,
-        const HALF_FULL_ROUNDS: usize,
-        const PARTIAL_ROUNDS: usize,
-    >
-    BorrowMut<
-        Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>,
-    > for [T]
-{
-    fn borrow_mut(
-        &mut self,
-    ) -> &mut Poseidon2Cols<T, WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>
-    {
-        // debug_assert_eq!(self.len(), NUM_COLS);
-        let (prefix, shorts, suffix) = unsafe {
-            self.align_to_mut::<Poseidon2Cols<
-                T,
-                WIDTH,
-                SBOX_DEGREE,
-                SBOX_REGISTERS,
-                HALF_FULL_ROUNDS,
-                PARTIAL_ROUNDS,
-            >>()
-        };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &mut shorts[0]
-    }
+    column_map()
 }
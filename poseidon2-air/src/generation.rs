@@ -1,5 +1,6 @@
 use alloc::vec::Vec;
 
+use p3_air::Padding;
 use p3_field::PrimeField;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_maybe_rayon::prelude::*;
@@ -9,6 +10,8 @@ use tracing::instrument;
 use crate::columns::{num_cols, Poseidon2Cols};
 use crate::{FullRound, PartialRound, RoundConstants, SBox};
 
+/// Generates the trace of the permutations of `inputs`, `VECTOR_LEN` per row, padded to a
+/// power-of-two height by repeating the last row.
 #[instrument(name = "generate vectorized Poseidon2 trace", skip_all)]
 pub fn generate_vectorized_trace_rows<
     F: PrimeField,
@@ -28,8 +31,8 @@ pub fn generate_vectorized_trace_rows<
 ) -> RowMajorMatrix<F> {
     let n = inputs.len();
     assert!(
-        n % VECTOR_LEN == 0 && (n / VECTOR_LEN).is_power_of_two(),
-        "Callers expected to pad inputs to VECTOR_LEN times a power of two"
+        n > 0 && n % VECTOR_LEN == 0,
+        "Callers expected to pass a nonzero multiple of VECTOR_LEN inputs"
     );
 
     let nrows = n.div_ceil(VECTOR_LEN);
@@ -61,10 +64,13 @@ pub fn generate_vectorized_trace_rows<
         );
     });
 
+    Padding::RepeatLast(1).pad(&mut trace);
     trace
 }
 
 // TODO: Take generic iterable
+/// Generates the trace of the permutations of `inputs`, padded to a power-of-two height by
+/// repeating the last row.
 #[instrument(name = "generate Poseidon2 trace", skip_all)]
 pub fn generate_trace_rows<
     F: PrimeField,
@@ -82,10 +88,7 @@ pub fn generate_trace_rows<
     internal_linear_layer: &Diffusion,
 ) -> RowMajorMatrix<F> {
     let n = inputs.len();
-    assert!(
-        n.is_power_of_two(),
-        "Callers expected to pad inputs to a power of two"
-    );
+    assert!(n > 0, "Callers expected to pass at least one input");
 
     let ncols = num_cols::<WIDTH, SBOX_DEGREE, SBOX_REGISTERS, HALF_FULL_ROUNDS, PARTIAL_ROUNDS>();
     let mut trace = RowMajorMatrix::new(F::zero_vec(n * ncols), ncols);
@@ -114,6 +117,7 @@ pub fn generate_trace_rows<
         );
     });
 
+    Padding::RepeatLast(1).pad(&mut trace);
     trace
 }
 
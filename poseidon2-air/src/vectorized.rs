@@ -1,6 +1,6 @@
 use core::borrow::{Borrow, BorrowMut};
 
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, BaseAir, Padding};
 use p3_field::Field;
 use p3_matrix::Matrix;
 use p3_poseidon2::{DiffusionPermutation, MdsLightPermutation};
@@ -211,6 +211,11 @@ impl<
     fn width(&self) -> usize {
         self.air.width() * VECTOR_LEN
     }
+
+    /// Rows are independent permutations, so the last one can be repeated.
+    fn padding(&self) -> Padding<F> {
+        Padding::RepeatLast(1)
+    }
 }
 
 impl<
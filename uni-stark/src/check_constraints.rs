@@ -1,63 +1,138 @@
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
-use p3_field::Field;
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPeriodicColumns, AirBuilderWithPublicValues,
+    AirBuilderWithRowSelectors, ExtensionBuilder, InteractionBuilder, PairBuilder,
+    PermutationAirBuilder, RowSelector,
+};
+use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::stack::VerticalPair;
 use p3_matrix::Matrix;
 use tracing::instrument;
 
+use crate::lookup::{eval_interaction, eval_lookup_sum};
+use crate::symbolic_builder::row_selector_index;
+
 #[instrument(name = "check constraints", skip_all)]
-pub(crate) fn check_constraints<F, A>(air: &A, main: &RowMajorMatrix<F>, public_values: &Vec<F>)
-where
+pub(crate) fn check_constraints<F, EF, A>(
+    air: &A,
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    permutation: Option<&RowMajorMatrix<EF>>,
+    permutation_challenges: &[EF],
+    public_values: &Vec<F>,
+) where
     F: Field,
-    A: for<'a> Air<DebugConstraintBuilder<'a, F>>,
+    EF: ExtensionField<F>,
+    A: for<'a> Air<DebugConstraintBuilder<'a, F, EF>>,
 {
     let height = main.height();
+    let lookup_offset = air.permutation_width();
+    let preprocessed = preprocessed.map_or_else(
+        || RowMajorMatrixView::new(&[], 0),
+        |preprocessed| {
+            assert_eq!(
+                preprocessed.height(),
+                height,
+                "preprocessed and main traces must have the same height"
+            );
+            preprocessed.as_view()
+        },
+    );
+    let permutation = permutation.map_or_else(
+        || RowMajorMatrixView::new(&[], 0),
+        |permutation| {
+            assert_eq!(
+                permutation.height(),
+                height,
+                "permutation and main traces must have the same height"
+            );
+            permutation.as_view()
+        },
+    );
 
+    let window_size = air.window_size();
+    let periodic_columns = air.periodic_columns();
+    let row_selectors = air.row_selectors();
     (0..height).for_each(|i| {
         let i_next = (i + 1) % height;
 
-        let local = main.row_slice(i);
-        let next = main.row_slice(i_next);
-        let main = VerticalPair::new(
-            RowMajorMatrixView::new_row(&*local),
-            RowMajorMatrixView::new_row(&*next),
+        let main_window = (0..window_size)
+            .flat_map(|offset| main.row_slice((i + offset) % height).to_vec())
+            .collect::<Vec<_>>();
+        let main = RowMajorMatrixView::new(&main_window, main.width());
+
+        let preprocessed_window = [i, i_next]
+            .into_iter()
+            .flat_map(|row| preprocessed.row_slice(row).to_vec())
+            .collect::<Vec<_>>();
+        let preprocessed = RowMajorMatrixView::new(&preprocessed_window, preprocessed.width());
+
+        let permutation_local = permutation.row_slice(i);
+        let permutation_next = permutation.row_slice(i_next);
+        let permutation = VerticalPair::new(
+            RowMajorMatrixView::new_row(&*permutation_local),
+            RowMajorMatrixView::new_row(&*permutation_next),
         );
 
         let mut builder = DebugConstraintBuilder {
             row_index: i,
             main,
+            preprocessed,
+            permutation,
+            permutation_challenges,
+            lookup_offset,
+            num_interactions: 0,
             public_values,
+            periodic_values: periodic_columns
+                .iter()
+                .map(|column| column[i % column.len()])
+                .collect(),
+            row_selectors: &row_selectors,
             is_first_row: F::from_bool(i == 0),
             is_last_row: F::from_bool(i == height - 1),
             is_transition: F::from_bool(i != height - 1),
+            is_transition_window: F::from_bool(i + window_size - 1 < height),
+            window_size,
         };
 
         air.eval(&mut builder);
+        let num_interactions = builder.num_interactions;
+        eval_lookup_sum(&mut builder, lookup_offset, num_interactions);
     });
 }
 
 /// An `AirBuilder` which asserts that each constraint is zero, allowing any failed constraints to
 /// be detected early.
 #[derive(Debug)]
-pub struct DebugConstraintBuilder<'a, F: Field> {
+pub struct DebugConstraintBuilder<'a, F: Field, EF: ExtensionField<F>> {
     row_index: usize,
-    main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    main: RowMajorMatrixView<'a, F>,
+    preprocessed: RowMajorMatrixView<'a, F>,
+    permutation: VerticalPair<RowMajorMatrixView<'a, EF>, RowMajorMatrixView<'a, EF>>,
+    permutation_challenges: &'a [EF],
+    lookup_offset: usize,
+    num_interactions: usize,
     public_values: &'a [F],
+    periodic_values: Vec<F>,
+    row_selectors: &'a [RowSelector],
     is_first_row: F,
     is_last_row: F,
     is_transition: F,
+    is_transition_window: F,
+    window_size: usize,
 }
 
-impl<'a, F> AirBuilder for DebugConstraintBuilder<'a, F>
+impl<'a, F, EF> AirBuilder for DebugConstraintBuilder<'a, F, EF>
 where
     F: Field,
+    EF: ExtensionField<F>,
 {
     type F = F;
     type Expr = F;
     type Var = F;
-    type M = VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>;
+    type M = RowMajorMatrixView<'a, F>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -74,8 +149,10 @@ where
     fn is_transition_window(&self, size: usize) -> Self::Expr {
         if size == 2 {
             self.is_transition
+        } else if size == self.window_size {
+            self.is_transition_window
         } else {
-            panic!("only supports a window size of 2")
+            panic!("only supports transition windows of size 2 or the AIR's window_size")
         }
     }
 
@@ -99,10 +176,89 @@ where
     }
 }
 
-impl<'a, F: Field> AirBuilderWithPublicValues for DebugConstraintBuilder<'a, F> {
+impl<'a, F: Field, EF: ExtensionField<F>> AirBuilderWithPublicValues
+    for DebugConstraintBuilder<'a, F, EF>
+{
     type PublicVar = Self::F;
 
     fn public_values(&self) -> &[Self::F] {
         self.public_values
     }
 }
+
+impl<'a, F: Field, EF: ExtensionField<F>> AirBuilderWithPeriodicColumns
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    type PeriodicVar = Self::F;
+
+    fn periodic_values(&self) -> &[Self::F] {
+        &self.periodic_values
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> AirBuilderWithRowSelectors
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    fn row_selector(&self, selector: RowSelector) -> Self::Expr {
+        row_selector_index(self.row_selectors, selector);
+        F::from_bool(selector.contains(self.row_index))
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> PairBuilder for DebugConstraintBuilder<'a, F, EF> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> ExtensionBuilder for DebugConstraintBuilder<'a, F, EF> {
+    type EF = EF;
+    type ExprEF = EF;
+    type VarEF = EF;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        assert_eq!(
+            x.into(),
+            EF::zero(),
+            "constraints had nonzero value on row {}",
+            self.row_index
+        );
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> PermutationAirBuilder
+    for DebugConstraintBuilder<'a, F, EF>
+{
+    type MP = VerticalPair<RowMajorMatrixView<'a, EF>, RowMajorMatrixView<'a, EF>>;
+    type RandomVar = EF;
+
+    fn permutation(&self) -> Self::MP {
+        self.permutation
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.permutation_challenges
+    }
+}
+
+impl<'a, F: Field, EF: ExtensionField<F>> InteractionBuilder for DebugConstraintBuilder<'a, F, EF> {
+    fn send<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let column = self.lookup_offset + self.num_interactions;
+        self.num_interactions += 1;
+        eval_interaction(
+            self,
+            column,
+            bus,
+            values.into_iter().map(Into::into),
+            multiplicity.into(),
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::array;
+
+use p3_air::AirBuilder;
+use p3_commit::PolynomialSpace;
+use p3_field::{AbstractExtensionField, AbstractField, Field, PackedValue};
+use p3_matrix::Matrix;
+
+use crate::{
+    Domain, Entry, PackedChallenge, PackedVal, ProverConstraintFolder, StarkGenericConfig,
+    SymbolicDag, SymbolicExpression, SymbolicNode, Val,
+};
+
+/// Where the value of a node is stored while evaluating [`CompiledConstraints`]. Nodes which
+/// depend on permutation columns or challenges are extension field elements; others stay in the
+/// base field.
+#[derive(Copy, Clone, Debug)]
+enum Slot {
+    Base(usize),
+    Ext(usize),
+}
+
+/// An instruction writing to the slot `out`, or folding a constraint into the accumulator.
+/// Operands are base field slots, unless prefixed by `ext`; the `Ext` instructions write to
+/// extension slots.
+#[derive(Copy, Clone, Debug)]
+enum Instruction<F> {
+    /// Loads a variable, selector or constant into a base field slot.
+    Load {
+        leaf: SymbolicNode<F>,
+        out: usize,
+    },
+    Add {
+        x: usize,
+        y: usize,
+        out: usize,
+    },
+    Sub {
+        x: usize,
+        y: usize,
+        out: usize,
+    },
+    Neg {
+        x: usize,
+        out: usize,
+    },
+    Mul {
+        x: usize,
+        y: usize,
+        out: usize,
+    },
+    /// Loads a permutation column or challenge into an extension slot.
+    LoadExt {
+        leaf: SymbolicNode<F>,
+        out: usize,
+    },
+    AddExt {
+        ext_x: usize,
+        ext_y: usize,
+        out: usize,
+    },
+    AddExtBase {
+        ext_x: usize,
+        y: usize,
+        out: usize,
+    },
+    SubExt {
+        ext_x: usize,
+        ext_y: usize,
+        out: usize,
+    },
+    SubExtBase {
+        ext_x: usize,
+        y: usize,
+        out: usize,
+    },
+    SubBaseExt {
+        x: usize,
+        ext_y: usize,
+        out: usize,
+    },
+    NegExt {
+        ext_x: usize,
+        out: usize,
+    },
+    MulExt {
+        ext_x: usize,
+        ext_y: usize,
+        out: usize,
+    },
+    MulExtBase {
+        ext_x: usize,
+        y: usize,
+        out: usize,
+    },
+    Accumulate {
+        x: usize,
+        constraint_index: usize,
+    },
+    AccumulateExt {
+        ext_x: usize,
+        constraint_index: usize,
+    },
+}
+
+/// A flat instruction tape evaluating the nodes of a [`SymbolicDag`] in order, which the prover
+/// runs over packed values in place of `air.eval`.
+///
+/// Slots are reused once the node stored in them has no further uses, so only as many values
+/// are kept around as are live at once, and each constraint is accumulated as soon as it's known.
+///
+/// The symbolic form of extension field constraints is recorded over the base field, so this is
+/// only faithful when extension field values are only added, subtracted and multiplied, as in
+/// the lookup argument's constraints. The prover thus doesn't use it for AIRs with permutation
+/// columns of their own.
+#[derive(Clone, Debug)]
+pub struct CompiledConstraints<F> {
+    instructions: Vec<Instruction<F>>,
+    num_constraints: usize,
+    num_base_slots: usize,
+    num_ext_slots: usize,
+}
+
+impl<F: Field> CompiledConstraints<F> {
+    pub fn new(constraints: &[SymbolicExpression<F>]) -> Self {
+        let dag = SymbolicDag::new(constraints);
+
+        // The index of the last node using each node; constraints are used by themselves, as
+        // they're accumulated right after being evaluated.
+        let mut last_use: Vec<usize> = (0..dag.nodes.len()).collect();
+        for (i, node) in dag.nodes.iter().enumerate() {
+            match *node {
+                SymbolicNode::Add(x, y) | SymbolicNode::Sub(x, y) | SymbolicNode::Mul(x, y) => {
+                    last_use[x] = i;
+                    last_use[y] = i;
+                }
+                SymbolicNode::Neg(x) => last_use[x] = i,
+                _ => {}
+            }
+        }
+        let mut constraints_of_node = vec![Vec::new(); dag.nodes.len()];
+        for (constraint_index, &node) in dag.constraints.iter().enumerate() {
+            constraints_of_node[node].push(constraint_index);
+        }
+
+        let mut allocator = SlotAllocator::default();
+        let mut slots: Vec<Slot> = Vec::with_capacity(dag.nodes.len());
+        let mut instructions = Vec::with_capacity(dag.nodes.len() + dag.constraints.len());
+        for (i, &node) in dag.nodes.iter().enumerate() {
+            let operands = match node {
+                SymbolicNode::Add(x, y) | SymbolicNode::Sub(x, y) | SymbolicNode::Mul(x, y) => {
+                    vec![x, y]
+                }
+                SymbolicNode::Neg(x) => vec![x],
+                _ => vec![],
+            };
+            let is_ext = match node {
+                SymbolicNode::Variable { entry, .. } => {
+                    matches!(entry, Entry::Permutation { .. } | Entry::Challenge)
+                }
+                _ => operands
+                    .iter()
+                    .any(|&operand| matches!(slots[operand], Slot::Ext(_))),
+            };
+            // Operands are read before the result is written, so their slots can be reused for
+            // the result right away.
+            for (j, &operand) in operands.iter().enumerate() {
+                if last_use[operand] == i && !operands[..j].contains(&operand) {
+                    allocator.free(slots[operand]);
+                }
+            }
+            let slot = allocator.alloc(is_ext);
+            slots.push(slot);
+
+            use Slot::{Base, Ext};
+            let out = match slot {
+                Base(out) | Ext(out) => out,
+            };
+            let operands = (
+                operands.first().map(|&x| slots[x]),
+                operands.get(1).map(|&y| slots[y]),
+            );
+            instructions.push(match (node, operands) {
+                (SymbolicNode::Add(..), (Some(Base(x)), Some(Base(y)))) => {
+                    Instruction::Add { x, y, out }
+                }
+                (SymbolicNode::Add(..), (Some(Ext(ext_x)), Some(Ext(ext_y)))) => {
+                    Instruction::AddExt { ext_x, ext_y, out }
+                }
+                (SymbolicNode::Add(..), (Some(Ext(ext_x)), Some(Base(y))))
+                | (SymbolicNode::Add(..), (Some(Base(y)), Some(Ext(ext_x)))) => {
+                    Instruction::AddExtBase { ext_x, y, out }
+                }
+                (SymbolicNode::Sub(..), (Some(Base(x)), Some(Base(y)))) => {
+                    Instruction::Sub { x, y, out }
+                }
+                (SymbolicNode::Sub(..), (Some(Ext(ext_x)), Some(Ext(ext_y)))) => {
+                    Instruction::SubExt { ext_x, ext_y, out }
+                }
+                (SymbolicNode::Sub(..), (Some(Ext(ext_x)), Some(Base(y)))) => {
+                    Instruction::SubExtBase { ext_x, y, out }
+                }
+                (SymbolicNode::Sub(..), (Some(Base(x)), Some(Ext(ext_y)))) => {
+                    Instruction::SubBaseExt { x, ext_y, out }
+                }
+                (SymbolicNode::Neg(..), (Some(Base(x)), _)) => Instruction::Neg { x, out },
+                (SymbolicNode::Neg(..), (Some(Ext(ext_x)), _)) => {
+                    Instruction::NegExt { ext_x, out }
+                }
+                (SymbolicNode::Mul(..), (Some(Base(x)), Some(Base(y)))) => {
+                    Instruction::Mul { x, y, out }
+                }
+                (SymbolicNode::Mul(..), (Some(Ext(ext_x)), Some(Ext(ext_y)))) => {
+                    Instruction::MulExt { ext_x, ext_y, out }
+                }
+                (SymbolicNode::Mul(..), (Some(Ext(ext_x)), Some(Base(y))))
+                | (SymbolicNode::Mul(..), (Some(Base(y)), Some(Ext(ext_x)))) => {
+                    Instruction::MulExtBase { ext_x, y, out }
+                }
+                (leaf, _) if is_ext => Instruction::LoadExt { leaf, out },
+                (leaf, _) => Instruction::Load { leaf, out },
+            });
+
+            for &constraint_index in &constraints_of_node[i] {
+                instructions.push(match slot {
+                    Base(x) => Instruction::Accumulate {
+                        x,
+                        constraint_index,
+                    },
+                    Ext(ext_x) => Instruction::AccumulateExt {
+                        ext_x,
+                        constraint_index,
+                    },
+                });
+            }
+            if last_use[i] == i {
+                allocator.free(slot);
+            }
+        }
+        Self {
+            instructions,
+            num_constraints: dag.constraints.len(),
+            num_base_slots: allocator.num_base_slots,
+            num_ext_slots: allocator.num_ext_slots,
+        }
+    }
+
+    /// The number of constraints.
+    pub fn len(&self) -> usize {
+        self.num_constraints
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_constraints == 0
+    }
+}
+
+impl<F> CompiledConstraints<F> {
+    /// The number of rows over which each instruction is run at once by [`Self::eval`], which is
+    /// enough to amortize dispatching it while keeping the live values in cache.
+    pub const BATCH_ROWS: usize = 64;
+}
+
+impl<F: Field> CompiledConstraints<F> {
+    /// Folds the constraints into the accumulator of each folder, as evaluating the AIR (followed
+    /// by the lookup constraints) with it would.
+    ///
+    /// Each instruction is run over every folder before moving on to the next one, so the cost
+    /// of dispatching it is shared by the whole batch.
+    pub fn eval<SC>(&self, folders: &mut [ProverConstraintFolder<'_, SC>])
+    where
+        SC: StarkGenericConfig,
+        Domain<SC>: PolynomialSpace<Val = F>,
+    {
+        match Self::BATCH_ROWS / PackedVal::<SC>::WIDTH {
+            64.. => self.eval_batches::<SC, 64>(folders),
+            32.. => self.eval_batches::<SC, 32>(folders),
+            16.. => self.eval_batches::<SC, 16>(folders),
+            8.. => self.eval_batches::<SC, 8>(folders),
+            4.. => self.eval_batches::<SC, 4>(folders),
+            2.. => self.eval_batches::<SC, 2>(folders),
+            _ => self.eval_batches::<SC, 1>(folders),
+        }
+    }
+
+    fn eval_batches<SC, const N: usize>(&self, folders: &mut [ProverConstraintFolder<'_, SC>])
+    where
+        SC: StarkGenericConfig,
+        Domain<SC>: PolynomialSpace<Val = F>,
+    {
+        for folders in folders.chunks_mut(N) {
+            self.eval_batch::<SC, N>(folders);
+        }
+    }
+
+    fn eval_batch<SC, const N: usize>(&self, folders: &mut [ProverConstraintFolder<'_, SC>])
+    where
+        SC: StarkGenericConfig,
+        Domain<SC>: PolynomialSpace<Val = F>,
+    {
+        // Lanes past the number of folders are computed on zeros, and ignored.
+        let mut base = vec![[PackedVal::<SC>::zero(); N]; self.num_base_slots];
+        let mut ext = vec![[PackedChallenge::<SC>::zero(); N]; self.num_ext_slots];
+
+        for instruction in &self.instructions {
+            match *instruction {
+                Instruction::Load { leaf, out } => {
+                    for (k, folder) in folders.iter().enumerate() {
+                        base[out][k] = folder.load_base(leaf);
+                    }
+                }
+                Instruction::Add { x, y, out } => {
+                    let (x, y) = (base[x], base[y]);
+                    base[out] = array::from_fn(|k| x[k] + y[k]);
+                }
+                Instruction::Sub { x, y, out } => {
+                    let (x, y) = (base[x], base[y]);
+                    base[out] = array::from_fn(|k| x[k] - y[k]);
+                }
+                Instruction::Neg { x, out } => {
+                    let x = base[x];
+                    base[out] = array::from_fn(|k| -x[k]);
+                }
+                Instruction::Mul { x, y, out } => {
+                    let (x, y) = (base[x], base[y]);
+                    base[out] = array::from_fn(|k| x[k] * y[k]);
+                }
+                Instruction::LoadExt { leaf, out } => {
+                    for (k, folder) in folders.iter().enumerate() {
+                        ext[out][k] = folder.load_ext(leaf);
+                    }
+                }
+                Instruction::AddExt { ext_x, ext_y, out } => {
+                    let (x, y) = (&ext[ext_x], &ext[ext_y]);
+                    ext[out] = array::from_fn(|k| x[k] + y[k]);
+                }
+                Instruction::AddExtBase { ext_x, y, out } => {
+                    let (x, y) = (&ext[ext_x], &base[y]);
+                    ext[out] = array::from_fn(|k| x[k] + y[k]);
+                }
+                Instruction::SubExt { ext_x, ext_y, out } => {
+                    let (x, y) = (&ext[ext_x], &ext[ext_y]);
+                    ext[out] = array::from_fn(|k| x[k] - y[k]);
+                }
+                Instruction::SubExtBase { ext_x, y, out } => {
+                    let (x, y) = (&ext[ext_x], &base[y]);
+                    ext[out] = array::from_fn(|k| x[k] - y[k]);
+                }
+                Instruction::SubBaseExt { x, ext_y, out } => {
+                    let (x, y) = (&base[x], &ext[ext_y]);
+                    ext[out] = array::from_fn(|k| PackedChallenge::<SC>::from_base(x[k]) - y[k]);
+                }
+                Instruction::NegExt { ext_x, out } => {
+                    let x = &ext[ext_x];
+                    ext[out] = array::from_fn(|k| -x[k]);
+                }
+                Instruction::MulExt { ext_x, ext_y, out } => {
+                    let (x, y) = (&ext[ext_x], &ext[ext_y]);
+                    ext[out] = array::from_fn(|k| x[k] * y[k]);
+                }
+                Instruction::MulExtBase { ext_x, y, out } => {
+                    let (x, y) = (&ext[ext_x], &base[y]);
+                    ext[out] = array::from_fn(|k| x[k] * y[k]);
+                }
+                Instruction::Accumulate {
+                    x,
+                    constraint_index,
+                } => {
+                    for (folder, &value) in folders.iter_mut().zip(&base[x]) {
+                        folder.accumulate(constraint_index, value);
+                    }
+                }
+                Instruction::AccumulateExt {
+                    ext_x,
+                    constraint_index,
+                } => {
+                    for (folder, &value) in folders.iter_mut().zip(&ext[ext_x]) {
+                        folder.accumulate(constraint_index, value);
+                    }
+                }
+            }
+        }
+
+        for folder in folders {
+            folder.constraint_index += self.num_constraints;
+        }
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> ProverConstraintFolder<'a, SC> {
+    fn accumulate<T>(&mut self, constraint_index: usize, value: T)
+    where
+        PackedChallenge<SC>: core::ops::Mul<T, Output = PackedChallenge<SC>>,
+    {
+        let alpha_power = PackedChallenge::<SC>::from_f(
+            self.alpha_powers[self.constraint_index + constraint_index],
+        );
+        self.accumulator += alpha_power * value;
+    }
+
+    fn load_base(&self, leaf: SymbolicNode<Val<SC>>) -> PackedVal<SC> {
+        match leaf {
+            SymbolicNode::Variable { entry, index } => match entry {
+                Entry::Main { offset } => self.main.get(offset, index),
+                Entry::Preprocessed { offset } => self.preprocessed.get(offset, index),
+                Entry::Public => self.public_values[index].into(),
+                Entry::Periodic => self.periodic_values[index],
+                Entry::RowSelector => self.row_selector_values[index],
+                Entry::Permutation { .. } | Entry::Challenge => {
+                    unreachable!("permutation values are loaded into the extension")
+                }
+            },
+            SymbolicNode::IsFirstRow => self.is_first_row,
+            SymbolicNode::IsLastRow => self.is_last_row,
+            SymbolicNode::IsTransition => self.is_transition,
+            SymbolicNode::IsTransitionWindow(size) => self.is_transition_window(size),
+            SymbolicNode::Constant(c) => c.into(),
+            _ => unreachable!("only leaves are loaded"),
+        }
+    }
+
+    fn load_ext(&self, leaf: SymbolicNode<Val<SC>>) -> PackedChallenge<SC> {
+        match leaf {
+            SymbolicNode::Variable {
+                entry: Entry::Permutation { offset },
+                index,
+            } => self.permutation.get(offset, index),
+            SymbolicNode::Variable {
+                entry: Entry::Challenge,
+                index,
+            } => self.permutation_challenges[index],
+            _ => unreachable!("only permutation values are loaded into the extension"),
+        }
+    }
+}
+
+/// Hands out slots, reusing freed ones before growing the number of slots.
+#[derive(Default)]
+struct SlotAllocator {
+    free_base: Vec<usize>,
+    free_ext: Vec<usize>,
+    num_base_slots: usize,
+    num_ext_slots: usize,
+}
+
+impl SlotAllocator {
+    fn alloc(&mut self, is_ext: bool) -> Slot {
+        if is_ext {
+            Slot::Ext(self.free_ext.pop().unwrap_or_else(|| {
+                self.num_ext_slots += 1;
+                self.num_ext_slots - 1
+            }))
+        } else {
+            Slot::Base(self.free_base.pop().unwrap_or_else(|| {
+                self.num_base_slots += 1;
+                self.num_base_slots - 1
+            }))
+        }
+    }
+
+    fn free(&mut self, slot: Slot) {
+        match slot {
+            Slot::Base(x) => self.free_base.push(x),
+            Slot::Ext(x) => self.free_ext.push(x),
+        }
+    }
+}
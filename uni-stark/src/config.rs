@@ -1,8 +1,10 @@
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{ExtensionField, Field};
+use p3_symmetric::CryptographicHasher;
 
 pub type PcsError<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
@@ -14,6 +16,16 @@ pub type Domain<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenger,
 >>::Domain;
 
+pub type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
+
+pub type PcsProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
+
 pub type Val<SC> = <Domain<SC> as PolynomialSpace>::Val;
 
 pub type PackedVal<SC> = <Val<SC> as Field>::Packing;
@@ -33,37 +45,79 @@ pub trait StarkGenericConfig {
         + CanObserve<<Self::Pcs as Pcs<Self::Challenge, Self::Challenger>>::Commitment>
         + CanSample<Self::Challenge>;
 
+    /// The hash function used to compute the [`statement_digest`](crate::statement_digest).
+    type StatementHasher: CryptographicHasher<u8, [u8; 32]>;
+
     fn pcs(&self) -> &Self::Pcs;
+
+    fn statement_hasher(&self) -> &Self::StatementHasher;
+
+    /// Whether proofs are zero-knowledge, which is opted into by using a hiding PCS.
+    ///
+    /// In ZK mode every committed polynomial has its degree doubled.
+    fn is_zk(&self) -> bool {
+        <Self::Pcs as Pcs<Self::Challenge, Self::Challenger>>::ZK
+    }
+
+    /// A domain-separation label, which is absorbed into every transcript as part of the
+    /// [`statement_digest`](crate::statement_digest), so that proofs made for one application
+    /// aren't valid for another which happens to use the same AIR and configuration.
+    fn label(&self) -> &[u8] {
+        &[]
+    }
 }
 
 #[derive(Debug)]
-pub struct StarkConfig<Pcs, Challenge, Challenger> {
+pub struct StarkConfig<Pcs, Challenge, Challenger, StatementHasher> {
     pcs: Pcs,
+    statement_hasher: StatementHasher,
+    label: Vec<u8>,
     _phantom: PhantomData<(Challenge, Challenger)>,
 }
 
-impl<Pcs, Challenge, Challenger> StarkConfig<Pcs, Challenge, Challenger> {
-    pub const fn new(pcs: Pcs) -> Self {
+impl<Pcs, Challenge, Challenger, StatementHasher>
+    StarkConfig<Pcs, Challenge, Challenger, StatementHasher>
+{
+    pub const fn new(pcs: Pcs, statement_hasher: StatementHasher) -> Self {
         Self {
             pcs,
+            statement_hasher,
+            label: Vec::new(),
             _phantom: PhantomData,
         }
     }
+
+    /// Sets the domain-separation label; see [`StarkGenericConfig::label`].
+    pub fn with_label(mut self, label: impl Into<Vec<u8>>) -> Self {
+        self.label = label.into();
+        self
+    }
 }
 
-impl<Pcs, Challenge, Challenger> StarkGenericConfig for StarkConfig<Pcs, Challenge, Challenger>
+impl<Pcs, Challenge, Challenger, StatementHasher> StarkGenericConfig
+    for StarkConfig<Pcs, Challenge, Challenger, StatementHasher>
 where
     Challenge: ExtensionField<<Pcs::Domain as PolynomialSpace>::Val>,
     Pcs: p3_commit::Pcs<Challenge, Challenger>,
     Challenger: FieldChallenger<<Pcs::Domain as PolynomialSpace>::Val>
         + CanObserve<<Pcs as p3_commit::Pcs<Challenge, Challenger>>::Commitment>
         + CanSample<Challenge>,
+    StatementHasher: CryptographicHasher<u8, [u8; 32]>,
 {
     type Pcs = Pcs;
     type Challenge = Challenge;
     type Challenger = Challenger;
+    type StatementHasher = StatementHasher;
 
     fn pcs(&self) -> &Self::Pcs {
         &self.pcs
     }
+
+    fn statement_hasher(&self) -> &Self::StatementHasher {
+        &self.statement_hasher
+    }
+
+    fn label(&self) -> &[u8] {
+        &self.label
+    }
 }
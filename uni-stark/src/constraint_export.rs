@@ -0,0 +1,360 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use p3_air::Air;
+use p3_field::Field;
+use serde::Serialize;
+
+use crate::{
+    get_symbolic_constraints, Entry, SymbolicAirBuilder, SymbolicDag, SymbolicExpression,
+    SymbolicNode,
+};
+
+/// A condition under which a constraint is enforced, as added by `AirBuilder::when_first_row` and
+/// the like.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Guard {
+    FirstRow,
+    LastRow,
+    Transition,
+    TransitionWindow(usize),
+    /// The selector `BaseAir::row_selectors()[index]`.
+    RowSelector(usize),
+}
+
+impl Guard {
+    fn of<F>(expr: &SymbolicExpression<F>) -> Option<Self> {
+        match expr {
+            SymbolicExpression::IsFirstRow => Some(Self::FirstRow),
+            SymbolicExpression::IsLastRow => Some(Self::LastRow),
+            SymbolicExpression::IsTransition => Some(Self::Transition),
+            SymbolicExpression::IsTransitionWindow(size) => Some(Self::TransitionWindow(*size)),
+            SymbolicExpression::Variable(v) if v.entry == Entry::RowSelector => {
+                Some(Self::RowSelector(v.index))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FirstRow => write!(f, "first row"),
+            Self::LastRow => write!(f, "last row"),
+            Self::Transition => write!(f, "transition"),
+            Self::TransitionWindow(size) => write!(f, "transition window {size}"),
+            Self::RowSelector(index) => write!(f, "selector[{index}]"),
+        }
+    }
+}
+
+/// A constraint of an [`ExportedConstraints`].
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportedConstraint {
+    /// The conditions under which the constraint is enforced; it applies to every row if empty.
+    pub guards: Vec<Guard>,
+    /// The node of the expression which must vanish when the guards hold.
+    pub body: usize,
+    /// The degree of the whole constraint, guards included, as a multiple of the trace length.
+    pub degree: usize,
+    /// The body as a formula, as displayed by [`ExportedConstraints`].
+    pub formula: String,
+}
+
+/// The constraints of an AIR in a form meant for people and external tools.
+///
+/// The `Display` implementation prints one constraint per line, with its guards and degree, which
+/// is convenient for diffing constraint systems. [`ExportedConstraints::to_json`] gives the same
+/// constraints with their bodies as a [`SymbolicDag`], whose nodes refer to their operands by index.
+#[derive(Clone, Debug, Serialize)]
+#[serde(bound = "")]
+pub struct ExportedConstraints<F: Field> {
+    pub preprocessed_width: usize,
+    pub main_width: usize,
+    pub num_public_values: usize,
+    /// The names of the main columns, or an empty list if they have none.
+    pub column_names: Vec<String>,
+    pub nodes: Vec<SymbolicNode<F>>,
+    pub constraints: Vec<ExportedConstraint>,
+}
+
+/// Exports the constraints of `air`, as given by [`get_symbolic_constraints`], referring to the main
+/// columns by their `BaseAir::column_names` if any.
+pub fn export_constraints<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> ExportedConstraints<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let constraints = get_symbolic_constraints(air, preprocessed_width, num_public_values);
+    let (guards, bodies): (Vec<_>, Vec<_>) = constraints
+        .iter()
+        .map(|constraint| {
+            let mut guards = Vec::new();
+            let mut body = constraint;
+            while let SymbolicExpression::Mul { x, y, .. } = body {
+                if let Some(guard) = Guard::of(x) {
+                    guards.push(guard);
+                    body = y;
+                } else if let Some(guard) = Guard::of(y) {
+                    guards.push(guard);
+                    body = x;
+                } else {
+                    break;
+                }
+            }
+            (guards, body.clone())
+        })
+        .unzip();
+    let dag = SymbolicDag::new(&bodies);
+
+    let constraints = guards
+        .into_iter()
+        .zip(&constraints)
+        .zip(bodies.iter().zip(dag.constraints))
+        .map(|((guards, constraint), (body, node))| ExportedConstraint {
+            guards,
+            body: node,
+            degree: constraint.degree_multiple(),
+            formula: body.to_string(),
+        })
+        .collect();
+    let exported = ExportedConstraints {
+        preprocessed_width,
+        main_width: air.width(),
+        num_public_values,
+        column_names: Vec::new(),
+        nodes: dag.nodes,
+        constraints,
+    };
+    let column_names = air.column_names();
+    if column_names.is_empty() {
+        exported
+    } else {
+        exported.with_column_names(column_names)
+    }
+}
+
+impl<F: Field> ExportedConstraints<F> {
+    /// Refers to the main columns by the given names rather than by index.
+    pub fn with_column_names<S: Into<String>>(
+        mut self,
+        names: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.column_names = names.into_iter().map(Into::into).collect();
+        assert_eq!(
+            self.column_names.len(),
+            self.main_width,
+            "expected one name per main column"
+        );
+        for constraint in &mut self.constraints {
+            constraint.formula = ExprDisplay {
+                nodes: &self.nodes,
+                node: constraint.body,
+                column_names: &self.column_names,
+            }
+            .to_string();
+        }
+        self
+    }
+
+    /// The largest degree of a constraint.
+    pub fn max_degree(&self) -> usize {
+        self.constraints.iter().map(|c| c.degree).max().unwrap_or(0)
+    }
+
+    /// The main columns read by the given constraint, in increasing order.
+    pub fn main_columns(&self, constraint_index: usize) -> Vec<usize> {
+        let mut reached = vec![false; self.nodes.len()];
+        reached[self.constraints[constraint_index].body] = true;
+        let mut columns = Vec::new();
+        // Operands come before the nodes using them, so a reverse scan visits every reached node.
+        for id in (0..self.nodes.len()).rev() {
+            if !reached[id] {
+                continue;
+            }
+            match self.nodes[id] {
+                SymbolicNode::Variable {
+                    entry: Entry::Main { .. },
+                    index,
+                } => columns.push(index),
+                SymbolicNode::Add(x, y) | SymbolicNode::Sub(x, y) | SymbolicNode::Mul(x, y) => {
+                    reached[x] = true;
+                    reached[y] = true;
+                }
+                SymbolicNode::Neg(x) => reached[x] = true,
+                _ => {}
+            }
+        }
+        columns.sort_unstable();
+        columns.dedup();
+        columns
+    }
+
+    /// Serializes the constraints as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("constraints are serializable")
+    }
+}
+
+impl<F: Field> fmt::Display for ExportedConstraints<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} constraints of degree at most {}",
+            self.constraints.len(),
+            self.max_degree()
+        )?;
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            writeln!(f, "{i}: {constraint}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExportedConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, guard) in self.guards.iter().enumerate() {
+            let separator = if i == 0 { "when" } else { " and" };
+            write!(f, "{separator} {guard}")?;
+        }
+        if !self.guards.is_empty() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{} = 0  (degree {})", self.formula, self.degree)
+    }
+}
+
+/// Displays the expression, using `x'` for `x` in the next row. Main columns are shown as
+/// `main[i]`; see [`ExportedConstraints::with_column_names`] to name them.
+impl<F: Field> fmt::Display for SymbolicExpression<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dag = SymbolicDag::new(core::slice::from_ref(self));
+        ExprDisplay {
+            nodes: &dag.nodes,
+            node: dag.constraints[0],
+            column_names: &[],
+        }
+        .fmt(f)
+    }
+}
+
+/// Displays a node of a DAG as a formula, expanding shared nodes.
+struct ExprDisplay<'a, F> {
+    nodes: &'a [SymbolicNode<F>],
+    node: usize,
+    column_names: &'a [String],
+}
+
+/// How tightly an operator binds, for deciding where parentheses are needed.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Product,
+    Atom,
+}
+
+impl<F: Field> ExprDisplay<'_, F> {
+    fn precedence(&self, node: usize) -> Precedence {
+        match self.nodes[node] {
+            SymbolicNode::Add(..) | SymbolicNode::Sub(..) => Precedence::Sum,
+            SymbolicNode::Mul(..) => Precedence::Product,
+            // Negations are printed as `-x`, which can't follow another operator unparenthesized.
+            SymbolicNode::Neg(_) => Precedence::Sum,
+            SymbolicNode::Constant(c) if is_negative(&c) => Precedence::Sum,
+            _ => Precedence::Atom,
+        }
+    }
+
+    /// Writes `node`, in parentheses if it binds less tightly than `min`.
+    fn write_operand(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        node: usize,
+        min: Precedence,
+    ) -> fmt::Result {
+        let operand = ExprDisplay { node, ..*self };
+        if self.precedence(node) < min {
+            write!(f, "({operand})")
+        } else {
+            write!(f, "{operand}")
+        }
+    }
+
+    fn write_variable(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        entry: Entry,
+        index: usize,
+    ) -> fmt::Result {
+        let offset = match entry {
+            Entry::Main { offset } => {
+                match self.column_names.get(index) {
+                    Some(name) => write!(f, "{name}")?,
+                    None => write!(f, "main[{index}]")?,
+                }
+                offset
+            }
+            Entry::Preprocessed { offset } => {
+                write!(f, "preprocessed[{index}]")?;
+                offset
+            }
+            Entry::Permutation { offset } => {
+                write!(f, "permutation[{index}]")?;
+                offset
+            }
+            Entry::Public => return write!(f, "public[{index}]"),
+            Entry::Periodic => return write!(f, "periodic[{index}]"),
+            Entry::RowSelector => return write!(f, "selector[{index}]"),
+            Entry::Challenge => return write!(f, "challenge[{index}]"),
+        };
+        for _ in 0..offset {
+            write!(f, "'")?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> fmt::Display for ExprDisplay<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.nodes[self.node] {
+            SymbolicNode::Variable { entry, index } => self.write_variable(f, entry, index),
+            SymbolicNode::IsFirstRow => write!(f, "is_first_row"),
+            SymbolicNode::IsLastRow => write!(f, "is_last_row"),
+            SymbolicNode::IsTransition => write!(f, "is_transition"),
+            SymbolicNode::IsTransitionWindow(size) => write!(f, "is_transition_window({size})"),
+            SymbolicNode::Constant(c) if is_negative(&c) => write!(f, "-{}", -c),
+            SymbolicNode::Constant(c) => write!(f, "{c}"),
+            SymbolicNode::Add(x, y) => {
+                self.write_operand(f, x, Precedence::Sum)?;
+                write!(f, " + ")?;
+                self.write_operand(f, y, Precedence::Product)
+            }
+            SymbolicNode::Sub(x, y) => {
+                self.write_operand(f, x, Precedence::Sum)?;
+                write!(f, " - ")?;
+                self.write_operand(f, y, Precedence::Product)
+            }
+            SymbolicNode::Neg(x) => {
+                write!(f, "-")?;
+                self.write_operand(f, x, Precedence::Atom)
+            }
+            SymbolicNode::Mul(x, y) => {
+                self.write_operand(f, x, Precedence::Product)?;
+                write!(f, " * ")?;
+                self.write_operand(f, y, Precedence::Atom)
+            }
+        }
+    }
+}
+
+/// Whether `c` is more readable as the negation of `-c`, like `-1` rather than `p - 1`.
+fn is_negative<F: Field>(c: &F) -> bool {
+    (-*c).to_string().len() < c.to_string().len()
+}
@@ -0,0 +1,291 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPeriodicColumns, AirBuilderWithPublicValues,
+    AirBuilderWithRowSelectors, PairBuilder, RowSelector,
+};
+use p3_field::Field;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+use crate::symbolic_builder::row_selector_index;
+use crate::{get_symbolic_constraints, SymbolicAirBuilder, SymbolicExpression};
+
+/// A constraint which doesn't vanish on some row of a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintFailure<F> {
+    pub row: usize,
+    /// The index of the constraint, in the order in which the AIR asserts its constraints.
+    pub constraint_index: usize,
+    /// The value of the constraint on `row`, which should have been zero.
+    pub value: F,
+}
+
+/// The result of [`check_constraints_report`].
+#[derive(Clone, Debug)]
+pub struct ConstraintReport<F: Field> {
+    /// Every constraint of the AIR, indexed by [`ConstraintFailure::constraint_index`].
+    pub constraints: Vec<SymbolicExpression<F>>,
+    /// Every failed constraint, ordered by row and then by constraint index.
+    pub failures: Vec<ConstraintFailure<F>>,
+}
+
+impl<F: Field> ConstraintReport<F> {
+    /// Whether every constraint vanished on every row.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The symbolic form of the constraint which failed.
+    pub fn constraint(&self, failure: &ConstraintFailure<F>) -> &SymbolicExpression<F> {
+        &self.constraints[failure.constraint_index]
+    }
+
+    /// The rows on which the given constraint failed, in increasing order.
+    pub fn failing_rows(&self, constraint_index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.failures
+            .iter()
+            .filter(move |failure| failure.constraint_index == constraint_index)
+            .map(|failure| failure.row)
+    }
+}
+
+/// Evaluates every constraint of `air` on every row of `main`, collecting those which don't
+/// vanish instead of panicking on the first one, as the prover's debug check does.
+///
+/// Unlike that check, this is available in release builds, so it can be used on large traces.
+/// The AIR's preprocessed trace, if any, is taken from `air.preprocessed_trace()`. AIRs with
+/// permutation columns or interactions aren't supported, as their constraints depend on
+/// challenges.
+#[instrument(name = "check constraints with report", skip_all)]
+pub fn check_constraints_report<F, A>(
+    air: &A,
+    main: &RowMajorMatrix<F>,
+    public_values: &[F],
+) -> ConstraintReport<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>> + for<'a> Air<ConstraintReportBuilder<'a, F>>,
+{
+    check_constraints_on_rows(air, main, public_values, 0..main.height())
+}
+
+/// Like [`check_constraints_report`], but only evaluates the constraints on the given rows.
+pub(crate) fn check_constraints_on_rows<F, A>(
+    air: &A,
+    main: &RowMajorMatrix<F>,
+    public_values: &[F],
+    rows: Range<usize>,
+) -> ConstraintReport<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>> + for<'a> Air<ConstraintReportBuilder<'a, F>>,
+{
+    let checker = RowChecker::new(air, main.height());
+    let constraints =
+        get_symbolic_constraints::<F, A>(air, checker.preprocessed_width(), public_values.len());
+    let width = main.width();
+    let failures = rows
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            checker.failures(i, public_values, |row| &main.values[row * width..][..width])
+        })
+        .collect();
+
+    ConstraintReport {
+        constraints,
+        failures,
+    }
+}
+
+/// Evaluates an AIR's constraints on single rows of main traces of a given height, reading each
+/// row of the main trace through a closure so that callers can substitute modified rows.
+pub(crate) struct RowChecker<'a, F, A> {
+    air: &'a A,
+    height: usize,
+    window_size: usize,
+    main_width: usize,
+    preprocessed: Option<RowMajorMatrix<F>>,
+    periodic_columns: Vec<Vec<F>>,
+    row_selectors: Vec<RowSelector>,
+}
+
+impl<'a, F, A> RowChecker<'a, F, A>
+where
+    F: Field,
+    A: for<'b> Air<ConstraintReportBuilder<'b, F>>,
+{
+    pub(crate) fn new(air: &'a A, height: usize) -> Self {
+        let preprocessed = air.preprocessed_trace();
+        if let Some(preprocessed) = &preprocessed {
+            assert_eq!(
+                preprocessed.height(),
+                height,
+                "preprocessed and main traces must have the same height"
+            );
+        }
+        Self {
+            air,
+            height,
+            window_size: air.window_size(),
+            main_width: air.width(),
+            preprocessed,
+            periodic_columns: air.periodic_columns(),
+            row_selectors: air.row_selectors(),
+        }
+    }
+
+    pub(crate) fn preprocessed_width(&self) -> usize {
+        self.preprocessed
+            .as_ref()
+            .map_or(0, |preprocessed| preprocessed.width())
+    }
+
+    /// The rows whose constraints read row `row` of the main trace.
+    #[cfg(feature = "test-utils")]
+    pub(crate) fn rows_reading(&self, row: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.window_size.min(self.height))
+            .map(move |offset| (row + self.height - offset) % self.height)
+    }
+
+    /// The constraints which don't vanish on row `i`, where `main_row(j)` is row `j` of the main
+    /// trace.
+    pub(crate) fn failures<'m>(
+        &self,
+        i: usize,
+        public_values: &[F],
+        main_row: impl Fn(usize) -> &'m [F],
+    ) -> Vec<ConstraintFailure<F>>
+    where
+        F: 'm,
+    {
+        let height = self.height;
+        let window_size = self.window_size;
+        let main_window = (0..window_size)
+            .flat_map(|offset| main_row((i + offset) % height).iter().copied())
+            .collect::<Vec<_>>();
+        let preprocessed_width = self.preprocessed_width();
+        let preprocessed_window = self
+            .preprocessed
+            .iter()
+            .flat_map(|preprocessed| {
+                [i, (i + 1) % height]
+                    .into_iter()
+                    .flat_map(|row| preprocessed.row_slice(row).to_vec())
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = ConstraintReportBuilder {
+            row_index: i,
+            main: RowMajorMatrixView::new(&main_window, self.main_width),
+            preprocessed: RowMajorMatrixView::new(&preprocessed_window, preprocessed_width),
+            public_values,
+            periodic_values: self
+                .periodic_columns
+                .iter()
+                .map(|column| column[i % column.len()])
+                .collect(),
+            row_selectors: &self.row_selectors,
+            is_first_row: F::from_bool(i == 0),
+            is_last_row: F::from_bool(i == height - 1),
+            is_transition: F::from_bool(i != height - 1),
+            is_transition_window: F::from_bool(i + window_size - 1 < height),
+            window_size,
+            constraint_index: 0,
+            failures: Vec::new(),
+        };
+        self.air.eval(&mut builder);
+        builder.failures
+    }
+}
+
+/// An `AirBuilder` which records each constraint that doesn't vanish on a row.
+#[derive(Debug)]
+pub struct ConstraintReportBuilder<'a, F: Field> {
+    row_index: usize,
+    main: RowMajorMatrixView<'a, F>,
+    preprocessed: RowMajorMatrixView<'a, F>,
+    public_values: &'a [F],
+    periodic_values: Vec<F>,
+    row_selectors: &'a [RowSelector],
+    is_first_row: F,
+    is_last_row: F,
+    is_transition: F,
+    is_transition_window: F,
+    window_size: usize,
+    constraint_index: usize,
+    failures: Vec<ConstraintFailure<F>>,
+}
+
+impl<'a, F: Field> AirBuilder for ConstraintReportBuilder<'a, F> {
+    type F = F;
+    type Expr = F;
+    type Var = F;
+    type M = RowMajorMatrixView<'a, F>;
+
+    fn main(&self) -> Self::M {
+        self.main
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.is_first_row
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.is_last_row
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        if size == 2 {
+            self.is_transition
+        } else if size == self.window_size {
+            self.is_transition_window
+        } else {
+            panic!("only supports transition windows of size 2 or the AIR's window_size")
+        }
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        let value = x.into();
+        if !value.is_zero() {
+            self.failures.push(ConstraintFailure {
+                row: self.row_index,
+                constraint_index: self.constraint_index,
+                value,
+            });
+        }
+        self.constraint_index += 1;
+    }
+}
+
+impl<'a, F: Field> AirBuilderWithPublicValues for ConstraintReportBuilder<'a, F> {
+    type PublicVar = Self::F;
+
+    fn public_values(&self) -> &[Self::F] {
+        self.public_values
+    }
+}
+
+impl<'a, F: Field> AirBuilderWithPeriodicColumns for ConstraintReportBuilder<'a, F> {
+    type PeriodicVar = Self::F;
+
+    fn periodic_values(&self) -> &[Self::F] {
+        &self.periodic_values
+    }
+}
+
+impl<'a, F: Field> AirBuilderWithRowSelectors for ConstraintReportBuilder<'a, F> {
+    fn row_selector(&self, selector: RowSelector) -> Self::Expr {
+        row_selector_index(self.row_selectors, selector);
+        F::from_bool(selector.contains(self.row_index))
+    }
+}
+
+impl<'a, F: Field> PairBuilder for ConstraintReportBuilder<'a, F> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
@@ -0,0 +1,316 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use hashbrown::HashMap;
+use p3_air::{
+    Air, AirBuilderWithPeriodicColumns, AirBuilderWithPublicValues, AirBuilderWithRowSelectors,
+    BaseAir, PairBuilder, RowSelector,
+};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+
+use crate::{
+    get_interactions, get_symbolic_constraints, Entry, SymbolicAirBuilder, SymbolicDag,
+    SymbolicExpression, SymbolicNode, SymbolicVariable,
+};
+
+/// An AIR whose constraints are those of `inner`, rewritten to have a degree of at most
+/// `max_degree` by introducing virtual columns.
+///
+/// Whenever a product has too high a degree, its operand of highest degree is replaced by a new
+/// column, appended to the main trace, which is constrained to equal it on every row. Use
+/// [`DegreeReducedAir::extend_trace`] to fill in these columns.
+///
+/// Subexpressions involving `is_first_row`, `is_last_row` or the transition selectors are never
+/// moved to virtual columns, since those selectors are not zero-one valued polynomials over the
+/// whole trace domain. Products of such subexpressions which can't be reduced otherwise keep their
+/// degree. AIRs with permutation columns or lookups are not supported.
+pub struct DegreeReducedAir<A, F> {
+    inner: A,
+    inner_width: usize,
+    num_virtual_columns: usize,
+    /// The rewritten constraints of `inner`, followed by those defining the virtual columns.
+    constraints: SymbolicDag<F>,
+    /// The expressions defining the virtual columns, in order. Virtual columns only appear in the
+    /// definitions of later columns, so the node of each definition precedes every use of its
+    /// column.
+    definitions: SymbolicDag<F>,
+    row_selectors: Vec<RowSelector>,
+}
+
+impl<A, F> DegreeReducedAir<A, F>
+where
+    A: BaseAir<F> + Air<SymbolicAirBuilder<F>>,
+    F: Field,
+{
+    /// Rewrites the constraints of `inner`, evaluated with `num_public_values` public values, to
+    /// have a degree of at most `max_degree`, which must be at least 2.
+    pub fn new(inner: A, num_public_values: usize, max_degree: usize) -> Self {
+        assert!(max_degree >= 2, "the maximum degree must be at least 2");
+        let preprocessed_width = inner.preprocessed_trace().map_or(0, |trace| trace.width());
+        assert!(
+            inner.permutation_width() == 0
+                && get_interactions::<F, A>(&inner, preprocessed_width, num_public_values)
+                    .is_empty(),
+            "degree reduction does not support permutation columns or lookups"
+        );
+
+        let inner_width = inner.width();
+        let dag = SymbolicDag::new(&get_symbolic_constraints::<F, A>(
+            &inner,
+            preprocessed_width,
+            num_public_values,
+        ));
+
+        let virtual_column = |column: usize| -> SymbolicExpression<F> {
+            SymbolicVariable::new(Entry::Main { offset: 0 }, inner_width + column).into()
+        };
+
+        // The rewritten expression of each node, and whether it may be moved to a virtual column.
+        let mut reduced: Vec<SymbolicExpression<F>> = Vec::with_capacity(dag.nodes.len());
+        let mut extractable = Vec::with_capacity(dag.nodes.len());
+        let mut definitions = vec![];
+        // The virtual column of each node which has been moved to one.
+        let mut columns = HashMap::new();
+
+        for node in &dag.nodes {
+            let (expr, can_extract) = match *node {
+                SymbolicNode::Variable { entry, index } => (
+                    SymbolicVariable::new(entry, index).into(),
+                    !matches!(entry, Entry::Permutation { .. } | Entry::Challenge),
+                ),
+                SymbolicNode::IsFirstRow => (SymbolicExpression::IsFirstRow, false),
+                SymbolicNode::IsLastRow => (SymbolicExpression::IsLastRow, false),
+                SymbolicNode::IsTransition => (SymbolicExpression::IsTransition, false),
+                SymbolicNode::IsTransitionWindow(size) => {
+                    (SymbolicExpression::IsTransitionWindow(size), false)
+                }
+                SymbolicNode::Constant(c) => (SymbolicExpression::Constant(c), true),
+                SymbolicNode::Add(x, y) => (
+                    reduced[x].clone() + reduced[y].clone(),
+                    extractable[x] && extractable[y],
+                ),
+                SymbolicNode::Sub(x, y) => (
+                    reduced[x].clone() - reduced[y].clone(),
+                    extractable[x] && extractable[y],
+                ),
+                SymbolicNode::Neg(x) => (-reduced[x].clone(), extractable[x]),
+                SymbolicNode::Mul(x, y) => {
+                    let mut operands = [x, y].map(|id| (id, reduced[id].clone()));
+                    loop {
+                        let degree =
+                            operands[0].1.degree_multiple() + operands[1].1.degree_multiple();
+                        if degree <= max_degree {
+                            break;
+                        }
+                        // Move the operand of highest degree which can be moved, if any.
+                        let Some((id, expr)) = operands
+                            .iter()
+                            .filter(|(id, expr)| extractable[*id] && expr.degree_multiple() > 1)
+                            .max_by_key(|(_, expr)| expr.degree_multiple())
+                            .cloned()
+                        else {
+                            break;
+                        };
+                        let column = *columns.entry(id).or_insert_with(|| {
+                            definitions.push(expr);
+                            definitions.len() - 1
+                        });
+                        // Both operands are replaced when squaring.
+                        for operand in &mut operands {
+                            if operand.0 == id {
+                                operand.1 = virtual_column(column);
+                            }
+                        }
+                    }
+                    let [(_, x_expr), (_, y_expr)] = operands;
+                    (x_expr * y_expr, extractable[x] && extractable[y])
+                }
+            };
+            reduced.push(expr);
+            extractable.push(can_extract);
+        }
+
+        let constraints = dag
+            .constraints
+            .iter()
+            .map(|&constraint| reduced[constraint].clone())
+            .chain(
+                definitions
+                    .iter()
+                    .enumerate()
+                    .map(|(column, definition)| virtual_column(column) - definition.clone()),
+            )
+            .collect::<Vec<_>>();
+
+        Self {
+            inner_width,
+            num_virtual_columns: definitions.len(),
+            constraints: SymbolicDag::new(&constraints),
+            definitions: SymbolicDag::new(&definitions),
+            row_selectors: inner.row_selectors(),
+            inner,
+        }
+    }
+}
+
+impl<A: BaseAir<F>, F: Field> DegreeReducedAir<A, F> {
+    pub const fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// The number of columns appended to the main trace of `inner`.
+    pub const fn num_virtual_columns(&self) -> usize {
+        self.num_virtual_columns
+    }
+
+    /// Appends the virtual columns to a main trace of `inner`.
+    ///
+    /// Constraints referring to later rows wrap around, so the trace should already have its final
+    /// height; pad it with `inner`'s padding first if needed.
+    pub fn extend_trace(
+        &self,
+        trace: &RowMajorMatrix<F>,
+        public_values: &[F],
+    ) -> RowMajorMatrix<F> {
+        assert_eq!(
+            trace.width(),
+            self.inner_width,
+            "the trace must have the inner AIR's width"
+        );
+        let height = trace.height();
+        let width = self.inner_width + self.num_virtual_columns;
+        let preprocessed = self.inner.preprocessed_trace();
+        let periodic_columns = self.inner.periodic_columns();
+
+        let mut values = F::zero_vec(height * width);
+        values
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(row, out)| {
+                out[..self.inner_width]
+                    .copy_from_slice(&trace.values[row * self.inner_width..][..self.inner_width]);
+                let mut node_values: Vec<F> = Vec::with_capacity(self.definitions.nodes.len());
+                for node in &self.definitions.nodes {
+                    let value = match *node {
+                        SymbolicNode::Variable { entry, index } => match entry {
+                            Entry::Main { offset } if index >= self.inner_width => {
+                                debug_assert_eq!(offset, 0);
+                                let column = index - self.inner_width;
+                                node_values[self.definitions.constraints[column]]
+                            }
+                            Entry::Main { offset } => trace.get((row + offset) % height, index),
+                            Entry::Preprocessed { offset } => preprocessed
+                                .as_ref()
+                                .expect("the AIR has no preprocessed trace")
+                                .get((row + offset) % height, index),
+                            Entry::Public => public_values[index],
+                            Entry::Periodic => {
+                                let column = &periodic_columns[index];
+                                column[row % column.len()]
+                            }
+                            Entry::RowSelector => {
+                                F::from_bool(self.row_selectors[index].contains(row))
+                            }
+                            Entry::Permutation { .. } | Entry::Challenge => {
+                                unreachable!("virtual columns never depend on permutation columns")
+                            }
+                        },
+                        SymbolicNode::Constant(c) => c,
+                        SymbolicNode::Add(x, y) => node_values[x] + node_values[y],
+                        SymbolicNode::Sub(x, y) => node_values[x] - node_values[y],
+                        SymbolicNode::Neg(x) => -node_values[x],
+                        SymbolicNode::Mul(x, y) => node_values[x] * node_values[y],
+                        SymbolicNode::IsFirstRow
+                        | SymbolicNode::IsLastRow
+                        | SymbolicNode::IsTransition
+                        | SymbolicNode::IsTransitionWindow(_) => {
+                            unreachable!("virtual columns never depend on selectors")
+                        }
+                    };
+                    node_values.push(value);
+                }
+                for (column, &definition) in self.definitions.constraints.iter().enumerate() {
+                    out[self.inner_width + column] = node_values[definition];
+                }
+            });
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+impl<A: BaseAir<F>, F: Field> BaseAir<F> for DegreeReducedAir<A, F> {
+    fn width(&self) -> usize {
+        self.inner_width + self.num_virtual_columns
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names = self.inner.column_names();
+        if !names.is_empty() {
+            names.extend((0..self.num_virtual_columns).map(|i| format!("virtual[{i}]")));
+        }
+        names
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        self.inner.preprocessed_trace()
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        self.inner.periodic_columns()
+    }
+
+    fn row_selectors(&self) -> Vec<RowSelector> {
+        self.row_selectors.clone()
+    }
+
+    fn window_size(&self) -> usize {
+        self.inner.window_size()
+    }
+}
+
+impl<A, F, AB> Air<AB> for DegreeReducedAir<A, F>
+where
+    A: BaseAir<F>,
+    F: Field,
+    AB: PairBuilder<F = F>
+        + AirBuilderWithPublicValues
+        + AirBuilderWithPeriodicColumns
+        + AirBuilderWithRowSelectors,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let preprocessed = builder.preprocessed();
+        let mut values: Vec<AB::Expr> = Vec::with_capacity(self.constraints.nodes.len());
+        for node in &self.constraints.nodes {
+            let value = match *node {
+                SymbolicNode::Variable { entry, index } => match entry {
+                    Entry::Main { offset } => main.row_slice(offset)[index].into(),
+                    Entry::Preprocessed { offset } => preprocessed.row_slice(offset)[index].into(),
+                    Entry::Public => builder.public_values()[index].into(),
+                    Entry::Periodic => builder.periodic_values()[index].into(),
+                    Entry::RowSelector => builder.row_selector(self.row_selectors[index]),
+                    Entry::Permutation { .. } | Entry::Challenge => {
+                        unreachable!("degree reduction does not support permutation columns")
+                    }
+                },
+                SymbolicNode::IsFirstRow => builder.is_first_row(),
+                SymbolicNode::IsLastRow => builder.is_last_row(),
+                SymbolicNode::IsTransition => builder.is_transition(),
+                SymbolicNode::IsTransitionWindow(size) => builder.is_transition_window(size),
+                SymbolicNode::Constant(c) => c.into(),
+                SymbolicNode::Add(x, y) => values[x].clone() + values[y].clone(),
+                SymbolicNode::Sub(x, y) => values[x].clone() - values[y].clone(),
+                SymbolicNode::Neg(x) => -values[x].clone(),
+                SymbolicNode::Mul(x, y) => values[x].clone() * values[y].clone(),
+            };
+            values.push(value);
+        }
+        for &constraint in &self.constraints.constraints {
+            builder.assert_zero(values[constraint].clone());
+        }
+    }
+}
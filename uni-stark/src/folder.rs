@@ -1,19 +1,39 @@
 use alloc::vec::Vec;
 
-use p3_air::{AirBuilder, AirBuilderWithPublicValues};
+use p3_air::{
+    AirBuilder, AirBuilderWithPeriodicColumns, AirBuilderWithPublicValues,
+    AirBuilderWithRowSelectors, ExtensionBuilder, InteractionBuilder, PairBuilder,
+    PermutationAirBuilder, RowSelector,
+};
 use p3_field::AbstractField;
 use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::stack::VerticalPair;
 
+use crate::lookup::eval_interaction;
+use crate::symbolic_builder::row_selector_index;
 use crate::{PackedChallenge, PackedVal, StarkGenericConfig, Val};
 
 #[derive(Debug)]
 pub struct ProverConstraintFolder<'a, SC: StarkGenericConfig> {
     pub main: RowMajorMatrixView<'a, PackedVal<SC>>,
+    pub preprocessed: RowMajorMatrixView<'a, PackedVal<SC>>,
+    pub permutation: RowMajorMatrixView<'a, PackedChallenge<SC>>,
+    pub permutation_challenges: &'a [PackedChallenge<SC>],
+    /// The first permutation column used by the lookup argument.
+    pub lookup_offset: usize,
+    /// The number of interactions sent or received so far.
+    pub num_interactions: usize,
     pub public_values: &'a Vec<Val<SC>>,
+    pub periodic_values: &'a [PackedVal<SC>],
+    pub row_selectors: &'a [RowSelector],
+    /// The value of each of `row_selectors`.
+    pub row_selector_values: &'a [PackedVal<SC>],
     pub is_first_row: PackedVal<SC>,
     pub is_last_row: PackedVal<SC>,
     pub is_transition: PackedVal<SC>,
+    /// The selector for `is_transition_window(window_size)`, when the window is larger than 2.
+    pub is_transition_window: PackedVal<SC>,
+    pub window_size: usize,
     pub alpha_powers: &'a [SC::Challenge],
     pub accumulator: PackedChallenge<SC>,
     pub constraint_index: usize,
@@ -23,11 +43,25 @@ type ViewPair<'a, T> = VerticalPair<RowMajorMatrixView<'a, T>, RowMajorMatrixVie
 
 #[derive(Debug)]
 pub struct VerifierConstraintFolder<'a, SC: StarkGenericConfig> {
-    pub main: ViewPair<'a, SC::Challenge>,
+    pub main: RowMajorMatrixView<'a, SC::Challenge>,
+    pub preprocessed: RowMajorMatrixView<'a, SC::Challenge>,
+    pub permutation: ViewPair<'a, SC::Challenge>,
+    pub permutation_challenges: &'a [SC::Challenge],
+    /// The first permutation column used by the lookup argument.
+    pub lookup_offset: usize,
+    /// The number of interactions sent or received so far.
+    pub num_interactions: usize,
     pub public_values: &'a Vec<Val<SC>>,
+    pub periodic_values: &'a [SC::Challenge],
+    pub row_selectors: &'a [RowSelector],
+    /// The value of each of `row_selectors`.
+    pub row_selector_values: &'a [SC::Challenge],
     pub is_first_row: SC::Challenge,
     pub is_last_row: SC::Challenge,
     pub is_transition: SC::Challenge,
+    /// The selector for `is_transition_window(window_size)`, when the window is larger than 2.
+    pub is_transition_window: SC::Challenge,
+    pub window_size: usize,
     pub alpha: SC::Challenge,
     pub accumulator: SC::Challenge,
 }
@@ -57,8 +91,10 @@ impl<'a, SC: StarkGenericConfig> AirBuilder for ProverConstraintFolder<'a, SC> {
     fn is_transition_window(&self, size: usize) -> Self::Expr {
         if size == 2 {
             self.is_transition
+        } else if size == self.window_size {
+            self.is_transition_window
         } else {
-            panic!("uni-stark only supports a window size of 2")
+            panic!("uni-stark only supports transition windows of size 2 or the AIR's window_size")
         }
     }
 
@@ -80,11 +116,86 @@ impl<'a, SC: StarkGenericConfig> AirBuilderWithPublicValues for ProverConstraint
     }
 }
 
+impl<'a, SC: StarkGenericConfig> AirBuilderWithPeriodicColumns for ProverConstraintFolder<'a, SC> {
+    type PeriodicVar = PackedVal<SC>;
+
+    #[inline]
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.periodic_values
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> AirBuilderWithRowSelectors for ProverConstraintFolder<'a, SC> {
+    #[inline]
+    fn row_selector(&self, selector: RowSelector) -> Self::Expr {
+        self.row_selector_values[row_selector_index(self.row_selectors, selector)]
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PairBuilder for ProverConstraintFolder<'a, SC> {
+    #[inline]
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> ExtensionBuilder for ProverConstraintFolder<'a, SC> {
+    type EF = SC::Challenge;
+    type ExprEF = PackedChallenge<SC>;
+    type VarEF = PackedChallenge<SC>;
+
+    #[inline]
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        let x: PackedChallenge<SC> = x.into();
+        let alpha_power = self.alpha_powers[self.constraint_index];
+        self.accumulator += PackedChallenge::<SC>::from_f(alpha_power) * x;
+        self.constraint_index += 1;
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PermutationAirBuilder for ProverConstraintFolder<'a, SC> {
+    type MP = RowMajorMatrixView<'a, PackedChallenge<SC>>;
+    type RandomVar = PackedChallenge<SC>;
+
+    #[inline]
+    fn permutation(&self) -> Self::MP {
+        self.permutation
+    }
+
+    #[inline]
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.permutation_challenges
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> InteractionBuilder for ProverConstraintFolder<'a, SC> {
+    #[inline]
+    fn send<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let column = self.lookup_offset + self.num_interactions;
+        self.num_interactions += 1;
+        eval_interaction(
+            self,
+            column,
+            bus,
+            values.into_iter().map(Into::into),
+            multiplicity.into(),
+        );
+    }
+}
+
 impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC> {
     type F = Val<SC>;
     type Expr = SC::Challenge;
     type Var = SC::Challenge;
-    type M = ViewPair<'a, SC::Challenge>;
+    type M = RowMajorMatrixView<'a, SC::Challenge>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -101,8 +212,10 @@ impl<'a, SC: StarkGenericConfig> AirBuilder for VerifierConstraintFolder<'a, SC>
     fn is_transition_window(&self, size: usize) -> Self::Expr {
         if size == 2 {
             self.is_transition
+        } else if size == self.window_size {
+            self.is_transition_window
         } else {
-            panic!("uni-stark only supports a window size of 2")
+            panic!("uni-stark only supports transition windows of size 2 or the AIR's window_size")
         }
     }
 
@@ -120,3 +233,70 @@ impl<'a, SC: StarkGenericConfig> AirBuilderWithPublicValues for VerifierConstrai
         self.public_values
     }
 }
+
+impl<'a, SC: StarkGenericConfig> AirBuilderWithPeriodicColumns
+    for VerifierConstraintFolder<'a, SC>
+{
+    type PeriodicVar = SC::Challenge;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        self.periodic_values
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> AirBuilderWithRowSelectors for VerifierConstraintFolder<'a, SC> {
+    fn row_selector(&self, selector: RowSelector) -> Self::Expr {
+        self.row_selector_values[row_selector_index(self.row_selectors, selector)]
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PairBuilder for VerifierConstraintFolder<'a, SC> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> ExtensionBuilder for VerifierConstraintFolder<'a, SC> {
+    type EF = SC::Challenge;
+    type ExprEF = SC::Challenge;
+    type VarEF = SC::Challenge;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.assert_zero(x);
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> PermutationAirBuilder for VerifierConstraintFolder<'a, SC> {
+    type MP = ViewPair<'a, SC::Challenge>;
+    type RandomVar = SC::Challenge;
+
+    fn permutation(&self) -> Self::MP {
+        self.permutation
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.permutation_challenges
+    }
+}
+
+impl<'a, SC: StarkGenericConfig> InteractionBuilder for VerifierConstraintFolder<'a, SC> {
+    fn send<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let column = self.lookup_offset + self.num_interactions;
+        self.num_interactions += 1;
+        eval_interaction(
+            self,
+            column,
+            bus,
+            values.into_iter().map(Into::into),
+            multiplicity.into(),
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_air::Air;
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+use tracing::instrument;
+
+use crate::constraint_report::RowChecker;
+use crate::{check_constraints_report, ConstraintReportBuilder, SymbolicAirBuilder};
+
+/// How many mutations [`fuzz_constraints`] tries of each kind.
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    /// The number of random rows in which each column is set to a random other value.
+    pub rows_per_column: usize,
+    /// The number of random rows in which each boolean column, i.e. each column whose values are
+    /// all zero or one, is flipped.
+    pub rows_per_boolean_column: usize,
+    /// The number of random pairs of distinct rows which are swapped, besides every pair of
+    /// adjacent rows.
+    pub row_swaps: usize,
+    /// The number of random other values each public value is set to.
+    pub values_per_public_value: usize,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            rows_per_column: 4,
+            rows_per_boolean_column: 4,
+            row_swaps: 16,
+            values_per_public_value: 2,
+        }
+    }
+}
+
+/// A change to a valid trace or its public values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation<F> {
+    /// Sets the cell in the given row and column to `value`.
+    Cell { row: usize, column: usize, value: F },
+    /// Flips a cell of a boolean column.
+    FlipBoolean { row: usize, column: usize },
+    /// Swaps two rows.
+    SwapRows(usize, usize),
+    /// Sets the public value with the given index to `value`.
+    PublicValue { index: usize, value: F },
+}
+
+/// The result of [`fuzz_constraints`].
+#[derive(Clone, Debug)]
+pub struct FuzzReport<F> {
+    /// The number of mutations tried.
+    pub num_mutations: usize,
+    /// The mutations after which every constraint still held.
+    pub survivors: Vec<Mutation<F>>,
+}
+
+impl<F> FuzzReport<F> {
+    /// Whether every mutation broke a constraint.
+    pub fn is_ok(&self) -> bool {
+        self.survivors.is_empty()
+    }
+
+    /// The columns in which changing a single cell broke no constraint, which are likely
+    /// underconstrained.
+    pub fn underconstrained_columns(&self) -> Vec<usize> {
+        self.survivors
+            .iter()
+            .filter_map(|mutation| match *mutation {
+                Mutation::Cell { column, .. } | Mutation::FlipBoolean { column, .. } => {
+                    Some(column)
+                }
+                _ => None,
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// The public values which could be changed without breaking a constraint.
+    pub fn underconstrained_public_values(&self) -> Vec<usize> {
+        self.survivors
+            .iter()
+            .filter_map(|mutation| match *mutation {
+                Mutation::PublicValue { index, .. } => Some(index),
+                _ => None,
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+}
+
+/// Searches for missing constraints, by applying structured mutations to a valid trace and
+/// reporting those which still satisfy every constraint.
+///
+/// The mutations, whose number is set by `config`, set single cells to random values, flip cells
+/// of boolean columns, swap rows, and change public values. Each mutation is checked like
+/// [`check_constraints_report`] would, but only on the rows whose constraints read the mutated
+/// rows. A surviving mutation doesn't always mean a bug: a column may legitimately be free, like
+/// an unused input, and swapping identical rows changes nothing.
+///
+/// Panics if `trace` doesn't satisfy the constraints to begin with.
+#[instrument(name = "fuzz constraints", skip_all)]
+pub fn fuzz_constraints<F, A, R>(
+    air: &A,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+    config: &FuzzConfig,
+    rng: &mut R,
+) -> FuzzReport<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>> + for<'a> Air<ConstraintReportBuilder<'a, F>>,
+    R: Rng,
+    Standard: Distribution<F>,
+{
+    assert!(
+        check_constraints_report(air, trace, public_values).is_ok(),
+        "the trace must satisfy the constraints"
+    );
+    let (width, height) = (trace.width(), trace.height());
+
+    let mut mutations = vec![];
+    for column in 0..width {
+        for _ in 0..config.rows_per_column {
+            let row = rng.gen_range(0..height);
+            let value = trace.get(row, column) + random_nonzero(rng);
+            mutations.push(Mutation::Cell { row, column, value });
+        }
+        let is_boolean = (0..height).all(|row| {
+            let value = trace.get(row, column);
+            value.is_zero() || value.is_one()
+        });
+        if is_boolean {
+            for _ in 0..config.rows_per_boolean_column {
+                let row = rng.gen_range(0..height);
+                mutations.push(Mutation::FlipBoolean { row, column });
+            }
+        }
+    }
+    if height > 1 {
+        mutations.extend((0..height - 1).map(|row| Mutation::SwapRows(row, row + 1)));
+        for _ in 0..config.row_swaps {
+            let a = rng.gen_range(0..height);
+            let b = (a + rng.gen_range(1..height)) % height;
+            mutations.push(Mutation::SwapRows(a, b));
+        }
+    }
+    for (index, &value) in public_values.iter().enumerate() {
+        for _ in 0..config.values_per_public_value {
+            let value = value + random_nonzero(rng);
+            mutations.push(Mutation::PublicValue { index, value });
+        }
+    }
+
+    let checker = RowChecker::new(air, height);
+    let survivors = mutations
+        .par_iter()
+        .filter(|mutation| !breaks_constraint(&checker, trace, public_values, mutation))
+        .cloned()
+        .collect();
+
+    FuzzReport {
+        num_mutations: mutations.len(),
+        survivors,
+    }
+}
+
+fn random_nonzero<F: Field, R: Rng>(rng: &mut R) -> F
+where
+    Standard: Distribution<F>,
+{
+    loop {
+        let value: F = rng.gen();
+        if !value.is_zero() {
+            return value;
+        }
+    }
+}
+
+/// Whether some constraint fails on the trace and public values changed by `mutation`.
+fn breaks_constraint<F, A>(
+    checker: &RowChecker<'_, F, A>,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+    mutation: &Mutation<F>,
+) -> bool
+where
+    F: Field,
+    A: for<'a> Air<ConstraintReportBuilder<'a, F>>,
+{
+    let width = trace.width();
+    let original_row = |row: usize| trace.values[row * width..][..width].to_vec();
+
+    // The mutated rows with their new contents, and the public values.
+    let (rows, public_values) = match *mutation {
+        Mutation::Cell { row, column, value } => {
+            let mut values = original_row(row);
+            values[column] = value;
+            (vec![(row, values)], public_values.to_vec())
+        }
+        Mutation::FlipBoolean { row, column } => {
+            let mut values = original_row(row);
+            values[column] = F::one() - values[column];
+            (vec![(row, values)], public_values.to_vec())
+        }
+        Mutation::SwapRows(a, b) => (
+            vec![(a, original_row(b)), (b, original_row(a))],
+            public_values.to_vec(),
+        ),
+        Mutation::PublicValue { index, value } => {
+            let mut public_values = public_values.to_vec();
+            public_values[index] = value;
+            (vec![], public_values)
+        }
+    };
+
+    let main_row = |row: usize| {
+        rows.iter()
+            .find(|(mutated_row, _)| *mutated_row == row)
+            .map_or(&trace.values[row * width..][..width], |(_, values)| {
+                &values[..]
+            })
+    };
+    let checked_rows = if rows.is_empty() {
+        (0..trace.height()).collect_vec()
+    } else {
+        rows.iter()
+            .flat_map(|&(row, _)| checker.rows_reading(row))
+            .sorted()
+            .dedup()
+            .collect_vec()
+    };
+    checked_rows
+        .into_iter()
+        .any(|i| !checker.failures(i, &public_values, main_row).is_empty())
+}
@@ -4,27 +4,57 @@
 
 extern crate alloc;
 
+mod compiled_constraints;
 mod config;
+mod constraint_export;
+mod constraint_report;
+mod degree_reduction;
 mod folder;
+mod lookup;
+mod multi_prover;
+mod multi_verifier;
+mod padding;
 mod proof;
+mod proof_format;
 mod prover;
+mod setup;
+mod statement;
 mod symbolic_builder;
+mod symbolic_dag;
 mod symbolic_expression;
 mod symbolic_variable;
+mod trace_view;
 mod verifier;
 mod zerofier_coset;
 
 #[cfg(debug_assertions)]
 mod check_constraints;
+#[cfg(feature = "test-utils")]
+mod fuzz;
 
 #[cfg(debug_assertions)]
 pub use check_constraints::*;
+pub use compiled_constraints::*;
 pub use config::*;
+pub use constraint_export::*;
+pub use constraint_report::*;
+pub use degree_reduction::*;
 pub use folder::*;
+#[cfg(feature = "test-utils")]
+pub use fuzz::*;
+pub use lookup::*;
+pub use multi_prover::*;
+pub use multi_verifier::*;
+pub use padding::*;
 pub use proof::*;
+pub use proof_format::*;
 pub use prover::*;
+pub use setup::*;
+pub use statement::*;
 pub use symbolic_builder::*;
+pub use symbolic_dag::*;
 pub use symbolic_expression::*;
 pub use symbolic_variable::*;
+pub use trace_view::*;
 pub use verifier::*;
 pub use zerofier_coset::*;
@@ -0,0 +1,238 @@
+//! A LogUp lookup argument for balancing the interactions sent and received by an AIR.
+//!
+//! For every interaction `(bus, values, multiplicity)` the prover adds a helper column to the
+//! permutation trace holding `multiplicity / (beta + sum_i alpha^(i + 1) t_i)` of the tuple
+//! `t = (bus, values_0, values_1, ...)`, followed by a single running sum over all helper columns.
+//! The AIR's constraints are extended to check each helper column, the running sum, and that the
+//! running sum ends at zero, i.e. that every bus is balanced.
+
+use alloc::vec::Vec;
+
+use itertools::Itertools;
+use p3_air::{Air, ExtensionBuilder, PermutationAirBuilder, RowSelector};
+use p3_field::{
+    batch_multiplicative_inverse, AbstractExtensionField, AbstractField, ExtensionField, Field,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+use crate::{Entry, SymbolicAirBuilder, SymbolicExpression};
+
+/// The number of extension field challenges used by the lookup argument: one to combine the
+/// values of a tuple, and one to shift the combination.
+pub const NUM_LOOKUP_CHALLENGES: usize = 2;
+
+/// A tuple sent over a bus; receiving is represented as sending with a negated multiplicity.
+#[derive(Clone, Debug)]
+pub struct Interaction<E> {
+    pub bus: usize,
+    pub values: Vec<E>,
+    pub multiplicity: E,
+}
+
+/// The number of permutation columns needed to balance `num_interactions` interactions.
+pub const fn num_lookup_columns(num_interactions: usize) -> usize {
+    if num_interactions == 0 {
+        0
+    } else {
+        // One helper column per interaction, plus the running sum.
+        num_interactions + 1
+    }
+}
+
+#[instrument(name = "evaluate interactions symbolically", skip_all, level = "debug")]
+pub fn get_interactions<F, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_public_values: usize,
+) -> Vec<Interaction<SymbolicExpression<F>>>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    let mut builder = SymbolicAirBuilder::new(air, preprocessed_width, num_public_values);
+    air.eval(&mut builder);
+    builder.interactions()
+}
+
+/// Combines a tuple sent over `bus` into the denominator of its LogUp fraction, with the bus as the
+/// first element of the tuple.
+pub(crate) fn fingerprint<E, EE, I>(alpha: EE, beta: EE, bus: usize, values: I) -> EE
+where
+    E: AbstractField,
+    EE: AbstractExtensionField<E>,
+    I: IntoIterator<Item = E>,
+{
+    let mut acc = beta + alpha.clone() * E::from_canonical_usize(bus);
+    let mut alpha_power = alpha.square();
+    for value in values {
+        acc += alpha_power.clone() * value;
+        alpha_power *= alpha.clone();
+    }
+    acc
+}
+
+/// Constrains the helper column `column` of the permutation trace to hold the LogUp fraction of
+/// the given interaction.
+///
+/// The lookup challenges are expected to be the last two permutation challenges.
+pub(crate) fn eval_interaction<AB, I>(
+    builder: &mut AB,
+    column: usize,
+    bus: usize,
+    values: I,
+    multiplicity: AB::Expr,
+) where
+    AB: PermutationAirBuilder,
+    I: IntoIterator<Item = AB::Expr>,
+{
+    let randomness = builder.permutation_randomness();
+    let alpha: AB::ExprEF = randomness[randomness.len() - 2].into();
+    let beta: AB::ExprEF = randomness[randomness.len() - 1].into();
+    let helper: AB::ExprEF = builder.permutation().row_slice(0)[column].into();
+
+    let denominator = fingerprint(alpha, beta, bus, values);
+    builder.assert_eq_ext(helper * denominator, multiplicity);
+}
+
+/// Constrains the running sum over the `num_interactions` helper columns starting at
+/// `lookup_offset`, and checks that it ends at zero.
+pub(crate) fn eval_lookup_sum<AB: PermutationAirBuilder>(
+    builder: &mut AB,
+    lookup_offset: usize,
+    num_interactions: usize,
+) {
+    if num_interactions == 0 {
+        return;
+    }
+
+    let permutation = builder.permutation();
+    let local = permutation.row_slice(0);
+    let next = permutation.row_slice(1);
+    let helpers = lookup_offset..lookup_offset + num_interactions;
+    let sum_column = lookup_offset + num_interactions;
+
+    let helpers_local: AB::ExprEF = local[helpers.clone()].iter().map(|&h| h.into()).sum();
+    let helpers_next: AB::ExprEF = next[helpers].iter().map(|&h| h.into()).sum();
+    let sum_local: AB::ExprEF = local[sum_column].into();
+    let sum_next: AB::ExprEF = next[sum_column].into();
+
+    builder
+        .when_first_row()
+        .assert_eq_ext(sum_local.clone(), helpers_local);
+    builder
+        .when_transition()
+        .assert_eq_ext(sum_next - sum_local.clone(), helpers_next);
+    builder.when_last_row().assert_zero_ext(sum_local);
+}
+
+/// Builds the LogUp helper columns and running sum for `interactions` over the given traces.
+#[instrument(name = "generate lookup trace", skip_all)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn generate_lookup_trace<F, EF>(
+    interactions: &[Interaction<SymbolicExpression<F>>],
+    preprocessed: Option<&RowMajorMatrix<F>>,
+    main: &RowMajorMatrix<F>,
+    public_values: &[F],
+    periodic_columns: &[Vec<F>],
+    row_selectors: &[RowSelector],
+    alpha: EF,
+    beta: EF,
+) -> RowMajorMatrix<EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    let height = main.height();
+    let num_interactions = interactions.len();
+
+    let (denominators, multiplicities): (Vec<EF>, Vec<F>) = (0..height)
+        .into_par_iter()
+        .flat_map_iter(|row| {
+            let window = RowWindow {
+                preprocessed,
+                main,
+                public_values,
+                periodic_columns,
+                row_selectors,
+                row,
+            };
+            interactions
+                .iter()
+                .map(|interaction| {
+                    let values = interaction.values.iter().map(|v| window.eval(v));
+                    (
+                        fingerprint(alpha, beta, interaction.bus, values),
+                        window.eval(&interaction.multiplicity),
+                    )
+                })
+                .collect_vec()
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .unzip();
+    let inverses = batch_multiplicative_inverse(&denominators);
+
+    let width = num_lookup_columns(num_interactions);
+    let mut values = Vec::with_capacity(height * width);
+    let mut running_sum = EF::zero();
+    for (row_inverses, row_multiplicities) in inverses
+        .chunks_exact(num_interactions)
+        .zip(multiplicities.chunks_exact(num_interactions))
+    {
+        for (&inverse, &multiplicity) in row_inverses.iter().zip(row_multiplicities) {
+            let helper = inverse * multiplicity;
+            running_sum += helper;
+            values.push(helper);
+        }
+        values.push(running_sum);
+    }
+    RowMajorMatrix::new(values, width)
+}
+
+/// Concrete values for the variables an interaction may refer to, on a single row.
+struct RowWindow<'a, F> {
+    preprocessed: Option<&'a RowMajorMatrix<F>>,
+    main: &'a RowMajorMatrix<F>,
+    public_values: &'a [F],
+    periodic_columns: &'a [Vec<F>],
+    row_selectors: &'a [RowSelector],
+    row: usize,
+}
+
+impl<'a, F: Field> RowWindow<'a, F> {
+    fn eval(&self, expr: &SymbolicExpression<F>) -> F {
+        let height = self.main.height();
+        let get = |m: &RowMajorMatrix<F>, offset: usize, index: usize| {
+            m.values[((self.row + offset) % height) * m.width + index]
+        };
+        match expr {
+            SymbolicExpression::Variable(v) => match v.entry {
+                Entry::Preprocessed { offset } => get(self.preprocessed.unwrap(), offset, v.index),
+                Entry::Main { offset } => get(self.main, offset, v.index),
+                Entry::Public => self.public_values[v.index],
+                Entry::Periodic => {
+                    let column = &self.periodic_columns[v.index];
+                    column[self.row % column.len()]
+                }
+                Entry::RowSelector => F::from_bool(self.row_selectors[v.index].contains(self.row)),
+                Entry::Permutation { .. } | Entry::Challenge => {
+                    panic!("interactions may not depend on the permutation trace")
+                }
+            },
+            SymbolicExpression::IsFirstRow => F::from_bool(self.row == 0),
+            SymbolicExpression::IsLastRow => F::from_bool(self.row == height - 1),
+            SymbolicExpression::IsTransition => F::from_bool(self.row != height - 1),
+            SymbolicExpression::IsTransitionWindow(size) => {
+                F::from_bool(self.row + size - 1 < height)
+            }
+            SymbolicExpression::Constant(c) => *c,
+            SymbolicExpression::Add { x, y, .. } => self.eval(x) + self.eval(y),
+            SymbolicExpression::Sub { x, y, .. } => self.eval(x) - self.eval(y),
+            SymbolicExpression::Neg { x, .. } => -self.eval(x),
+            SymbolicExpression::Mul { x, y, .. } => self.eval(x) * self.eval(y),
+        }
+    }
+}
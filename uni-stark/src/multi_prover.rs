@@ -0,0 +1,316 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_air::Air;
+use p3_challenger::{CanObserve, CanSample, FieldChallenger};
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_field::AbstractField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use tracing::{info_span, instrument};
+
+use crate::prover::quotient_values;
+use crate::statement::observe_statement_digest;
+use crate::symbolic_builder::get_log_quotient_degree;
+use crate::verifier::{valid_periods, valid_row_selectors, window_points};
+use crate::{
+    get_interactions, get_symbolic_constraints, statement_digest, Commitments, MultiProof,
+    OpenedValues, ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder,
+    SymbolicExpression, Val,
+};
+
+/// Proves that each trace satisfies the corresponding AIR, in a single proof.
+///
+/// All traces are committed in one PCS round and all quotients in another, and every table is
+/// opened by one `Pcs::open` call. Traces may have different widths and (power-of-two) heights.
+/// AIRs of different types can be combined by wrapping them in an enum which implements `Air` by
+/// delegating to its variants.
+///
+/// AIRs with preprocessed or permutation columns, including lookups, are not supported yet, and
+/// neither is ZK mode.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_multi<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> MultiProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(airs.len(), traces.len(), "expected one trace per AIR");
+    assert_eq!(
+        airs.len(),
+        public_values.len(),
+        "expected one set of public values per AIR"
+    );
+
+    let air_data = airs
+        .iter()
+        .zip(public_values)
+        .map(|(air, public_values)| AirData::new(config, air, public_values.len()))
+        .collect_vec();
+    prove_tables(
+        config,
+        &airs.iter().collect_vec(),
+        &air_data.iter().collect_vec(),
+        challenger,
+        traces,
+        public_values,
+    )
+}
+
+/// Proves that every trace satisfies `air`, in a single proof.
+///
+/// This is [`prove_multi`] for many instances of the same AIR, whose symbolic constraints and
+/// quotient degree are only computed once. The proof can be verified with
+/// [`verify_many`](crate::verify_many), or equivalently by [`verify_multi`](crate::verify_multi)
+/// with one copy of the AIR per trace. Every trace must have the same number of public values.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_many<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> MultiProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(
+        traces.len(),
+        public_values.len(),
+        "expected one set of public values per trace"
+    );
+    let num_public_values = public_values.first().map_or(0, Vec::len);
+    assert!(
+        public_values
+            .iter()
+            .all(|public_values| public_values.len() == num_public_values),
+        "every trace must have the same number of public values"
+    );
+
+    let air_data = AirData::new(config, air, num_public_values);
+    prove_tables(
+        config,
+        &vec![air; traces.len()],
+        &vec![&air_data; traces.len()],
+        challenger,
+        traces,
+        public_values,
+    )
+}
+
+/// What the prover derives from an AIR alone, which is shared by all of its tables.
+struct AirData<SC: StarkGenericConfig> {
+    symbolic_constraints: Vec<SymbolicExpression<Val<SC>>>,
+    log_quotient_degree: usize,
+    statement_digest: [u8; 32],
+}
+
+impl<SC: StarkGenericConfig> AirData<SC> {
+    fn new<A>(config: &SC, air: &A, num_public_values: usize) -> Self
+    where
+        A: Air<SymbolicAirBuilder<Val<SC>>>,
+    {
+        assert!(
+            air.preprocessed_trace().is_none()
+                && air.permutation_width() == 0
+                && get_interactions::<Val<SC>, A>(air, 0, num_public_values).is_empty(),
+            "prove_multi does not support preprocessed or permutation columns"
+        );
+        Self {
+            symbolic_constraints: get_symbolic_constraints::<Val<SC>, A>(air, 0, num_public_values),
+            log_quotient_degree: get_log_quotient_degree::<Val<SC>, A>(
+                air,
+                0,
+                num_public_values,
+                0,
+            ),
+            statement_digest: statement_digest(config, air, None, num_public_values),
+        }
+    }
+}
+
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+fn prove_tables<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    airs: &[&A],
+    air_data: &[&AirData<SC>],
+    challenger: &mut SC::Challenger,
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> MultiProof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    let pcs = config.pcs();
+    assert!(!config.is_zk(), "prove_multi does not support ZK mode");
+
+    #[cfg(debug_assertions)]
+    for ((air, trace), public_values) in airs.iter().zip(&traces).zip(public_values) {
+        crate::check_constraints::check_constraints::<_, SC::Challenge, _>(
+            *air,
+            None,
+            trace,
+            None,
+            &[],
+            public_values,
+        );
+    }
+
+    let log_degrees = traces
+        .iter()
+        .map(|trace| log2_strict_usize(trace.height()))
+        .collect_vec();
+
+    for (air, &log_degree) in airs.iter().zip(&log_degrees) {
+        assert!(
+            1 << log_degree >= pcs.min_degree(),
+            "a trace has {} rows, but the PCS needs at least {}",
+            1 << log_degree,
+            pcs.min_degree()
+        );
+        assert!(
+            valid_periods(&air.periodic_columns(), 1 << log_degree),
+            "periods of periodic columns must be powers of two no larger than the trace height"
+        );
+        assert!(
+            valid_row_selectors(&air.row_selectors(), 1 << log_degree),
+            "row selectors must have a positive step and select rows within the trace"
+        );
+    }
+
+    let trace_domains = traces
+        .iter()
+        .map(|trace| pcs.natural_domain_for_degree(trace.height()))
+        .collect_vec();
+
+    let (trace_commit, trace_data) = info_span!("commit to trace data")
+        .in_scope(|| pcs.commit(izip!(trace_domains.iter().copied(), traces).collect_vec()));
+
+    // Observe the instance.
+    challenger.observe(Val::<SC>::from_canonical_usize(airs.len()));
+    for air_data in air_data {
+        observe_statement_digest(challenger, air_data.statement_digest);
+    }
+    for &log_degree in &log_degrees {
+        challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
+    }
+
+    challenger.observe(trace_commit.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+    let alpha: SC::Challenge = challenger.sample_ext_element();
+
+    let mut quotient_degrees = Vec::with_capacity(airs.len());
+    let mut quotient_chunks_and_domains = vec![];
+    for (i, (air, public_values)) in airs.iter().zip(public_values).enumerate() {
+        let log_quotient_degree = air_data[i].log_quotient_degree;
+        let quotient_degree = 1 << log_quotient_degree;
+        let trace_domain = trace_domains[i];
+        let quotient_domain =
+            trace_domain.create_disjoint_domain(1 << (log_degrees[i] + log_quotient_degree));
+
+        let trace_on_quotient_domain =
+            pcs.get_evaluations_on_domain(&trace_data, i, quotient_domain);
+        let quotient_values = quotient_values(
+            *air,
+            public_values,
+            trace_domain,
+            quotient_domain,
+            None,
+            trace_on_quotient_domain,
+            None,
+            &[],
+            alpha,
+            &air_data[i].symbolic_constraints,
+        );
+        let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
+        let qc_domains = quotient_domain.split_domains(quotient_degree);
+        quotient_chunks_and_domains.extend(izip!(qc_domains, quotient_chunks));
+        quotient_degrees.push(quotient_degree);
+    }
+
+    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
+        .in_scope(|| pcs.commit(quotient_chunks_and_domains));
+    challenger.observe(quotient_commit.clone());
+
+    let commitments = Commitments {
+        trace: trace_commit,
+        permutation: None,
+        quotient_chunks: quotient_commit,
+    };
+
+    let zeta: SC::Challenge = challenger.sample();
+
+    let (opened_values, opening_proof) = info_span!("open")
+        .in_scope(|| {
+            let trace_points = airs
+                .iter()
+                .zip(&trace_domains)
+                .map(|(air, &domain)| window_points::<SC>(domain, zeta, air.window_size()))
+                .collect_vec();
+            // open every chunk of every quotient at zeta
+            let quotient_points = (0..quotient_degrees.iter().sum())
+                .map(|_| vec![zeta])
+                .collect_vec();
+            pcs.open(
+                vec![
+                    (&trace_data, trace_points),
+                    (&quotient_data, quotient_points),
+                ],
+                challenger,
+            )
+        })
+        .expect("the PCS extended the committed polynomials, so they are of low degree");
+
+    let mut opened_quotient_chunks = opened_values[1].iter().map(|v| v[0].clone());
+    let opened_values = opened_values[0]
+        .iter()
+        .zip(&quotient_degrees)
+        .map(|(trace_openings, &quotient_degree)| OpenedValues {
+            trace_local: trace_openings[0].clone(),
+            trace_next: trace_openings[1].clone(),
+            trace_window: trace_openings[2..].to_vec(),
+            preprocessed_local: vec![],
+            preprocessed_next: vec![],
+            permutation_local: vec![],
+            permutation_next: vec![],
+            quotient_chunks: opened_quotient_chunks
+                .by_ref()
+                .take(quotient_degree)
+                .collect(),
+            random: vec![],
+        })
+        .collect();
+
+    MultiProof {
+        commitments,
+        opened_values,
+        opening_proof,
+        degree_bits: log_degrees,
+    }
+}
@@ -0,0 +1,308 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use itertools::{izip, Itertools};
+use p3_air::{Air, BaseAir};
+use p3_challenger::{CanObserve, CanSample, FieldChallenger};
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_field::{AbstractExtensionField, AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrixView;
+use p3_matrix::stack::VerticalPair;
+use tracing::instrument;
+
+use crate::statement::observe_statement_digest;
+use crate::symbolic_builder::{get_log_quotient_degree, SymbolicAirBuilder};
+use crate::verifier::{
+    is_transition_window_at_points, periodic_values_at_point, recompose_quotient, selected_rows,
+    trace_rows, valid_periods, valid_row_selectors, window_points,
+};
+use crate::{
+    get_interactions, statement_digest, MultiProof, PcsError, StarkGenericConfig, Val,
+    VerificationError, VerifierConstraintFolder,
+};
+
+/// Verifies a proof produced by [`prove_multi`](crate::prove_multi), which doesn't support ZK mode.
+#[instrument(skip_all)]
+pub fn verify_multi<SC, A>(
+    config: &SC,
+    airs: &[A],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    // Lookups aren't supported, so there are no lookup challenges to evaluate interactions with.
+    let supported = airs
+        .iter()
+        .zip(public_values)
+        .all(|(air, public_values)| supports_air::<Val<SC>, A>(air, public_values.len()));
+    if public_values.len() != airs.len() || !supported {
+        return Err(VerificationError::InvalidProofShape);
+    }
+    let (log_quotient_degrees, digests): (Vec<_>, Vec<_>) = airs
+        .iter()
+        .zip(public_values)
+        .map(|(air, public_values)| {
+            (
+                get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len(), 0),
+                statement_digest(config, air, None, public_values.len()),
+            )
+        })
+        .unzip();
+    verify_tables(
+        config,
+        &airs.iter().collect_vec(),
+        &log_quotient_degrees,
+        &digests,
+        challenger,
+        proof,
+        public_values,
+    )
+}
+
+/// Verifies a proof produced by [`prove_many`](crate::prove_many), which doesn't support ZK mode.
+#[instrument(skip_all)]
+pub fn verify_many<SC, A>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let num_tables = public_values.len();
+    let num_public_values = public_values.first().map_or(0, Vec::len);
+    if public_values
+        .iter()
+        .any(|public_values| public_values.len() != num_public_values)
+        || !supports_air::<Val<SC>, A>(air, num_public_values)
+    {
+        return Err(VerificationError::InvalidProofShape);
+    }
+    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, num_public_values, 0);
+    let digest = statement_digest(config, air, None, num_public_values);
+    verify_tables(
+        config,
+        &vec![air; num_tables],
+        &vec![log_quotient_degree; num_tables],
+        &vec![digest; num_tables],
+        challenger,
+        proof,
+        public_values,
+    )
+}
+
+/// Whether `air` has no permutation columns or interactions, which multi-table proofs don't support.
+fn supports_air<F, A>(air: &A, num_public_values: usize) -> bool
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>>,
+{
+    <A as BaseAir<F>>::permutation_width(air) == 0
+        && get_interactions::<F, A>(air, 0, num_public_values).is_empty()
+}
+
+fn verify_tables<SC, A>(
+    config: &SC,
+    airs: &[&A],
+    log_quotient_degrees: &[usize],
+    digests: &[[u8; 32]],
+    challenger: &mut SC::Challenger,
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    let MultiProof {
+        commitments,
+        opened_values,
+        opening_proof,
+        degree_bits,
+    } = proof;
+
+    assert!(!config.is_zk(), "verify_multi does not support ZK mode");
+
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    let valid_shape = opened_values.len() == airs.len()
+        && degree_bits.len() == airs.len()
+        && public_values.len() == airs.len()
+        && commitments.permutation.is_none();
+    if !valid_shape {
+        return Err(VerificationError::InvalidProofShape);
+    }
+
+    for (air, opened_values, &bits, &log_quotient_degree) in
+        izip!(airs, opened_values, degree_bits, log_quotient_degrees)
+    {
+        let air_width = <A as BaseAir<Val<SC>>>::width(air);
+        let window_size = <A as BaseAir<Val<SC>>>::window_size(air);
+        let periodic_columns = <A as BaseAir<Val<SC>>>::periodic_columns(air);
+        let valid_shape = valid_periods(&periodic_columns, 1 << bits)
+            && valid_row_selectors(&<A as BaseAir<Val<SC>>>::row_selectors(air), 1 << bits)
+            && opened_values.trace_local.len() == air_width
+            && opened_values.trace_next.len() == air_width
+            && opened_values.trace_window.len() == window_size - 2
+            && opened_values
+                .trace_window
+                .iter()
+                .all(|row| row.len() == air_width)
+            && opened_values.preprocessed_local.is_empty()
+            && opened_values.preprocessed_next.is_empty()
+            && opened_values.permutation_local.is_empty()
+            && opened_values.permutation_next.is_empty()
+            && opened_values.random.is_empty()
+            && opened_values.quotient_chunks.len() == 1 << log_quotient_degree
+            && opened_values
+                .quotient_chunks
+                .iter()
+                .all(|qc| qc.len() == ext_degree);
+        if !valid_shape {
+            return Err(VerificationError::InvalidProofShape);
+        }
+    }
+
+    let pcs = config.pcs();
+    let trace_domains = degree_bits
+        .iter()
+        .map(|&bits| pcs.natural_domain_for_degree(1 << bits))
+        .collect_vec();
+    let quotient_chunks_domains = izip!(&trace_domains, degree_bits, log_quotient_degrees)
+        .map(|(trace_domain, &bits, &log_quotient_degree)| {
+            trace_domain
+                .create_disjoint_domain(1 << (bits + log_quotient_degree))
+                .split_domains(1 << log_quotient_degree)
+        })
+        .collect_vec();
+
+    // Observe the instance.
+    challenger.observe(Val::<SC>::from_canonical_usize(airs.len()));
+    for &digest in digests {
+        observe_statement_digest(challenger, digest);
+    }
+    for &bits in degree_bits {
+        challenger.observe(Val::<SC>::from_canonical_usize(bits));
+    }
+
+    challenger.observe(commitments.trace.clone());
+    for public_values in public_values {
+        challenger.observe_slice(public_values);
+    }
+    let alpha: SC::Challenge = challenger.sample_ext_element();
+    challenger.observe(commitments.quotient_chunks.clone());
+
+    let zeta: SC::Challenge = challenger.sample();
+    let window_points = airs
+        .iter()
+        .zip(&trace_domains)
+        .map(|(air, &domain)| {
+            window_points::<SC>(domain, zeta, <A as BaseAir<Val<SC>>>::window_size(air))
+        })
+        .collect_vec();
+
+    let rounds = vec![
+        (
+            commitments.trace.clone(),
+            izip!(&trace_domains, &window_points, opened_values)
+                .map(|(&domain, points, opened_values)| {
+                    (
+                        domain,
+                        points
+                            .iter()
+                            .copied()
+                            .zip(trace_rows(opened_values).cloned())
+                            .collect_vec(),
+                    )
+                })
+                .collect_vec(),
+        ),
+        (
+            commitments.quotient_chunks.clone(),
+            quotient_chunks_domains
+                .iter()
+                .zip(opened_values)
+                .flat_map(|(domains, opened_values)| {
+                    domains
+                        .iter()
+                        .zip(&opened_values.quotient_chunks)
+                        .map(|(&domain, values)| (domain, vec![(zeta, values.clone())]))
+                })
+                .collect_vec(),
+        ),
+    ];
+
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(VerificationError::InvalidOpeningArgument)?;
+
+    for (
+        air,
+        opened_values,
+        public_values,
+        &trace_domain,
+        quotient_chunks_domains,
+        window_points,
+    ) in izip!(
+        airs,
+        opened_values,
+        public_values,
+        &trace_domains,
+        &quotient_chunks_domains,
+        &window_points
+    ) {
+        let quotient = recompose_quotient::<SC>(
+            quotient_chunks_domains,
+            &opened_values.quotient_chunks,
+            zeta,
+        );
+
+        let row_selectors = <A as BaseAir<Val<SC>>>::row_selectors(air);
+        let sels = trace_domain
+            .selectors_at_point(zeta, &selected_rows(&row_selectors, trace_domain.size()));
+
+        let main_values = trace_rows(opened_values).flatten().copied().collect_vec();
+        let main = RowMajorMatrixView::new(&main_values, <A as BaseAir<Val<SC>>>::width(air));
+        let empty = RowMajorMatrixView::new(&[], 0);
+        let periodic_values = periodic_values_at_point::<SC>(
+            trace_domain,
+            &<A as BaseAir<Val<SC>>>::periodic_columns(air),
+            zeta,
+        );
+
+        let mut folder = VerifierConstraintFolder {
+            main,
+            preprocessed: empty,
+            permutation: VerticalPair::new(empty, empty),
+            permutation_challenges: &[],
+            lookup_offset: 0,
+            num_interactions: 0,
+            public_values,
+            periodic_values: &periodic_values,
+            row_selectors: &row_selectors,
+            row_selector_values: &sels.rows,
+            is_first_row: sels.is_first_row,
+            is_last_row: sels.is_last_row,
+            is_transition: sels.is_transition,
+            is_transition_window: is_transition_window_at_points::<SC>(trace_domain, window_points),
+            window_size: window_points.len(),
+            alpha,
+            accumulator: SC::Challenge::zero(),
+        };
+        air.eval(&mut folder);
+        let folded_constraints = folder.accumulator;
+
+        // Finally, check that
+        //     folded_constraints(zeta) / Z_H(zeta) = quotient(zeta)
+        if folded_constraints * sels.inv_zeroifier != quotient {
+            return Err(VerificationError::OodEvaluationMismatch);
+        }
+    }
+
+    Ok(())
+}
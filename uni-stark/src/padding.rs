@@ -0,0 +1,129 @@
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_ceil_usize;
+use tracing::instrument;
+
+use crate::constraint_report::check_constraints_on_rows;
+use crate::{
+    prove, verify, ConstraintReport, ConstraintReportBuilder, PcsError, Proof,
+    ProverConstraintFolder, StarkGenericConfig, StarkVerifyingKey, SymbolicAirBuilder, Val,
+    VerificationError, VerifierConstraintFolder,
+};
+
+/// Pads `trace` to a power-of-two height as described by `air.padding()`, then checks that the
+/// constraints hold on the padding rows, including the transitions into them.
+///
+/// On failure, returns the report of the constraints which failed, listing only those rows.
+#[instrument(name = "pad trace", skip_all)]
+pub fn pad_trace<F, A>(
+    air: &A,
+    mut trace: RowMajorMatrix<F>,
+    public_values: &[F],
+) -> Result<RowMajorMatrix<F>, ConstraintReport<F>>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>> + for<'a> Air<ConstraintReportBuilder<'a, F>>,
+{
+    let height = trace.height();
+    air.padding().pad(&mut trace);
+    if trace.height() == height {
+        return Ok(trace);
+    }
+
+    let first_checked_row = height.saturating_sub(air.window_size() - 1);
+    let report = check_constraints_on_rows(
+        air,
+        &trace,
+        public_values,
+        first_checked_row..trace.height(),
+    );
+    if report.is_ok() {
+        Ok(trace)
+    } else {
+        Err(report)
+    }
+}
+
+/// Proves that `trace`, whose height needn't be a power of two, satisfies `air` once padded by
+/// [`pad_trace`].
+///
+/// The number of rows of `trace` is appended to `public_values`, so the AIR can refer to it as its
+/// last public value, and the verifier must pass it to [`verify_padded`]. The proof only binds it
+/// to the padded height, i.e. it must round up to the same power of two; which rows are padding
+/// isn't constrained unless the AIR does so itself, e.g. with a column counting the rows up to this
+/// public value.
+///
+/// Panics if the padding rows don't satisfy the constraints.
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_padded<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>
+        + for<'a> Air<ConstraintReportBuilder<'a, Val<SC>>>
+        + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert!(trace.height() > 0, "the trace must have at least one row");
+    let public_values = with_num_rows(public_values, trace.height());
+    let trace = pad_trace(air, trace, &public_values).unwrap_or_else(|report| {
+        panic!(
+            "padding rows don't satisfy the constraints: {:?}",
+            report.failures
+        )
+    });
+    prove(config, air, challenger, trace, &public_values)
+}
+
+/// Verifies a proof produced by [`prove_padded`] for a trace of `num_rows` rows, against the
+/// verifying key from [`setup`](crate::setup). The key must count the appended number of rows
+/// among the public values.
+///
+/// `num_rows` is only checked to round up to the proof's trace height; see [`prove_padded`] for how
+/// the AIR can constrain it further.
+#[instrument(skip_all)]
+pub fn verify_padded<SC, A>(
+    config: &SC,
+    verifying_key: &StarkVerifyingKey<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    proof: &Proof<SC>,
+    num_rows: usize,
+    public_values: &[Val<SC>],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+{
+    if num_rows == 0 || proof.degree_bits != log2_ceil_usize(num_rows) {
+        return Err(VerificationError::InvalidProofShape);
+    }
+    let public_values = with_num_rows(public_values, num_rows);
+    verify(
+        config,
+        verifying_key,
+        air,
+        challenger,
+        proof,
+        &public_values,
+    )
+}
+
+fn with_num_rows<F: Field>(public_values: &[F], num_rows: usize) -> Vec<F> {
+    let mut public_values = public_values.to_vec();
+    public_values.push(F::from_canonical_usize(num_rows));
+    public_values
+}
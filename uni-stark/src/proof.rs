@@ -1,15 +1,12 @@
 use alloc::vec::Vec;
 
-use p3_commit::Pcs;
+use p3_commit::{Pcs, PcsProofStats};
+use p3_util::binary::encoded_len;
 use serde::{Deserialize, Serialize};
 
-use crate::StarkGenericConfig;
+use crate::{Com, StarkGenericConfig};
 
-type Com<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
-    <SC as StarkGenericConfig>::Challenge,
-    <SC as StarkGenericConfig>::Challenger,
->>::Commitment;
-type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+pub type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
     <SC as StarkGenericConfig>::Challenge,
     <SC as StarkGenericConfig>::Challenger,
 >>::Proof;
@@ -23,15 +20,219 @@ pub struct Proof<SC: StarkGenericConfig> {
     pub(crate) degree_bits: usize,
 }
 
+impl<SC: StarkGenericConfig> Proof<SC> {
+    pub const fn commitments(&self) -> &Commitments<Com<SC>> {
+        &self.commitments
+    }
+
+    pub const fn opened_values(&self) -> &OpenedValues<SC::Challenge> {
+        &self.opened_values
+    }
+
+    pub const fn opening_proof(&self) -> &PcsProof<SC> {
+        &self.opening_proof
+    }
+
+    /// The log2 of the trace height.
+    pub const fn degree_bits(&self) -> usize {
+        self.degree_bits
+    }
+
+    /// Breaks down the size of this proof by component, and counts the hashes needed to verify
+    /// it.
+    pub fn stats(&self, config: &SC) -> ProofStats {
+        let mut stats = ProofStats::new(config, &self.commitments, &self.opening_proof);
+        stats.add_opened_values(&self.opened_values);
+        stats.degree_bits = encoded_len(&self.degree_bits);
+        stats
+    }
+}
+
+/// A proof for several AIRs at once, with one commitment per round shared by all tables.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MultiProof<SC: StarkGenericConfig> {
+    pub(crate) commitments: Commitments<Com<SC>>,
+    pub(crate) opened_values: Vec<OpenedValues<SC::Challenge>>,
+    pub(crate) opening_proof: PcsProof<SC>,
+    pub(crate) degree_bits: Vec<usize>,
+}
+
+impl<SC: StarkGenericConfig> MultiProof<SC> {
+    pub const fn commitments(&self) -> &Commitments<Com<SC>> {
+        &self.commitments
+    }
+
+    /// The opened values of each table.
+    pub fn opened_values(&self) -> &[OpenedValues<SC::Challenge>] {
+        &self.opened_values
+    }
+
+    pub const fn opening_proof(&self) -> &PcsProof<SC> {
+        &self.opening_proof
+    }
+
+    /// The log2 of each trace's height.
+    pub fn degree_bits(&self) -> &[usize] {
+        &self.degree_bits
+    }
+
+    /// Breaks down the size of this proof by component, summing over all tables, and counts the
+    /// hashes needed to verify it.
+    pub fn stats(&self, config: &SC) -> ProofStats {
+        let mut stats = ProofStats::new(config, &self.commitments, &self.opening_proof);
+        let len_before_openings = stats.total();
+        for opened_values in &self.opened_values {
+            stats.add_opened_values(opened_values);
+        }
+        // Count the length prefix of the list of tables with the trace openings.
+        stats.trace_openings +=
+            encoded_len(&self.opened_values) - (stats.total() - len_before_openings);
+        stats.degree_bits = encoded_len(&self.degree_bits);
+        stats
+    }
+}
+
+/// The size of a [`Proof`] or [`MultiProof`], broken down by component, and the work needed to
+/// verify it.
+///
+/// Sizes are in bytes of the encoding written by [`Proof::to_bytes`], excluding its header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofStats {
+    pub trace_commitment: usize,
+    /// The permutation trace commitment, or the tag marking its absence.
+    pub permutation_commitment: usize,
+    pub quotient_commitment: usize,
+    /// The opened rows of the main trace, including any after the next row.
+    pub trace_openings: usize,
+    pub preprocessed_openings: usize,
+    pub permutation_openings: usize,
+    /// The opened quotient chunks, along with the random codewords in ZK mode.
+    pub quotient_openings: usize,
+    /// The log2 of the trace height(s).
+    pub degree_bits: usize,
+    pub opening_proof: PcsProofStats,
+}
+
+impl ProofStats {
+    fn new<SC: StarkGenericConfig>(
+        config: &SC,
+        commitments: &Commitments<Com<SC>>,
+        opening_proof: &PcsProof<SC>,
+    ) -> Self {
+        Self {
+            trace_commitment: encoded_len(&commitments.trace),
+            permutation_commitment: encoded_len(&commitments.permutation),
+            quotient_commitment: encoded_len(&commitments.quotient_chunks),
+            opening_proof: config.pcs().proof_stats(opening_proof),
+            ..Default::default()
+        }
+    }
+
+    fn add_opened_values<Challenge: Serialize>(&mut self, opened_values: &OpenedValues<Challenge>) {
+        self.trace_openings += encoded_len(&opened_values.trace_local)
+            + encoded_len(&opened_values.trace_next)
+            + encoded_len(&opened_values.trace_window);
+        self.preprocessed_openings += encoded_len(&opened_values.preprocessed_local)
+            + encoded_len(&opened_values.preprocessed_next);
+        self.permutation_openings += encoded_len(&opened_values.permutation_local)
+            + encoded_len(&opened_values.permutation_next);
+        self.quotient_openings +=
+            encoded_len(&opened_values.quotient_chunks) + encoded_len(&opened_values.random);
+    }
+
+    /// The size of the whole proof, in bytes.
+    pub const fn total(&self) -> usize {
+        self.trace_commitment
+            + self.permutation_commitment
+            + self.quotient_commitment
+            + self.trace_openings
+            + self.preprocessed_openings
+            + self.permutation_openings
+            + self.quotient_openings
+            + self.degree_bits
+            + self.opening_proof.total()
+    }
+
+    /// The number of hash and compression calls the verifier makes to check Merkle paths.
+    pub const fn verifier_hashes(&self) -> usize {
+        self.opening_proof.verifier_hashes
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Commitments<Com> {
     pub(crate) trace: Com,
+    pub(crate) permutation: Option<Com>,
     pub(crate) quotient_chunks: Com,
 }
 
+impl<Com> Commitments<Com> {
+    pub const fn trace(&self) -> &Com {
+        &self.trace
+    }
+
+    /// The commitment to the permutation trace, if the AIR has one.
+    pub const fn permutation(&self) -> Option<&Com> {
+        self.permutation.as_ref()
+    }
+
+    pub const fn quotient_chunks(&self) -> &Com {
+        &self.quotient_chunks
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OpenedValues<Challenge> {
     pub(crate) trace_local: Vec<Challenge>,
     pub(crate) trace_next: Vec<Challenge>,
+    /// The rows after `trace_next` in the AIR's constraint window; empty for the usual two rows.
+    pub(crate) trace_window: Vec<Vec<Challenge>>,
+    pub(crate) preprocessed_local: Vec<Challenge>,
+    pub(crate) preprocessed_next: Vec<Challenge>,
+    /// The permutation trace is committed over the base field, so each extension column is opened
+    /// as `D` consecutive base coordinates.
+    pub(crate) permutation_local: Vec<Challenge>,
+    pub(crate) permutation_next: Vec<Challenge>,
     pub(crate) quotient_chunks: Vec<Vec<Challenge>>,
+    /// The random codewords committed with the quotient chunks in ZK mode; empty otherwise.
+    pub(crate) random: Vec<Challenge>,
+}
+
+impl<Challenge> OpenedValues<Challenge> {
+    pub fn trace_local(&self) -> &[Challenge] {
+        &self.trace_local
+    }
+
+    pub fn trace_next(&self) -> &[Challenge] {
+        &self.trace_next
+    }
+
+    pub fn trace_window(&self) -> &[Vec<Challenge>] {
+        &self.trace_window
+    }
+
+    pub fn preprocessed_local(&self) -> &[Challenge] {
+        &self.preprocessed_local
+    }
+
+    pub fn preprocessed_next(&self) -> &[Challenge] {
+        &self.preprocessed_next
+    }
+
+    pub fn permutation_local(&self) -> &[Challenge] {
+        &self.permutation_local
+    }
+
+    pub fn permutation_next(&self) -> &[Challenge] {
+        &self.permutation_next
+    }
+
+    pub fn quotient_chunks(&self) -> &[Vec<Challenge>] {
+        &self.quotient_chunks
+    }
+
+    pub fn random(&self) -> &[Challenge] {
+        &self.random
+    }
 }
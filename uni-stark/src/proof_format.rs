@@ -0,0 +1,145 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use p3_commit::Pcs;
+use p3_field::{AbstractExtensionField, Field};
+use p3_util::binary::{self, BinaryError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{MultiProof, Proof, StarkGenericConfig, Val};
+
+/// The version of the binary format written by [`Proof::to_bytes`] and [`MultiProof::to_bytes`].
+///
+/// An encoded proof is a header followed by the proof's canonical encoding, as defined by
+/// [`p3_util::binary`]. The header consists of
+///
+/// - four magic bytes: `P3UP` for a [`Proof`] or `P3MP` for a [`MultiProof`],
+/// - the format version, as a little-endian `u16`,
+/// - the [`config_fingerprint`] of the configuration the proof was made with, as a little-endian
+///   `u64`.
+pub const PROOF_FORMAT_VERSION: u16 = 2;
+
+const PROOF_MAGIC: [u8; 4] = *b"P3UP";
+const MULTI_PROOF_MAGIC: [u8; 4] = *b"P3MP";
+const HEADER_LEN: usize = 4 + 2 + 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProofFormatError {
+    /// The bytes don't start with the magic bytes of the expected kind of proof.
+    BadMagic,
+    /// The proof was written in a version of the format which isn't supported.
+    UnsupportedVersion(u16),
+    /// The proof was made with a different configuration.
+    ConfigMismatch,
+    /// The proof itself is malformed or non-canonical.
+    Malformed(BinaryError),
+}
+
+impl fmt::Display for ProofFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => f.write_str("not a proof of the expected kind"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported proof format version {version}")
+            }
+            Self::ConfigMismatch => {
+                f.write_str("the proof was made with a different configuration")
+            }
+            Self::Malformed(err) => write!(f, "malformed proof: {err}"),
+        }
+    }
+}
+
+impl<SC: StarkGenericConfig> Proof<SC> {
+    /// Encodes this proof in the versioned binary format described by
+    /// [`PROOF_FORMAT_VERSION`].
+    ///
+    /// Panics if the PCS's commitments or proofs use serde features the format doesn't support,
+    /// such as floats.
+    pub fn to_bytes(&self, config: &SC) -> Vec<u8> {
+        encode(PROOF_MAGIC, config, self)
+    }
+
+    /// Decodes a proof written by [`Proof::to_bytes`] with the same configuration.
+    pub fn from_bytes(config: &SC, bytes: &[u8]) -> Result<Self, ProofFormatError> {
+        decode(PROOF_MAGIC, config, bytes)
+    }
+}
+
+impl<SC: StarkGenericConfig> MultiProof<SC> {
+    /// Encodes this proof in the versioned binary format described by
+    /// [`PROOF_FORMAT_VERSION`].
+    ///
+    /// Panics if the PCS's commitments or proofs use serde features the format doesn't support,
+    /// such as floats.
+    pub fn to_bytes(&self, config: &SC) -> Vec<u8> {
+        encode(MULTI_PROOF_MAGIC, config, self)
+    }
+
+    /// Decodes a proof written by [`MultiProof::to_bytes`] with the same configuration.
+    pub fn from_bytes(config: &SC, bytes: &[u8]) -> Result<Self, ProofFormatError> {
+        decode(MULTI_PROOF_MAGIC, config, bytes)
+    }
+}
+
+/// A fingerprint of the parts of `config` which proofs depend on.
+///
+/// This is the 64-bit FNV-1a hash of the canonical encoding of the tuple
+/// `(order, extension_degree, is_zk, pcs_parameters)`: the order of the base field as
+/// little-endian bytes, the degree of the challenge field over it, whether the configuration is
+/// zero-knowledge, and `Pcs::parameters`. It catches proofs being decoded with the wrong
+/// configuration, but isn't a commitment to the configuration: it doesn't cover the choice of
+/// hash function, for example.
+pub fn config_fingerprint<SC: StarkGenericConfig>(config: &SC) -> u64 {
+    let bytes =
+        binary::to_bytes(&config_description(config)).expect("the description is always encodable");
+    fnv1a(&bytes)
+}
+
+/// The parts of `config` which proofs depend on, as described in [`config_fingerprint`].
+pub(crate) fn config_description<SC: StarkGenericConfig>(
+    config: &SC,
+) -> (Vec<u8>, usize, usize, Vec<usize>) {
+    (
+        Val::<SC>::order().to_bytes_le(),
+        <SC::Challenge as AbstractExtensionField<Val<SC>>>::D,
+        config.is_zk() as usize,
+        config.pcs().parameters(),
+    )
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn encode<SC: StarkGenericConfig, T: Serialize>(magic: [u8; 4], config: &SC, proof: &T) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.extend(magic);
+    bytes.extend(PROOF_FORMAT_VERSION.to_le_bytes());
+    bytes.extend(config_fingerprint(config).to_le_bytes());
+    binary::serialize_into(&mut bytes, proof)
+        .expect("the proof's types must be supported by the binary format");
+    bytes
+}
+
+fn decode<SC: StarkGenericConfig, T: DeserializeOwned>(
+    magic: [u8; 4],
+    config: &SC,
+    bytes: &[u8],
+) -> Result<T, ProofFormatError> {
+    if bytes.len() < HEADER_LEN || bytes[..4] != magic {
+        return Err(ProofFormatError::BadMagic);
+    }
+    let version = u16::from_le_bytes(bytes[4..6].try_into().unwrap());
+    if version != PROOF_FORMAT_VERSION {
+        return Err(ProofFormatError::UnsupportedVersion(version));
+    }
+    let fingerprint = u64::from_le_bytes(bytes[6..HEADER_LEN].try_into().unwrap());
+    if fingerprint != config_fingerprint(config) {
+        return Err(ProofFormatError::ConfigMismatch);
+    }
+    binary::from_bytes(&bytes[HEADER_LEN..]).map_err(ProofFormatError::Malformed)
+}
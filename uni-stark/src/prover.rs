@@ -1,5 +1,6 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::cmp;
 
 use itertools::{izip, Itertools};
 use p3_air::Air;
@@ -7,21 +8,31 @@ use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{AbstractExtensionField, AbstractField, PackedValue};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::stack::HorizontalPair;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_util::{log2_ceil_usize, log2_strict_usize};
+use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
+use crate::lookup::{eval_lookup_sum, generate_lookup_trace};
+use crate::statement::observe_statement_digest;
+use crate::verifier::{selected_rows, valid_periods, valid_row_selectors, window_points};
 use crate::{
-    get_symbolic_constraints, Commitments, Domain, OpenedValues, PackedChallenge, PackedVal, Proof,
-    ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, SymbolicExpression, Val,
+    get_interactions, get_log_quotient_degree, get_symbolic_constraints, num_lookup_columns, setup,
+    Commitments, CompiledConstraints, Domain, OpenedValues, PackedChallenge, PackedVal, Proof,
+    ProverConstraintFolder, StarkGenericConfig, StarkProvingKey, SymbolicAirBuilder,
+    SymbolicExpression, Val, NUM_LOOKUP_CHALLENGES,
 };
 
+/// Proves that `trace` satisfies `air`.
+///
+/// If the AIR has a preprocessed trace, it is committed here on every call; use [`setup`] and
+/// [`prove_with_key`] to commit to it only once.
 #[instrument(skip_all)]
 #[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
 pub fn prove<
     SC,
-    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>>>,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
     #[cfg(not(debug_assertions))] A,
 >(
     config: &SC,
@@ -34,86 +45,254 @@ where
     SC: StarkGenericConfig,
     A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
 {
+    let (pk, _) = setup(config, air, public_values.len());
+    prove_with_key(config, &pk, air, challenger, trace, public_values)
+}
+
+#[instrument(skip_all)]
+#[allow(clippy::multiple_bound_locations)] // cfg not supported in where clauses?
+pub fn prove_with_key<
+    SC,
+    #[cfg(debug_assertions)] A: for<'a> Air<crate::check_constraints::DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    #[cfg(not(debug_assertions))] A,
+>(
+    config: &SC,
+    proving_key: &StarkProvingKey<SC>,
+    air: &A,
+    challenger: &mut SC::Challenger,
+    trace: RowMajorMatrix<Val<SC>>,
+    public_values: &Vec<Val<SC>>,
+) -> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>> + for<'a> Air<ProverConstraintFolder<'a, SC>>,
+{
+    assert_eq!(
+        public_values.len(),
+        proving_key.num_public_values,
+        "the proving key was set up for a different number of public values"
+    );
+    let pcs = config.pcs();
+    let preprocessed = proving_key.preprocessed.as_ref();
+    let preprocessed_width = preprocessed.map_or(0, |pp| pp.width);
+
+    let interactions = get_interactions::<Val<SC>, A>(air, preprocessed_width, public_values.len());
+    let air_permutation_width = air.permutation_width();
+    let num_air_challenges = air.num_permutation_challenges();
+    let lookup_width = num_lookup_columns(interactions.len());
+    let has_permutation = air_permutation_width + lookup_width > 0;
+    let num_permutation_challenges = if lookup_width > 0 {
+        num_air_challenges + NUM_LOOKUP_CHALLENGES
+    } else {
+        num_air_challenges
+    };
+
+    // The permutation trace is built from the main trace once it has been committed.
+    let main_trace = has_permutation.then(|| trace.clone());
+
     #[cfg(debug_assertions)]
-    crate::check_constraints::check_constraints(air, &trace, public_values);
+    if !has_permutation {
+        crate::check_constraints::check_constraints::<_, SC::Challenge, _>(
+            air,
+            preprocessed.map(|pp| &pp.trace),
+            &trace,
+            None,
+            &[],
+            public_values,
+        );
+    }
 
     let degree = trace.height();
     let log_degree = log2_strict_usize(degree);
+    assert!(
+        degree >= pcs.min_degree(),
+        "the trace has {degree} rows, but the PCS needs at least {}",
+        pcs.min_degree()
+    );
+    if let Some(pp) = preprocessed {
+        assert_eq!(
+            pp.degree_bits, log_degree,
+            "preprocessed and main traces must have the same height"
+        );
+    }
+    assert!(
+        valid_periods(&air.periodic_columns(), degree),
+        "periods of periodic columns must be powers of two no larger than the trace height"
+    );
+    assert!(
+        valid_row_selectors(&air.row_selectors(), degree),
+        "row selectors must have a positive step and select rows within the trace"
+    );
 
-    let symbolic_constraints = get_symbolic_constraints::<Val<SC>, A>(air, 0, public_values.len());
-    let constraint_count = symbolic_constraints.len();
-    let constraint_degree = symbolic_constraints
-        .iter()
-        .map(SymbolicExpression::degree_multiple)
-        .max()
-        .unwrap_or(0);
-    let log_quotient_degree = log2_ceil_usize(constraint_degree - 1);
-    let quotient_degree = 1 << log_quotient_degree;
+    let symbolic_constraints =
+        get_symbolic_constraints::<Val<SC>, A>(air, preprocessed_width, public_values.len());
+    let is_zk = config.is_zk();
+    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        public_values.len(),
+        is_zk as usize,
+    );
+    let quotient_degree = 1 << (log_quotient_degree + is_zk as usize);
 
-    let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
 
     let (trace_commit, trace_data) =
         info_span!("commit to trace data").in_scope(|| pcs.commit(vec![(trace_domain, trace)]));
 
     // Observe the instance.
+    observe_statement_digest(challenger, proving_key.statement_digest);
     challenger.observe(Val::<SC>::from_canonical_usize(log_degree));
-    // TODO: Might be best practice to include other instance data here; see verifier comment.
 
     challenger.observe(trace_commit.clone());
     challenger.observe_slice(public_values);
+
+    let permutation_challenges: Vec<SC::Challenge> = (0..num_permutation_challenges)
+        .map(|_| challenger.sample_ext_element())
+        .collect();
+    let permutation = main_trace.map(|main_trace| {
+        // The AIR's own permutation columns come first, followed by the lookup columns.
+        let air_trace = (air_permutation_width > 0).then(|| {
+            let air_trace = air
+                .permutation_trace(&main_trace, &permutation_challenges[..num_air_challenges])
+                .expect("AIR has a permutation width but built no permutation trace");
+            assert_eq!(air_trace.width(), air_permutation_width);
+            air_trace
+        });
+        let lookup_trace = (lookup_width > 0).then(|| {
+            generate_lookup_trace(
+                &interactions,
+                preprocessed.map(|pp| &pp.trace),
+                &main_trace,
+                public_values,
+                &air.periodic_columns(),
+                &air.row_selectors(),
+                permutation_challenges[num_air_challenges],
+                permutation_challenges[num_air_challenges + 1],
+            )
+        });
+        let permutation_trace = match (air_trace, lookup_trace) {
+            (Some(air_trace), Some(lookup_trace)) => {
+                HorizontalPair::new(air_trace, lookup_trace).to_row_major_matrix()
+            }
+            (air_trace, lookup_trace) => air_trace.or(lookup_trace).unwrap(),
+        };
+        assert_eq!(
+            permutation_trace.height(),
+            degree,
+            "permutation and main traces must have the same height"
+        );
+
+        #[cfg(debug_assertions)]
+        crate::check_constraints::check_constraints(
+            air,
+            preprocessed.map(|pp| &pp.trace),
+            &main_trace,
+            Some(&permutation_trace),
+            &permutation_challenges,
+            public_values,
+        );
+
+        let (permutation_commit, permutation_data) = info_span!("commit to permutation trace")
+            .in_scope(|| pcs.commit(vec![(trace_domain, permutation_trace.flatten_to_base())]));
+        challenger.observe(permutation_commit.clone());
+        (permutation_commit, permutation_data)
+    });
+
     let alpha: SC::Challenge = challenger.sample_ext_element();
 
-    let quotient_domain =
-        trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
+    let quotient_domain = trace_domain
+        .create_disjoint_domain(1 << (log_degree + log_quotient_degree + is_zk as usize));
 
     let trace_on_quotient_domain = pcs.get_evaluations_on_domain(&trace_data, 0, quotient_domain);
+    let preprocessed_on_quotient_domain =
+        preprocessed.map(|pp| pcs.get_evaluations_on_domain(&pp.data, 0, quotient_domain));
+    let permutation_on_quotient_domain = permutation
+        .as_ref()
+        .map(|(_, data)| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
 
     let quotient_values = quotient_values(
         air,
         public_values,
         trace_domain,
         quotient_domain,
+        preprocessed_on_quotient_domain,
         trace_on_quotient_domain,
+        permutation_on_quotient_domain,
+        &permutation_challenges,
         alpha,
-        constraint_count,
+        &symbolic_constraints,
     );
     let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
     let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
     let qc_domains = quotient_domain.split_domains(quotient_degree);
 
     let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
-        .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
+        .in_scope(|| pcs.commit_quotient(izip!(qc_domains, quotient_chunks).collect_vec()));
     challenger.observe(quotient_commit.clone());
 
     let commitments = Commitments {
         trace: trace_commit,
+        permutation: permutation.as_ref().map(|(commit, _)| commit.clone()),
         quotient_chunks: quotient_commit,
     };
 
     let zeta: SC::Challenge = challenger.sample();
     let zeta_next = trace_domain.next_point(zeta).unwrap();
 
-    let (opened_values, opening_proof) = info_span!("open").in_scope(|| {
-        pcs.open(
-            vec![
-                (&trace_data, vec![vec![zeta, zeta_next]]),
+    let (opened_values, opening_proof) = info_span!("open")
+        .in_scope(|| {
+            let trace_points = window_points::<SC>(trace_domain, zeta, air.window_size());
+            let mut rounds = vec![
+                (&trace_data, vec![trace_points]),
                 (
                     &quotient_data,
-                    // open every chunk at zeta
-                    (0..quotient_degree).map(|_| vec![zeta]).collect_vec(),
+                    // open every chunk, and in ZK mode the random codewords, at zeta
+                    (0..quotient_degree + is_zk as usize)
+                        .map(|_| vec![zeta])
+                        .collect_vec(),
                 ),
-            ],
-            challenger,
-        )
-    });
+            ];
+            if let Some(pp) = preprocessed {
+                rounds.push((&pp.data, vec![vec![zeta, zeta_next]]));
+            }
+            if let Some((_, permutation_data)) = &permutation {
+                rounds.push((permutation_data, vec![vec![zeta, zeta_next]]));
+            }
+            pcs.open(rounds, challenger)
+        })
+        .expect("the PCS extended the committed polynomials, so they are of low degree");
     let trace_local = opened_values[0][0][0].clone();
     let trace_next = opened_values[0][0][1].clone();
-    let quotient_chunks = opened_values[1].iter().map(|v| v[0].clone()).collect_vec();
+    let trace_window = opened_values[0][0][2..].to_vec();
+    let mut quotient_chunks = opened_values[1].iter().map(|v| v[0].clone()).collect_vec();
+    let random = if is_zk {
+        quotient_chunks.pop().unwrap()
+    } else {
+        vec![]
+    };
+    // Optional rounds follow the trace and quotient rounds, in the order they were pushed above.
+    let mut optional_rounds = opened_values[2..].iter();
+    let mut open_local_and_next = |present: bool| {
+        if present {
+            let round = optional_rounds.next().unwrap();
+            (round[0][0].clone(), round[0][1].clone())
+        } else {
+            (vec![], vec![])
+        }
+    };
+    let (preprocessed_local, preprocessed_next) = open_local_and_next(preprocessed.is_some());
+    let (permutation_local, permutation_next) = open_local_and_next(permutation.is_some());
     let opened_values = OpenedValues {
         trace_local,
         trace_next,
+        trace_window,
+        preprocessed_local,
+        preprocessed_next,
+        permutation_local,
+        permutation_next,
         quotient_chunks,
+        random,
     };
     Proof {
         commitments,
@@ -124,14 +303,18 @@ where
 }
 
 #[instrument(name = "compute quotient polynomial", skip_all)]
-fn quotient_values<SC, A, Mat>(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn quotient_values<SC, A, Mat>(
     air: &A,
     public_values: &Vec<Val<SC>>,
     trace_domain: Domain<SC>,
     quotient_domain: Domain<SC>,
+    preprocessed_on_quotient_domain: Option<Mat>,
     trace_on_quotient_domain: Mat,
+    permutation_on_quotient_domain: Option<Mat>,
+    permutation_challenges: &[SC::Challenge],
     alpha: SC::Challenge,
-    constraint_count: usize,
+    constraints: &[SymbolicExpression<Val<SC>>],
 ) -> Vec<SC::Challenge>
 where
     SC: StarkGenericConfig,
@@ -140,11 +323,48 @@ where
 {
     let quotient_size = quotient_domain.size();
     let width = trace_on_quotient_domain.width();
-    let mut sels = trace_domain.selectors_on_coset(quotient_domain);
+    let preprocessed_width = preprocessed_on_quotient_domain
+        .as_ref()
+        .map_or(0, |m| m.width());
+    let lookup_offset = air.permutation_width();
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    let permutation_width = permutation_on_quotient_domain
+        .as_ref()
+        .map_or(0, |m| m.width() / ext_degree);
+    let permutation_challenges = permutation_challenges
+        .iter()
+        .map(|&c| PackedChallenge::<SC>::from_f(c))
+        .collect_vec();
+    let row_selectors = air.row_selectors();
+    let mut sels = trace_domain.selectors_on_coset(
+        quotient_domain,
+        &selected_rows(&row_selectors, trace_domain.size()),
+    );
+    // These are periodic over the quotient domain too, so only one period of each is computed.
+    let periodic_on_quotient_domain = air
+        .periodic_columns()
+        .iter()
+        .map(|column| trace_domain.periodic_on_coset(column, quotient_domain))
+        .collect_vec();
 
     let qdb = log2_strict_usize(quotient_domain.size()) - log2_strict_usize(trace_domain.size());
     let next_step = 1 << qdb;
 
+    // For windows larger than two rows, the transition selector is the product of the usual one
+    // over every row of the window but the last.
+    let window_size = air.window_size();
+    let mut is_transition_window = if window_size > 2 {
+        (0..quotient_size)
+            .map(|i| {
+                (0..window_size - 1)
+                    .map(|j| sels.is_transition[(i + j * next_step) % quotient_size])
+                    .product()
+            })
+            .collect_vec()
+    } else {
+        sels.is_transition.clone()
+    };
+
     // We take PackedVal::<SC>::WIDTH worth of values at a time from a quotient_size slice, so we need to
     // pad with default values in the case where quotient_size is smaller than PackedVal::<SC>::WIDTH.
     for _ in quotient_size..PackedVal::<SC>::WIDTH {
@@ -152,49 +372,148 @@ where
         sels.is_last_row.push(Val::<SC>::default());
         sels.is_transition.push(Val::<SC>::default());
         sels.inv_zeroifier.push(Val::<SC>::default());
+        is_transition_window.push(Val::<SC>::default());
     }
 
-    let mut alpha_powers = alpha.powers().take(constraint_count).collect_vec();
+    // AIRs with permutation columns of their own may use extension field operations which the
+    // symbolic constraints don't capture, so those are evaluated generically.
+    let compiled_constraints =
+        (air.permutation_width() == 0).then(|| CompiledConstraints::new(constraints));
+
+    let mut alpha_powers = alpha.powers().take(constraints.len()).collect_vec();
     alpha_powers.reverse();
 
+    // The main, preprocessed and permutation rows of the constraint window of the packed rows
+    // starting at `i_start`, and their periodic values followed by their row selectors.
+    let packed_windows = |i_start: usize| {
+        let main = if window_size == 2 {
+            RowMajorMatrix::new(
+                trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step),
+                width,
+            )
+        } else {
+            RowMajorMatrix::new(
+                (0..window_size)
+                    .flat_map(|j| {
+                        trace_on_quotient_domain.vertically_packed_row(i_start + j * next_step)
+                    })
+                    .collect(),
+                width,
+            )
+        };
+        let preprocessed = RowMajorMatrix::new(
+            preprocessed_on_quotient_domain
+                .as_ref()
+                .map_or_else(Vec::new, |m| {
+                    m.vertically_packed_row_pair(i_start, next_step)
+                }),
+            preprocessed_width,
+        );
+        let permutation = RowMajorMatrix::new(
+            permutation_on_quotient_domain
+                .as_ref()
+                .map_or_else(Vec::new, |m| {
+                    m.vertically_packed_row_pair::<PackedVal<SC>>(i_start, next_step)
+                        .chunks_exact(ext_degree)
+                        .map(PackedChallenge::<SC>::from_base_slice)
+                        .collect()
+                }),
+            permutation_width,
+        );
+        let periodic = periodic_on_quotient_domain
+            .iter()
+            .chain(&sels.rows)
+            .map(|column| PackedVal::<SC>::from_fn(|k| column[(i_start + k) % column.len()]))
+            .collect_vec();
+        (main, preprocessed, permutation, periodic)
+    };
+
+    // The compiled constraints run each instruction over a batch of packed rows, to make up for
+    // the cost of dispatching it.
+    let batch_size = if compiled_constraints.is_some() {
+        cmp::max(
+            CompiledConstraints::<Val<SC>>::BATCH_ROWS / PackedVal::<SC>::WIDTH,
+            1,
+        )
+    } else {
+        1
+    };
+
     (0..quotient_size)
         .into_par_iter()
-        .step_by(PackedVal::<SC>::WIDTH)
-        .flat_map_iter(|i_start| {
-            let i_range = i_start..i_start + PackedVal::<SC>::WIDTH;
+        .step_by(PackedVal::<SC>::WIDTH * batch_size)
+        .flat_map_iter(|batch_start| {
+            let batch_end = cmp::min(
+                batch_start + PackedVal::<SC>::WIDTH * batch_size,
+                quotient_size,
+            );
+            let i_starts = (batch_start..batch_end).step_by(PackedVal::<SC>::WIDTH);
+            let windows = i_starts.clone().map(packed_windows).collect_vec();
 
-            let is_first_row = *PackedVal::<SC>::from_slice(&sels.is_first_row[i_range.clone()]);
-            let is_last_row = *PackedVal::<SC>::from_slice(&sels.is_last_row[i_range.clone()]);
-            let is_transition = *PackedVal::<SC>::from_slice(&sels.is_transition[i_range.clone()]);
-            let inv_zeroifier = *PackedVal::<SC>::from_slice(&sels.inv_zeroifier[i_range.clone()]);
+            let mut folders = i_starts
+                .clone()
+                .zip(&windows)
+                .map(|(i_start, (main, preprocessed, permutation, periodic))| {
+                    let i_range = i_start..i_start + PackedVal::<SC>::WIDTH;
+                    ProverConstraintFolder {
+                        main: main.as_view(),
+                        preprocessed: preprocessed.as_view(),
+                        permutation: permutation.as_view(),
+                        permutation_challenges: &permutation_challenges,
+                        lookup_offset,
+                        num_interactions: 0,
+                        public_values,
+                        periodic_values: &periodic[..periodic_on_quotient_domain.len()],
+                        row_selectors: &row_selectors,
+                        row_selector_values: &periodic[periodic_on_quotient_domain.len()..],
+                        is_first_row: *PackedVal::<SC>::from_slice(
+                            &sels.is_first_row[i_range.clone()],
+                        ),
+                        is_last_row: *PackedVal::<SC>::from_slice(
+                            &sels.is_last_row[i_range.clone()],
+                        ),
+                        is_transition: *PackedVal::<SC>::from_slice(
+                            &sels.is_transition[i_range.clone()],
+                        ),
+                        is_transition_window: *PackedVal::<SC>::from_slice(
+                            &is_transition_window[i_range],
+                        ),
+                        window_size,
+                        alpha_powers: &alpha_powers,
+                        accumulator: PackedChallenge::<SC>::zero(),
+                        constraint_index: 0,
+                    }
+                })
+                .collect_vec();
+            if let Some(compiled_constraints) = &compiled_constraints {
+                compiled_constraints.eval(&mut folders);
+            } else {
+                for folder in &mut folders {
+                    air.eval(folder);
+                    let num_interactions = folder.num_interactions;
+                    eval_lookup_sum(folder, lookup_offset, num_interactions);
+                }
+            }
 
-            let main = RowMajorMatrix::new(
-                trace_on_quotient_domain.vertically_packed_row_pair(i_start, next_step),
-                width,
-            );
+            i_starts
+                .zip(folders)
+                .flat_map(|(i_start, folder)| {
+                    let i_range = i_start..i_start + PackedVal::<SC>::WIDTH;
+                    let inv_zeroifier = *PackedVal::<SC>::from_slice(&sels.inv_zeroifier[i_range]);
 
-            let accumulator = PackedChallenge::<SC>::zero();
-            let mut folder = ProverConstraintFolder {
-                main: main.as_view(),
-                public_values,
-                is_first_row,
-                is_last_row,
-                is_transition,
-                alpha_powers: &alpha_powers,
-                accumulator,
-                constraint_index: 0,
-            };
-            air.eval(&mut folder);
-
-            // quotient(x) = constraints(x) / Z_H(x)
-            let quotient = folder.accumulator * inv_zeroifier;
-
-            // "Transpose" D packed base coefficients into WIDTH scalar extension coefficients.
-            (0..core::cmp::min(quotient_size, PackedVal::<SC>::WIDTH)).map(move |idx_in_packing| {
-                SC::Challenge::from_base_fn(|coeff_idx| {
-                    quotient.as_base_slice()[coeff_idx].as_slice()[idx_in_packing]
+                    // quotient(x) = constraints(x) / Z_H(x)
+                    let quotient = folder.accumulator * inv_zeroifier;
+
+                    // "Transpose" D packed base coefficients into WIDTH scalar extension coefficients.
+                    (0..cmp::min(quotient_size, PackedVal::<SC>::WIDTH)).map(
+                        move |idx_in_packing| {
+                            SC::Challenge::from_base_fn(|coeff_idx| {
+                                quotient.as_base_slice()[coeff_idx].as_slice()[idx_in_packing]
+                            })
+                        },
+                    )
                 })
-            })
+                .collect_vec()
         })
         .collect()
 }
@@ -0,0 +1,115 @@
+use alloc::vec;
+
+use p3_air::Air;
+use p3_commit::Pcs;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, instrument};
+
+use crate::{statement_digest, Com, PcsProverData, StarkGenericConfig, SymbolicAirBuilder, Val};
+
+/// Prover-side data for a committed preprocessed trace.
+pub struct PreprocessedProverData<SC: StarkGenericConfig> {
+    pub trace: RowMajorMatrix<Val<SC>>,
+    pub width: usize,
+    pub degree_bits: usize,
+    pub commitment: Com<SC>,
+    pub data: PcsProverData<SC>,
+}
+
+/// Verifier-side data for a committed preprocessed trace.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreprocessedVerifierKey<SC: StarkGenericConfig> {
+    pub width: usize,
+    pub degree_bits: usize,
+    pub commitment: Com<SC>,
+}
+
+/// Everything the prover needs to know about an AIR ahead of time.
+pub struct StarkProvingKey<SC: StarkGenericConfig> {
+    pub preprocessed: Option<PreprocessedProverData<SC>>,
+    pub num_public_values: usize,
+    /// The [`statement_digest`] which proofs start their transcripts with.
+    pub statement_digest: [u8; 32],
+}
+
+/// Everything the verifier needs to know about an AIR ahead of time.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct StarkVerifyingKey<SC: StarkGenericConfig> {
+    pub preprocessed: Option<PreprocessedVerifierKey<SC>>,
+    pub num_public_values: usize,
+    /// The [`statement_digest`] which proofs start their transcripts with.
+    pub statement_digest: [u8; 32],
+}
+
+/// Commits to the AIR's preprocessed trace, if it has one, and computes the statement digest of
+/// the AIR with `num_public_values` public values.
+///
+/// This only needs to be run once per AIR; the resulting keys can be reused for any number of
+/// proofs. If the AIR has a preprocessed trace, every main trace must have the same height.
+#[instrument(skip_all)]
+pub fn setup<SC, A>(
+    config: &SC,
+    air: &A,
+    num_public_values: usize,
+) -> (StarkProvingKey<SC>, StarkVerifyingKey<SC>)
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let Some(preprocessed_trace) = air.preprocessed_trace() else {
+        let statement_digest = statement_digest(config, air, None, num_public_values);
+        return (
+            StarkProvingKey {
+                preprocessed: None,
+                num_public_values,
+                statement_digest,
+            },
+            StarkVerifyingKey {
+                preprocessed: None,
+                num_public_values,
+                statement_digest,
+            },
+        );
+    };
+
+    let pcs = config.pcs();
+    let width = preprocessed_trace.width();
+    let degree = preprocessed_trace.height();
+    let degree_bits = log2_strict_usize(degree);
+    let domain = pcs.natural_domain_for_degree(degree);
+
+    let (commitment, data) = info_span!("commit to preprocessed trace")
+        .in_scope(|| pcs.commit(vec![(domain, preprocessed_trace.clone())]));
+    let statement_digest =
+        statement_digest(config, air, Some((width, &commitment)), num_public_values);
+
+    let vk = PreprocessedVerifierKey {
+        width,
+        degree_bits,
+        commitment: commitment.clone(),
+    };
+    let pk = PreprocessedProverData {
+        trace: preprocessed_trace,
+        width,
+        degree_bits,
+        commitment,
+        data,
+    };
+    (
+        StarkProvingKey {
+            preprocessed: Some(pk),
+            num_public_values,
+            statement_digest,
+        },
+        StarkVerifyingKey {
+            preprocessed: Some(vk),
+            num_public_values,
+            statement_digest,
+        },
+    )
+}
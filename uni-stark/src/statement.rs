@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+
+use p3_air::{Air, BaseAir, RowSelector};
+use p3_challenger::FieldChallenger;
+use p3_field::Field;
+use p3_symmetric::CryptographicHasher;
+use p3_util::binary;
+use serde::Serialize;
+
+use crate::proof_format::config_description;
+use crate::{
+    get_symbolic_constraints, Com, StarkGenericConfig, SymbolicAirBuilder, SymbolicDag,
+    SymbolicNode, Val,
+};
+
+/// A digest of the statement being proven, which the prover and verifier absorb at the start of
+/// the transcript.
+///
+/// It is the hash, by the configuration's [`statement_hasher`](StarkGenericConfig::statement_hasher),
+/// of the canonical encoding, as defined by [`p3_util::binary`], of
+///
+/// - the configuration's [`label`](StarkGenericConfig::label),
+/// - the parts of the configuration described in [`config_fingerprint`](crate::config_fingerprint),
+///   which include the FRI parameters,
+/// - the AIR's width, window size, permutation width and number of permutation challenges,
+/// - the preprocessed width and commitment, if the AIR has a preprocessed trace,
+/// - the number of public values,
+/// - the AIR's periodic columns and row selectors,
+/// - its constraints, as the [`SymbolicDag`] of `get_symbolic_constraints`.
+///
+/// Binding the transcript to all of this ensures that a proof for one AIR can't be passed off as a
+/// proof for another, even when they share a configuration. It is computed once by
+/// [`setup`](crate::setup), and kept in the proving and verifying keys.
+pub fn statement_digest<SC, A>(
+    config: &SC,
+    air: &A,
+    preprocessed: Option<(usize, &Com<SC>)>,
+    num_public_values: usize,
+) -> [u8; 32]
+where
+    SC: StarkGenericConfig,
+    A: Air<SymbolicAirBuilder<Val<SC>>>,
+{
+    let preprocessed_width = preprocessed.map_or(0, |(width, _)| width);
+    let constraints =
+        get_symbolic_constraints::<Val<SC>, A>(air, preprocessed_width, num_public_values);
+    let dag = SymbolicDag::new(&constraints);
+    let row_selectors = <A as BaseAir<Val<SC>>>::row_selectors(air)
+        .into_iter()
+        .map(|selector| match selector {
+            RowSelector::Row(row) => (0u8, row, 0),
+            RowSelector::Every { step, offset } => (1, step, offset),
+        })
+        .collect::<Vec<_>>();
+
+    let statement = Statement::<SC> {
+        label: config.label(),
+        config: config_description(config),
+        width: <A as BaseAir<Val<SC>>>::width(air),
+        window_size: <A as BaseAir<Val<SC>>>::window_size(air),
+        permutation_width: <A as BaseAir<Val<SC>>>::permutation_width(air),
+        num_permutation_challenges: <A as BaseAir<Val<SC>>>::num_permutation_challenges(air),
+        preprocessed,
+        num_public_values,
+        periodic_columns: <A as BaseAir<Val<SC>>>::periodic_columns(air),
+        row_selectors,
+        constraint_nodes: dag.nodes,
+        constraints: dag.constraints,
+    };
+    let bytes = binary::to_bytes(&statement)
+        .expect("the PCS's commitments must be supported by the binary format");
+    config.statement_hasher().hash_iter(bytes)
+}
+
+/// Absorbs a [`statement_digest`] into the challenger, one byte per field element.
+pub(crate) fn observe_statement_digest<F, C>(challenger: &mut C, digest: [u8; 32])
+where
+    F: Field,
+    C: FieldChallenger<F>,
+{
+    for byte in digest {
+        challenger.observe(F::from_canonical_u8(byte));
+    }
+}
+
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct Statement<'a, SC: StarkGenericConfig> {
+    label: &'a [u8],
+    config: (Vec<u8>, usize, usize, Vec<usize>),
+    width: usize,
+    window_size: usize,
+    permutation_width: usize,
+    num_permutation_challenges: usize,
+    preprocessed: Option<(usize, &'a Com<SC>)>,
+    num_public_values: usize,
+    periodic_columns: Vec<Vec<Val<SC>>>,
+    row_selectors: Vec<(u8, usize, usize)>,
+    constraint_nodes: Vec<SymbolicNode<Val<SC>>>,
+    constraints: Vec<usize>,
+}
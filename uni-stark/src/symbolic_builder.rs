@@ -1,29 +1,38 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, PairBuilder};
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPeriodicColumns, AirBuilderWithPublicValues,
+    AirBuilderWithRowSelectors, BaseAir, ExtensionBuilder, InteractionBuilder, PairBuilder,
+    PermutationAirBuilder, RowSelector,
+};
 use p3_field::Field;
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
 use p3_util::log2_ceil_usize;
 use tracing::instrument;
 
+use crate::lookup::{eval_lookup_sum, fingerprint, num_lookup_columns};
 use crate::symbolic_expression::SymbolicExpression;
 use crate::symbolic_variable::SymbolicVariable;
-use crate::Entry;
+use crate::{Entry, Interaction, SymbolicDag, NUM_LOOKUP_CHALLENGES};
 
 #[instrument(name = "infer log of constraint degree", skip_all)]
 pub fn get_log_quotient_degree<F, A>(
     air: &A,
     preprocessed_width: usize,
     num_public_values: usize,
+    is_zk: usize,
 ) -> usize
 where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
+    // In ZK mode the committed trace polynomials have twice the degree, which we account for by
+    // bumping the constraint degree here, and doubling the number of quotient chunks.
     // We pad to at least degree 2, since a quotient argument doesn't make sense with smaller degrees.
     let constraint_degree =
-        get_max_constraint_degree(air, preprocessed_width, num_public_values).max(2);
+        (get_max_constraint_degree(air, preprocessed_width, num_public_values) + is_zk).max(2);
 
     // The quotient's actual degree is approximately (max_constraint_degree - 1) n,
     // where subtracting 1 comes from division by the zerofier.
@@ -58,9 +67,10 @@ where
     F: Field,
     A: Air<SymbolicAirBuilder<F>>,
 {
-    let mut builder = SymbolicAirBuilder::new(preprocessed_width, air.width(), num_public_values);
+    let mut builder = SymbolicAirBuilder::new(air, preprocessed_width, num_public_values);
     air.eval(&mut builder);
-    builder.constraints()
+    builder.eval_lookup_sum();
+    SymbolicDag::new(&builder.constraints()).to_expressions()
 }
 
 /// An `AirBuilder` for evaluating constraints symbolically, and recording them for later use.
@@ -68,12 +78,28 @@ where
 pub struct SymbolicAirBuilder<F: Field> {
     preprocessed: RowMajorMatrix<SymbolicVariable<F>>,
     main: RowMajorMatrix<SymbolicVariable<F>>,
+    window_size: usize,
+    permutation: RowMajorMatrix<SymbolicVariable<F>>,
+    permutation_challenges: Vec<SymbolicVariable<F>>,
     public_values: Vec<SymbolicVariable<F>>,
+    periodic_values: Vec<SymbolicVariable<F>>,
+    row_selectors: Vec<RowSelector>,
     constraints: Vec<SymbolicExpression<F>>,
+    interactions: Vec<Interaction<SymbolicExpression<F>>>,
 }
 
 impl<F: Field> SymbolicAirBuilder<F> {
-    pub(crate) fn new(preprocessed_width: usize, width: usize, num_public_values: usize) -> Self {
+    pub(crate) fn new<A: BaseAir<F>>(
+        air: &A,
+        preprocessed_width: usize,
+        num_public_values: usize,
+    ) -> Self {
+        let width = air.width();
+        let window_size = air.window_size();
+        assert!(
+            window_size >= 2,
+            "the window must include at least the local and next rows"
+        );
         let prep_values = [0, 1]
             .into_iter()
             .flat_map(|offset| {
@@ -81,8 +107,7 @@ impl<F: Field> SymbolicAirBuilder<F> {
                     .map(move |index| SymbolicVariable::new(Entry::Preprocessed { offset }, index))
             })
             .collect();
-        let main_values = [0, 1]
-            .into_iter()
+        let main_values = (0..window_size)
             .flat_map(|offset| {
                 (0..width).map(move |index| SymbolicVariable::new(Entry::Main { offset }, index))
             })
@@ -90,17 +115,62 @@ impl<F: Field> SymbolicAirBuilder<F> {
         let public_values = (0..num_public_values)
             .map(move |index| SymbolicVariable::new(Entry::Public, index))
             .collect();
+        let periodic_values = (0..air.periodic_columns().len())
+            .map(move |index| SymbolicVariable::new(Entry::Periodic, index))
+            .collect();
         Self {
             preprocessed: RowMajorMatrix::new(prep_values, preprocessed_width),
             main: RowMajorMatrix::new(main_values, width),
+            window_size,
+            permutation: Self::permutation_matrix(air.permutation_width()),
+            permutation_challenges: Self::challenges(air.num_permutation_challenges()),
             public_values,
+            periodic_values,
+            row_selectors: air.row_selectors(),
             constraints: vec![],
+            interactions: vec![],
         }
     }
 
+    fn permutation_matrix(width: usize) -> RowMajorMatrix<SymbolicVariable<F>> {
+        let values = [0, 1]
+            .into_iter()
+            .flat_map(|offset| {
+                (0..width)
+                    .map(move |index| SymbolicVariable::new(Entry::Permutation { offset }, index))
+            })
+            .collect();
+        RowMajorMatrix::new(values, width)
+    }
+
+    fn challenges(num_challenges: usize) -> Vec<SymbolicVariable<F>> {
+        (0..num_challenges)
+            .map(move |index| SymbolicVariable::new(Entry::Challenge, index))
+            .collect()
+    }
+
+    /// Appends the lookup columns and challenges for the recorded interactions, and records the
+    /// constraints on their running sum.
+    fn eval_lookup_sum(&mut self) {
+        let num_interactions = self.interactions.len();
+        if num_interactions == 0 {
+            return;
+        }
+        let lookup_offset = self.permutation.width();
+        self.permutation =
+            Self::permutation_matrix(lookup_offset + num_lookup_columns(num_interactions));
+        self.permutation_challenges =
+            Self::challenges(self.permutation_challenges.len() + NUM_LOOKUP_CHALLENGES);
+        eval_lookup_sum(self, lookup_offset, num_interactions);
+    }
+
     pub(crate) fn constraints(self) -> Vec<SymbolicExpression<F>> {
         self.constraints
     }
+
+    pub(crate) fn interactions(self) -> Vec<Interaction<SymbolicExpression<F>>> {
+        self.interactions
+    }
 }
 
 impl<F: Field> AirBuilder for SymbolicAirBuilder<F> {
@@ -124,8 +194,10 @@ impl<F: Field> AirBuilder for SymbolicAirBuilder<F> {
     fn is_transition_window(&self, size: usize) -> Self::Expr {
         if size == 2 {
             SymbolicExpression::IsTransition
+        } else if size == self.window_size {
+            SymbolicExpression::IsTransitionWindow(size)
         } else {
-            panic!("uni-stark only supports a window size of 2")
+            panic!("uni-stark only supports transition windows of size 2 or the AIR's window_size")
         }
     }
 
@@ -141,8 +213,89 @@ impl<F: Field> AirBuilderWithPublicValues for SymbolicAirBuilder<F> {
     }
 }
 
+impl<F: Field> AirBuilderWithPeriodicColumns for SymbolicAirBuilder<F> {
+    type PeriodicVar = SymbolicVariable<F>;
+
+    fn periodic_values(&self) -> &[Self::PeriodicVar] {
+        &self.periodic_values
+    }
+}
+
+impl<F: Field> AirBuilderWithRowSelectors for SymbolicAirBuilder<F> {
+    fn row_selector(&self, selector: RowSelector) -> Self::Expr {
+        let index = row_selector_index(&self.row_selectors, selector);
+        SymbolicVariable::new(Entry::RowSelector, index).into()
+    }
+}
+
+/// The position of `selector` among those declared by `BaseAir::row_selectors`.
+pub(crate) fn row_selector_index(row_selectors: &[RowSelector], selector: RowSelector) -> usize {
+    row_selectors
+        .iter()
+        .position(|&s| s == selector)
+        .unwrap_or_else(|| panic!("{selector:?} isn't declared by BaseAir::row_selectors"))
+}
+
 impl<F: Field> PairBuilder for SymbolicAirBuilder<F> {
     fn preprocessed(&self) -> Self::M {
         self.preprocessed.clone()
     }
 }
+
+// Extension field constraints are recorded over the base field: the symbolic form is only used to
+// count constraints and infer degrees, neither of which depends on the field.
+impl<F: Field> ExtensionBuilder for SymbolicAirBuilder<F> {
+    type EF = F;
+    type ExprEF = SymbolicExpression<F>;
+    type VarEF = SymbolicVariable<F>;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.constraints.push(x.into());
+    }
+}
+
+impl<F: Field> PermutationAirBuilder for SymbolicAirBuilder<F> {
+    type MP = RowMajorMatrix<Self::Var>;
+    type RandomVar = SymbolicVariable<F>;
+
+    fn permutation(&self) -> Self::MP {
+        self.permutation.clone()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        &self.permutation_challenges
+    }
+}
+
+impl<F: Field> InteractionBuilder for SymbolicAirBuilder<F> {
+    fn send<I, E, M>(&mut self, bus: usize, values: I, multiplicity: M)
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Self::Expr>,
+        M: Into<Self::Expr>,
+    {
+        let values = values.into_iter().map(Into::into).collect::<Vec<_>>();
+        let multiplicity = multiplicity.into();
+
+        // The lookup columns and challenges follow those of the AIR itself.
+        let column = self.permutation.width() + self.interactions.len();
+        let helper = SymbolicVariable::new(Entry::Permutation { offset: 0 }, column);
+        let num_challenges = self.permutation_challenges.len();
+        let alpha: SymbolicExpression<F> =
+            SymbolicVariable::new(Entry::Challenge, num_challenges).into();
+        let beta: SymbolicExpression<F> =
+            SymbolicVariable::new(Entry::Challenge, num_challenges + 1).into();
+        let denominator = fingerprint(alpha, beta, bus, values.iter().cloned());
+        self.constraints
+            .push(helper * denominator - multiplicity.clone());
+
+        self.interactions.push(Interaction {
+            bus,
+            values,
+            multiplicity,
+        });
+    }
+}
@@ -0,0 +1,153 @@
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cmp;
+
+use hashbrown::HashMap;
+use p3_field::Field;
+use serde::Serialize;
+
+use crate::{Entry, SymbolicExpression, SymbolicVariable};
+
+/// A node of a [`SymbolicDag`]. Operands refer to earlier nodes by their index.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum SymbolicNode<F> {
+    Variable { entry: Entry, index: usize },
+    IsFirstRow,
+    IsLastRow,
+    IsTransition,
+    IsTransitionWindow(usize),
+    Constant(F),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Neg(usize),
+    Mul(usize, usize),
+}
+
+/// A list of constraints as a DAG, in which equal subexpressions are represented by a single
+/// node.
+#[derive(Clone, Debug)]
+pub struct SymbolicDag<F> {
+    /// The nodes, in topological order: operands come before the nodes using them.
+    pub nodes: Vec<SymbolicNode<F>>,
+    /// The node of each constraint.
+    pub constraints: Vec<usize>,
+}
+
+impl<F: Field> SymbolicDag<F> {
+    pub fn new(constraints: &[SymbolicExpression<F>]) -> Self {
+        let mut builder = DagBuilder {
+            nodes: Vec::new(),
+            node_ids: HashMap::new(),
+            visited: HashMap::new(),
+        };
+        let constraints = constraints
+            .iter()
+            .map(|constraint| builder.add_expr(constraint))
+            .collect();
+        Self {
+            nodes: builder.nodes,
+            constraints,
+        }
+    }
+
+    /// Converts the DAG back to expressions, in which every shared node is a shared `Rc`.
+    pub fn to_expressions(&self) -> Vec<SymbolicExpression<F>> {
+        let mut exprs: Vec<Rc<SymbolicExpression<F>>> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let expr = match *node {
+                SymbolicNode::Variable { entry, index } => {
+                    SymbolicExpression::Variable(SymbolicVariable::new(entry, index))
+                }
+                SymbolicNode::IsFirstRow => SymbolicExpression::IsFirstRow,
+                SymbolicNode::IsLastRow => SymbolicExpression::IsLastRow,
+                SymbolicNode::IsTransition => SymbolicExpression::IsTransition,
+                SymbolicNode::IsTransitionWindow(size) => {
+                    SymbolicExpression::IsTransitionWindow(size)
+                }
+                SymbolicNode::Constant(c) => SymbolicExpression::Constant(c),
+                SymbolicNode::Add(x, y) => SymbolicExpression::Add {
+                    x: exprs[x].clone(),
+                    y: exprs[y].clone(),
+                    degree_multiple: cmp::max(
+                        exprs[x].degree_multiple(),
+                        exprs[y].degree_multiple(),
+                    ),
+                },
+                SymbolicNode::Sub(x, y) => SymbolicExpression::Sub {
+                    x: exprs[x].clone(),
+                    y: exprs[y].clone(),
+                    degree_multiple: cmp::max(
+                        exprs[x].degree_multiple(),
+                        exprs[y].degree_multiple(),
+                    ),
+                },
+                SymbolicNode::Neg(x) => SymbolicExpression::Neg {
+                    x: exprs[x].clone(),
+                    degree_multiple: exprs[x].degree_multiple(),
+                },
+                SymbolicNode::Mul(x, y) => SymbolicExpression::Mul {
+                    x: exprs[x].clone(),
+                    y: exprs[y].clone(),
+                    degree_multiple: exprs[x].degree_multiple() + exprs[y].degree_multiple(),
+                },
+            };
+            exprs.push(Rc::new(expr));
+        }
+        self.constraints
+            .iter()
+            .map(|&constraint| (*exprs[constraint]).clone())
+            .collect()
+    }
+}
+
+struct DagBuilder<F> {
+    nodes: Vec<SymbolicNode<F>>,
+    node_ids: HashMap<SymbolicNode<F>, usize>,
+    /// The nodes of the subexpressions visited so far, by address, so that each `Rc` shared
+    /// within the expressions is only traversed once.
+    visited: HashMap<*const SymbolicExpression<F>, usize>,
+}
+
+impl<F: Field> DagBuilder<F> {
+    fn add_rc(&mut self, expr: &Rc<SymbolicExpression<F>>) -> usize {
+        let ptr = Rc::as_ptr(expr);
+        if let Some(&id) = self.visited.get(&ptr) {
+            return id;
+        }
+        let id = self.add_expr(expr);
+        self.visited.insert(ptr, id);
+        id
+    }
+
+    fn add_expr(&mut self, expr: &SymbolicExpression<F>) -> usize {
+        let node = match expr {
+            SymbolicExpression::Variable(v) => SymbolicNode::Variable {
+                entry: v.entry,
+                index: v.index,
+            },
+            SymbolicExpression::IsFirstRow => SymbolicNode::IsFirstRow,
+            SymbolicExpression::IsLastRow => SymbolicNode::IsLastRow,
+            SymbolicExpression::IsTransition => SymbolicNode::IsTransition,
+            SymbolicExpression::IsTransitionWindow(size) => SymbolicNode::IsTransitionWindow(*size),
+            SymbolicExpression::Constant(c) => SymbolicNode::Constant(*c),
+            // Addition and multiplication are commutative, so their operands are sorted to find
+            // more common subexpressions.
+            SymbolicExpression::Add { x, y, .. } => {
+                let (x, y) = (self.add_rc(x), self.add_rc(y));
+                SymbolicNode::Add(x.min(y), x.max(y))
+            }
+            SymbolicExpression::Sub { x, y, .. } => {
+                SymbolicNode::Sub(self.add_rc(x), self.add_rc(y))
+            }
+            SymbolicExpression::Neg { x, .. } => SymbolicNode::Neg(self.add_rc(x)),
+            SymbolicExpression::Mul { x, y, .. } => {
+                let (x, y) = (self.add_rc(x), self.add_rc(y));
+                SymbolicNode::Mul(x.min(y), x.max(y))
+            }
+        };
+        *self.node_ids.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+}
@@ -15,6 +15,8 @@ pub enum SymbolicExpression<F> {
     IsFirstRow,
     IsLastRow,
     IsTransition,
+    /// Vanishes on the last `size - 1` rows, for constraints spanning a window of `size` rows.
+    IsTransitionWindow(usize),
     Constant(F),
     Add {
         x: Rc<Self>,
@@ -45,6 +47,9 @@ impl<F> SymbolicExpression<F> {
             SymbolicExpression::IsFirstRow => 1,
             SymbolicExpression::IsLastRow => 1,
             SymbolicExpression::IsTransition => 0,
+            // Like `IsTransition`, this vanishes on a few rows at the end of the trace, and its
+            // degree doesn't grow with `n`.
+            SymbolicExpression::IsTransitionWindow(_) => 0,
             SymbolicExpression::Constant(_) => 0,
             SymbolicExpression::Add {
                 degree_multiple, ..
@@ -142,6 +147,7 @@ where
         let rhs = rhs.into();
         match (self, rhs) {
             (Self::Constant(lhs), Self::Constant(rhs)) => Self::Constant(lhs + rhs),
+            (Self::Constant(zero), expr) | (expr, Self::Constant(zero)) if zero.is_zero() => expr,
             (lhs, rhs) => {
                 let degree_multiple = cmp::max(lhs.degree_multiple(), rhs.degree_multiple());
                 Self::Add {
@@ -184,6 +190,8 @@ where
         let rhs = rhs.into();
         match (self, rhs) {
             (Self::Constant(lhs), Self::Constant(rhs)) => Self::Constant(lhs - rhs),
+            (lhs, Self::Constant(zero)) if zero.is_zero() => lhs,
+            (Self::Constant(zero), rhs) if zero.is_zero() => -rhs,
             (lhs, rhs) => {
                 let degree_multiple = cmp::max(lhs.degree_multiple(), rhs.degree_multiple());
                 Self::Sub {
@@ -211,6 +219,7 @@ impl<F: Field> Neg for SymbolicExpression<F> {
     fn neg(self) -> Self {
         match self {
             Self::Constant(c) => Self::Constant(-c),
+            Self::Neg { x, .. } => (*x).clone(),
             expr => {
                 let degree_multiple = expr.degree_multiple();
                 Self::Neg {
@@ -232,6 +241,8 @@ where
         let rhs = rhs.into();
         match (self, rhs) {
             (Self::Constant(lhs), Self::Constant(rhs)) => Self::Constant(lhs * rhs),
+            (Self::Constant(zero), _) | (_, Self::Constant(zero)) if zero.is_zero() => Self::zero(),
+            (Self::Constant(one), expr) | (expr, Self::Constant(one)) if one.is_one() => expr,
             (lhs, rhs) => {
                 #[allow(clippy::suspicious_arithmetic_impl)]
                 let degree_multiple = lhs.degree_multiple() + rhs.degree_multiple();
@@ -2,19 +2,23 @@ use core::marker::PhantomData;
 use core::ops::{Add, Mul, Sub};
 
 use p3_field::Field;
+use serde::Serialize;
 
 use crate::symbolic_expression::SymbolicExpression;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum Entry {
     Preprocessed { offset: usize },
     Main { offset: usize },
     Permutation { offset: usize },
     Public,
+    Periodic,
+    RowSelector,
     Challenge,
 }
 
-/// A variable within the evaluation window, i.e. a column in either the local or next row.
+/// A variable within the evaluation window, i.e. a column in the row `offset` rows after the local
+/// one. For `Entry::RowSelector`, `index` is the position in `BaseAir::row_selectors()`.
 #[derive(Copy, Clone, Debug)]
 pub struct SymbolicVariable<F> {
     pub entry: Entry,
@@ -33,7 +37,11 @@ impl<F> SymbolicVariable<F> {
 
     pub const fn degree_multiple(&self) -> usize {
         match self.entry {
-            Entry::Preprocessed { .. } | Entry::Main { .. } | Entry::Permutation { .. } => 1,
+            Entry::Preprocessed { .. }
+            | Entry::Main { .. }
+            | Entry::Permutation { .. }
+            | Entry::Periodic
+            | Entry::RowSelector => 1,
             Entry::Public | Entry::Challenge => 0,
         }
     }
@@ -0,0 +1,193 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use p3_air::{Air, BaseAir};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use serde::Serialize;
+
+use crate::constraint_report::RowChecker;
+use crate::{
+    export_constraints, get_interactions, ConstraintFailure, ConstraintReportBuilder,
+    ExportedConstraints, SymbolicAirBuilder,
+};
+
+/// A few consecutive rows of a main trace, with the constraints failing on them.
+///
+/// Its `Display` implementation prints one line per column, with a column per row, which stays
+/// readable for wide traces. Rows on which a constraint fails are marked with `!`, and the columns
+/// read by failing constraints with `*`.
+#[derive(Clone, Debug)]
+pub struct TraceWindow<F: Field> {
+    /// The rows shown.
+    pub rows: Range<usize>,
+    /// The height of the whole trace.
+    pub height: usize,
+    /// The values of each row in `rows`.
+    pub values: Vec<Vec<F>>,
+    /// The constraints failing on `rows`, ordered by row and then by constraint index.
+    pub failures: Vec<ConstraintFailure<F>>,
+    /// The constraints of the AIR, which name the columns.
+    pub constraints: ExportedConstraints<F>,
+}
+
+/// Collects the rows of `main` at most `context` rows away from `row`, and evaluates the
+/// constraints of `air` on them, e.g. to investigate a failure found by
+/// [`check_constraints_report`](crate::check_constraints_report).
+///
+/// Columns are named by `BaseAir::column_names`, if any. Panics if the AIR has permutation columns
+/// or interactions, which aren't supported.
+pub fn trace_window<F, A>(
+    air: &A,
+    main: &RowMajorMatrix<F>,
+    public_values: &[F],
+    row: usize,
+    context: usize,
+) -> TraceWindow<F>
+where
+    F: Field,
+    A: Air<SymbolicAirBuilder<F>> + for<'a> Air<ConstraintReportBuilder<'a, F>>,
+{
+    let height = main.height();
+    assert!(
+        row < height,
+        "row {row} is outside a trace of height {height}"
+    );
+    let rows = row.saturating_sub(context)..(row + context + 1).min(height);
+
+    let checker = RowChecker::new(air, height);
+    let preprocessed_width = checker.preprocessed_width();
+    assert!(
+        air.permutation_width() == 0
+            && get_interactions::<F, A>(air, preprocessed_width, public_values.len()).is_empty(),
+        "AIRs with permutation columns or interactions aren't supported"
+    );
+    let failures = rows
+        .clone()
+        .flat_map(|i| checker.failures(i, public_values, |j| row_values(main, j)))
+        .collect();
+
+    TraceWindow {
+        values: rows.clone().map(|i| row_values(main, i).to_vec()).collect(),
+        rows,
+        height,
+        failures,
+        constraints: export_constraints(air, preprocessed_width, public_values.len()),
+    }
+}
+
+impl<F: Field> fmt::Display for TraceWindow<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "rows {}..{} of {}",
+            self.rows.start, self.rows.end, self.height
+        )?;
+
+        let names = column_labels(&self.constraints.column_names, self.constraints.main_width);
+        let mut involved = Vec::new();
+        for failure in &self.failures {
+            involved.extend(self.constraints.main_columns(failure.constraint_index));
+        }
+
+        // The table's cells, with the header row first and the column names in the first column.
+        let header = core::iter::once("row".to_string()).chain(self.rows.clone().map(|i| {
+            let fails = self.failures.iter().any(|failure| failure.row == i);
+            format!("{i}{}", if fails { "!" } else { "" })
+        }));
+        let lines = names.into_iter().enumerate().map(|(column, name)| {
+            let mark = if involved.contains(&column) { "*" } else { "" };
+            core::iter::once(format!("{name}{mark}"))
+                .chain(self.values.iter().map(|row| row[column].to_string()))
+                .collect::<Vec<_>>()
+        });
+        let table: Vec<Vec<String>> = core::iter::once(header.collect()).chain(lines).collect();
+
+        let num_cells = table[0].len();
+        let widths: Vec<usize> = (0..num_cells)
+            .map(|i| table.iter().map(|line| line[i].len()).max().unwrap_or(0))
+            .collect();
+        for line in &table {
+            write!(f, "{:<width$}", line[0], width = widths[0])?;
+            for (cell, &width) in line.iter().zip(&widths).skip(1) {
+                write!(f, "  {cell:>width$}")?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.failures.is_empty() {
+            writeln!(f, "failing constraints:")?;
+        }
+        for failure in &self.failures {
+            writeln!(
+                f,
+                "  row {}, constraint {} = {}: {}",
+                failure.row,
+                failure.constraint_index,
+                failure.value,
+                self.constraints.constraints[failure.constraint_index]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn row_values<F>(main: &RowMajorMatrix<F>, i: usize) -> &[F] {
+    &main.values[i * main.width..][..main.width]
+}
+
+/// The names of the main columns, or `main[i]` if unnamed, as in constraint formulas.
+fn column_labels(names: &[String], width: usize) -> Vec<String> {
+    if names.is_empty() {
+        (0..width).map(|i| format!("main[{i}]")).collect()
+    } else {
+        names.to_vec()
+    }
+}
+
+/// Exports a main trace of `air` as CSV, with a header row of column names and the row index in
+/// the first column.
+pub fn trace_to_csv<F: Field, A: BaseAir<F>>(air: &A, main: &RowMajorMatrix<F>) -> String {
+    let mut csv = String::new();
+    let names = column_labels(&air.column_names(), main.width());
+    csv.push_str("row");
+    for name in names {
+        csv.push(',');
+        if name.contains([',', '"', '\n']) {
+            csv.push_str(&format!("\"{}\"", name.replace('"', "\"\"")));
+        } else {
+            csv.push_str(&name);
+        }
+    }
+    csv.push('\n');
+    for i in 0..main.height() {
+        csv.push_str(&i.to_string());
+        for value in row_values(main, i) {
+            csv.push(',');
+            csv.push_str(&value.to_string());
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct TraceJson<'a, F: Field> {
+    columns: Vec<String>,
+    rows: Vec<&'a [F]>,
+}
+
+/// Exports a main trace of `air` as a JSON object with a list of column names, `columns`, and a
+/// list of rows of values, `rows`.
+pub fn trace_to_json<F: Field, A: BaseAir<F>>(air: &A, main: &RowMajorMatrix<F>) -> String {
+    let trace = TraceJson {
+        columns: column_labels(&air.column_names(), main.width()),
+        rows: (0..main.height()).map(|i| row_values(main, i)).collect(),
+    };
+    serde_json::to_string(&trace).expect("traces are serializable")
+}
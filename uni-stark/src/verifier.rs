@@ -1,21 +1,33 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::iter;
 
 use itertools::Itertools;
-use p3_air::{Air, BaseAir};
+use p3_air::{Air, BaseAir, RowSelector};
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
-use p3_commit::{Pcs, PolynomialSpace};
+use p3_commit::{Pcs, PolynomialSpace, RowProgression};
 use p3_field::{AbstractExtensionField, AbstractField, Field};
 use p3_matrix::dense::RowMajorMatrixView;
 use p3_matrix::stack::VerticalPair;
 use tracing::instrument;
 
+use crate::lookup::eval_lookup_sum;
+use crate::statement::observe_statement_digest;
 use crate::symbolic_builder::{get_log_quotient_degree, SymbolicAirBuilder};
-use crate::{PcsError, Proof, StarkGenericConfig, Val, VerifierConstraintFolder};
+use crate::{
+    get_interactions, num_lookup_columns, Domain, OpenedValues, PcsError, Proof,
+    StarkGenericConfig, StarkVerifyingKey, Val, VerifierConstraintFolder, NUM_LOOKUP_CHALLENGES,
+};
 
+/// Verifies a proof produced by [`prove`](crate::prove) or
+/// [`prove_with_key`](crate::prove_with_key), against the verifying key that [`setup`](crate::setup)
+/// returns for the AIR.
+///
+/// The key only needs to be computed once per AIR, and can be reused for any number of proofs.
 #[instrument(skip_all)]
 pub fn verify<SC, A>(
     config: &SC,
+    verifying_key: &StarkVerifyingKey<SC>,
     air: &A,
     challenger: &mut SC::Challenger,
     proof: &Proof<SC>,
@@ -32,115 +44,204 @@ where
         degree_bits,
     } = proof;
 
+    if public_values.len() != verifying_key.num_public_values {
+        return Err(VerificationError::InvalidProofShape);
+    }
+    let preprocessed = verifying_key.preprocessed.as_ref();
+    let preprocessed_width = preprocessed.map_or(0, |pp| pp.width);
+
     let degree = 1 << degree_bits;
-    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(air, 0, public_values.len());
-    let quotient_degree = 1 << log_quotient_degree;
+    let is_zk = config.is_zk();
+    let log_quotient_degree = get_log_quotient_degree::<Val<SC>, A>(
+        air,
+        preprocessed_width,
+        public_values.len(),
+        is_zk as usize,
+    );
+    let quotient_degree = 1 << (log_quotient_degree + is_zk as usize);
 
     let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(degree);
-    let quotient_domain =
-        trace_domain.create_disjoint_domain(1 << (degree_bits + log_quotient_degree));
+    let quotient_domain = trace_domain
+        .create_disjoint_domain(1 << (degree_bits + log_quotient_degree + is_zk as usize));
     let quotient_chunks_domains = quotient_domain.split_domains(quotient_degree);
 
     let air_width = <A as BaseAir<Val<SC>>>::width(air);
+    let num_interactions =
+        get_interactions::<Val<SC>, A>(air, preprocessed_width, public_values.len()).len();
+    let lookup_offset = <A as BaseAir<Val<SC>>>::permutation_width(air);
+    let permutation_width = lookup_offset + num_lookup_columns(num_interactions);
+    let ext_degree = <SC::Challenge as AbstractExtensionField<Val<SC>>>::D;
+    let window_size = <A as BaseAir<Val<SC>>>::window_size(air);
+    let periodic_columns = <A as BaseAir<Val<SC>>>::periodic_columns(air);
+    let row_selectors = <A as BaseAir<Val<SC>>>::row_selectors(air);
     let valid_shape = opened_values.trace_local.len() == air_width
         && opened_values.trace_next.len() == air_width
+        && opened_values.trace_window.len() == window_size - 2
+        && opened_values
+            .trace_window
+            .iter()
+            .all(|row| row.len() == air_width)
+        && opened_values.preprocessed_local.len() == preprocessed_width
+        && opened_values.preprocessed_next.len() == preprocessed_width
+        && preprocessed.is_none_or(|pp| pp.degree_bits == *degree_bits)
+        && valid_periods(&periodic_columns, degree)
+        && valid_row_selectors(&row_selectors, degree)
+        && commitments.permutation.is_some() == (permutation_width > 0)
+        && opened_values.permutation_local.len() == permutation_width * ext_degree
+        && opened_values.permutation_next.len() == permutation_width * ext_degree
         && opened_values.quotient_chunks.len() == quotient_degree
         && opened_values
             .quotient_chunks
             .iter()
-            .all(|qc| qc.len() == <SC::Challenge as AbstractExtensionField<Val<SC>>>::D);
+            .all(|qc| qc.len() == ext_degree)
+        && opened_values.random.is_empty() != is_zk
+        && opened_values.random.len() == pcs.num_random_codewords();
     if !valid_shape {
         return Err(VerificationError::InvalidProofShape);
     }
 
     // Observe the instance.
+    observe_statement_digest(challenger, verifying_key.statement_digest);
     challenger.observe(Val::<SC>::from_canonical_usize(proof.degree_bits));
-    // TODO: Might be best practice to include other instance data here in the transcript, like some
-    // encoding of the AIR. This protects against transcript collisions between distinct instances.
-    // Practically speaking though, the only related known attack is from failing to include public
-    // values. It's not clear if failing to include other instance data could enable a transcript
-    // collision, since most such changes would completely change the set of satisfying witnesses.
 
     challenger.observe(commitments.trace.clone());
     challenger.observe_slice(public_values);
+
+    let mut num_permutation_challenges = <A as BaseAir<Val<SC>>>::num_permutation_challenges(air);
+    if num_interactions > 0 {
+        num_permutation_challenges += NUM_LOOKUP_CHALLENGES;
+    }
+    let permutation_challenges: Vec<SC::Challenge> = (0..num_permutation_challenges)
+        .map(|_| challenger.sample_ext_element())
+        .collect();
+    if let Some(permutation_commit) = &commitments.permutation {
+        challenger.observe(permutation_commit.clone());
+    }
+
     let alpha: SC::Challenge = challenger.sample_ext_element();
     challenger.observe(commitments.quotient_chunks.clone());
 
     let zeta: SC::Challenge = challenger.sample();
     let zeta_next = trace_domain.next_point(zeta).unwrap();
+    let window_points = window_points::<SC>(trace_domain, zeta, window_size);
 
-    pcs.verify(
-        vec![
-            (
-                commitments.trace.clone(),
-                vec![(
-                    trace_domain,
-                    vec![
-                        (zeta, opened_values.trace_local.clone()),
-                        (zeta_next, opened_values.trace_next.clone()),
-                    ],
-                )],
-            ),
-            (
-                commitments.quotient_chunks.clone(),
-                quotient_chunks_domains
+    let mut rounds = vec![
+        (
+            commitments.trace.clone(),
+            vec![(
+                trace_domain,
+                window_points
                     .iter()
-                    .zip(&opened_values.quotient_chunks)
-                    .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+                    .copied()
+                    .zip(trace_rows(opened_values).cloned())
                     .collect_vec(),
-            ),
-        ],
-        opening_proof,
-        challenger,
-    )
-    .map_err(VerificationError::InvalidOpeningArgument)?;
-
-    let zps = quotient_chunks_domains
-        .iter()
-        .enumerate()
-        .map(|(i, domain)| {
+            )],
+        ),
+        (
+            commitments.quotient_chunks.clone(),
             quotient_chunks_domains
                 .iter()
-                .enumerate()
-                .filter(|(j, _)| *j != i)
-                .map(|(_, other_domain)| {
-                    other_domain.zp_at_point(zeta)
-                        * other_domain.zp_at_point(domain.first_point()).inverse()
-                })
-                .product::<SC::Challenge>()
-        })
-        .collect_vec();
+                .zip(&opened_values.quotient_chunks)
+                .map(|(domain, values)| (*domain, vec![(zeta, values.clone())]))
+                // The random codewords share the domain of the first chunk.
+                .chain(is_zk.then(|| {
+                    (
+                        quotient_chunks_domains[0],
+                        vec![(zeta, opened_values.random.clone())],
+                    )
+                }))
+                .collect_vec(),
+        ),
+    ];
+    if let Some(pp) = preprocessed {
+        rounds.push((
+            pp.commitment.clone(),
+            vec![(
+                trace_domain,
+                vec![
+                    (zeta, opened_values.preprocessed_local.clone()),
+                    (zeta_next, opened_values.preprocessed_next.clone()),
+                ],
+            )],
+        ));
+    }
 
-    let quotient = opened_values
-        .quotient_chunks
-        .iter()
-        .enumerate()
-        .map(|(ch_i, ch)| {
-            ch.iter()
-                .enumerate()
-                .map(|(e_i, &c)| zps[ch_i] * SC::Challenge::monomial(e_i) * c)
-                .sum::<SC::Challenge>()
-        })
-        .sum::<SC::Challenge>();
+    if let Some(permutation_commit) = &commitments.permutation {
+        rounds.push((
+            permutation_commit.clone(),
+            vec![(
+                trace_domain,
+                vec![
+                    (zeta, opened_values.permutation_local.clone()),
+                    (zeta_next, opened_values.permutation_next.clone()),
+                ],
+            )],
+        ));
+    }
+
+    pcs.verify(rounds, opening_proof, challenger)
+        .map_err(VerificationError::InvalidOpeningArgument)?;
+
+    let quotient = recompose_quotient::<SC>(
+        &quotient_chunks_domains,
+        &opened_values.quotient_chunks,
+        zeta,
+    );
 
-    let sels = trace_domain.selectors_at_point(zeta);
+    let sels = trace_domain.selectors_at_point(zeta, &selected_rows(&row_selectors, degree));
 
-    let main = VerticalPair::new(
-        RowMajorMatrixView::new_row(&opened_values.trace_local),
-        RowMajorMatrixView::new_row(&opened_values.trace_next),
+    let main_values = trace_rows(opened_values).flatten().copied().collect_vec();
+    let main = RowMajorMatrixView::new(&main_values, air_width);
+    let preprocessed_values = [
+        opened_values.preprocessed_local.clone(),
+        opened_values.preprocessed_next.clone(),
+    ]
+    .concat();
+    let preprocessed = RowMajorMatrixView::new(&preprocessed_values, preprocessed_width);
+
+    // Recombine the opened base coordinates of each permutation column.
+    let unflatten = |values: &[SC::Challenge]| {
+        values
+            .chunks_exact(ext_degree)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(e_i, &c)| SC::Challenge::monomial(e_i) * c)
+                    .sum::<SC::Challenge>()
+            })
+            .collect_vec()
+    };
+    let permutation_local = unflatten(&opened_values.permutation_local);
+    let permutation_next = unflatten(&opened_values.permutation_next);
+    let permutation = VerticalPair::new(
+        RowMajorMatrixView::new_row(&permutation_local),
+        RowMajorMatrixView::new_row(&permutation_next),
     );
+    let periodic_values = periodic_values_at_point::<SC>(trace_domain, &periodic_columns, zeta);
 
     let mut folder = VerifierConstraintFolder {
         main,
+        preprocessed,
+        permutation,
+        permutation_challenges: &permutation_challenges,
+        lookup_offset,
+        num_interactions: 0,
         public_values,
+        periodic_values: &periodic_values,
+        row_selectors: &row_selectors,
+        row_selector_values: &sels.rows,
         is_first_row: sels.is_first_row,
         is_last_row: sels.is_last_row,
         is_transition: sels.is_transition,
+        is_transition_window: is_transition_window_at_points::<SC>(trace_domain, &window_points),
+        window_size,
         alpha,
         accumulator: SC::Challenge::zero(),
     };
     air.eval(&mut folder);
+    eval_lookup_sum(&mut folder, lookup_offset, num_interactions);
     let folded_constraints = folder.accumulator;
 
     // Finally, check that
@@ -152,6 +253,115 @@ where
     Ok(())
 }
 
+/// The points at which the rows of a constraint window of `window_size` rows are opened, starting
+/// with `zeta`.
+pub(crate) fn window_points<SC: StarkGenericConfig>(
+    trace_domain: Domain<SC>,
+    zeta: SC::Challenge,
+    window_size: usize,
+) -> Vec<SC::Challenge> {
+    iter::successors(Some(zeta), |&point| trace_domain.next_point(point))
+        .take(window_size)
+        .collect()
+}
+
+/// Whether every period of the AIR's periodic columns is a power of two no larger than the trace.
+pub(crate) fn valid_periods<F>(periodic_columns: &[Vec<F>], degree: usize) -> bool {
+    periodic_columns
+        .iter()
+        .all(|column| column.len().is_power_of_two() && column.len() <= degree)
+}
+
+/// Evaluates each of the AIR's periodic columns at `point`.
+pub(crate) fn periodic_values_at_point<SC: StarkGenericConfig>(
+    trace_domain: Domain<SC>,
+    periodic_columns: &[Vec<Val<SC>>],
+    point: SC::Challenge,
+) -> Vec<SC::Challenge> {
+    periodic_columns
+        .iter()
+        .map(|column| trace_domain.periodic_at_point(column, point))
+        .collect()
+}
+
+/// Whether every row selector has a positive step, and selects rows within the trace.
+pub(crate) fn valid_row_selectors(row_selectors: &[RowSelector], degree: usize) -> bool {
+    row_selectors.iter().all(|selector| match *selector {
+        RowSelector::Row(row) => row < degree,
+        RowSelector::Every { step, .. } => step > 0,
+    })
+}
+
+/// The rows selected by each of the AIR's row selectors, in a trace of the given degree.
+pub(crate) fn selected_rows(row_selectors: &[RowSelector], degree: usize) -> Vec<RowProgression> {
+    row_selectors
+        .iter()
+        .map(|selector| match *selector {
+            RowSelector::Row(row) => RowProgression::single(row),
+            RowSelector::Every { step, offset } => RowProgression {
+                offset,
+                step,
+                len: degree.saturating_sub(offset).div_ceil(step),
+            },
+        })
+        .collect()
+}
+
+/// The rows of the main trace's constraint window, as opened in `opened_values`.
+pub(crate) fn trace_rows<Challenge>(
+    opened_values: &OpenedValues<Challenge>,
+) -> impl Iterator<Item = &Vec<Challenge>> {
+    [&opened_values.trace_local, &opened_values.trace_next]
+        .into_iter()
+        .chain(&opened_values.trace_window)
+}
+
+/// Evaluates the selector of `is_transition_window(window_points.len())`, which is the product of
+/// the transition selector over every point of the window but the last.
+pub(crate) fn is_transition_window_at_points<SC: StarkGenericConfig>(
+    trace_domain: Domain<SC>,
+    window_points: &[SC::Challenge],
+) -> SC::Challenge {
+    window_points[..window_points.len() - 1]
+        .iter()
+        .map(|&point| trace_domain.selectors_at_point(point, &[]).is_transition)
+        .product()
+}
+
+/// Recombines the quotient's chunks, opened at `zeta`, into the quotient's value at `zeta`.
+pub(crate) fn recompose_quotient<SC: StarkGenericConfig>(
+    quotient_chunks_domains: &[Domain<SC>],
+    quotient_chunks: &[Vec<SC::Challenge>],
+    zeta: SC::Challenge,
+) -> SC::Challenge {
+    let zps = quotient_chunks_domains
+        .iter()
+        .enumerate()
+        .map(|(i, domain)| {
+            quotient_chunks_domains
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other_domain)| {
+                    other_domain.zp_at_point(zeta)
+                        * other_domain.zp_at_point(domain.first_point()).inverse()
+                })
+                .product::<SC::Challenge>()
+        })
+        .collect_vec();
+
+    quotient_chunks
+        .iter()
+        .enumerate()
+        .map(|(ch_i, ch)| {
+            ch.iter()
+                .enumerate()
+                .map(|(e_i, &c)| zps[ch_i] * SC::Challenge::monomial(e_i) * c)
+                .sum::<SC::Challenge>()
+        })
+        .sum::<SC::Challenge>()
+}
+
 #[derive(Debug)]
 pub enum VerificationError<PcsErr> {
     InvalidProofShape,
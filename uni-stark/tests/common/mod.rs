@@ -0,0 +1,226 @@
+//! Fixtures shared by the integration tests: a Fibonacci AIR, and the BabyBear and Mersenne31
+//! configurations which most tests prove with.
+
+// Each test crate only uses some of the fixtures.
+#![allow(dead_code)]
+
+use core::borrow::Borrow;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, AirColumns, BaseAir};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+#[derive(AirColumns)]
+#[repr(C)]
+pub struct FibonacciCols<T> {
+    pub a: T,
+    pub b: T,
+}
+
+/// Computes Fibonacci numbers; the public values are the first two inputs and the final output.
+///
+/// Only the first two columns are read, so AIRs with more columns can reuse its constraints.
+#[derive(Clone)]
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        FibonacciCols::<F>::NUM_COLUMNS
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        FibonacciCols::<F>::column_names()
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &FibonacciCols<AB::Var> =
+            local[..FibonacciCols::<AB::Var>::NUM_COLUMNS].borrow();
+        let next: &FibonacciCols<AB::Var> = next[..FibonacciCols::<AB::Var>::NUM_COLUMNS].borrow();
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local.a, a);
+        when_first_row.assert_eq(local.b, b);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local.b, next.a);
+        when_transition.assert_eq(local.a + local.b, next.b);
+
+        builder.when_last_row().assert_eq(local.b, x);
+    }
+}
+
+/// [`FibonacciAir`] with a third column which is only checked to be boolean.
+#[derive(Clone)]
+pub struct FlaggedFibonacciAir;
+
+impl<F> BaseAir<F> for FlaggedFibonacciAir {
+    fn width(&self) -> usize {
+        FibonacciCols::<F>::NUM_COLUMNS + 1
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FlaggedFibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        FibonacciAir.eval(builder);
+        let main = builder.main();
+        let flag = main.row_slice(0)[FibonacciCols::<AB::Var>::NUM_COLUMNS];
+        builder.assert_bool(flag);
+    }
+}
+
+/// A trace of [`FibonacciAir`] starting at `(0, 1)`, with its public values.
+pub fn fibonacci_trace<F: Field>(log_height: usize) -> (RowMajorMatrix<F>, Vec<F>) {
+    fibonacci_trace_from(log_height, 0)
+}
+
+/// A trace of [`FibonacciAir`] starting at `(start, 1)`, with its public values.
+pub fn fibonacci_trace_from<F: Field>(
+    log_height: usize,
+    start: u32,
+) -> (RowMajorMatrix<F>, Vec<F>) {
+    let n = 1 << log_height;
+    let (mut a, mut b) = (F::from_canonical_u32(start), F::one());
+    let mut values = Vec::with_capacity(2 * n);
+    for _ in 0..n {
+        values.extend([a, b]);
+        (a, b) = (b, a + b);
+    }
+    let public_values = vec![values[0], values[1], values[2 * n - 1]];
+    (RowMajorMatrix::new(values, 2), public_values)
+}
+
+/// A trace of [`FlaggedFibonacciAir`] whose flag alternates between one and zero, with its public
+/// values.
+pub fn flagged_fibonacci_trace<F: Field>(log_height: usize) -> (RowMajorMatrix<F>, Vec<F>) {
+    let (trace, public_values) = fibonacci_trace::<F>(log_height);
+    let values = trace
+        .rows()
+        .enumerate()
+        .flat_map(|(i, row)| row.chain([F::from_bool(i % 2 == 0)]))
+        .collect();
+    (RowMajorMatrix::new(values, 3), public_values)
+}
+
+/// A two-adic FRI configuration over BabyBear, hashing with Poseidon2.
+pub mod baby_bear {
+    use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_commit::ExtensionMmcs;
+    use p3_dft::Radix2DitParallel;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::Field;
+    use p3_fri::{FriConfig, TwoAdicFriPcs};
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use p3_uni_stark::StarkConfig;
+    use rand::thread_rng;
+
+    pub type Val = BabyBear;
+    pub type Challenge = BinomialExtensionField<Val, 4>;
+    pub type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+    pub type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    pub type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    pub type ValMmcs =
+        MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+    pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    pub type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    pub type Dft = Radix2DitParallel<Val>;
+    pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+    pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
+
+    pub fn new_perm() -> Perm {
+        Perm::new_from_rng_128(
+            Poseidon2ExternalMatrixGeneral,
+            DiffusionMatrixBabyBear::default(),
+            &mut thread_rng(),
+        )
+    }
+
+    pub fn config_with_perm(perm: &Perm, log_blowup: usize, num_queries: usize) -> MyConfig {
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+        let val_mmcs = ValMmcs::new(hash, compress);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            log_folding_arity: 1,
+            num_queries,
+            proof_of_work_bits: 8,
+            mmcs: challenge_mmcs,
+        };
+        MyConfig::new(
+            Pcs::new(Dft::default(), val_mmcs, fri_config),
+            Keccak256Hash,
+        )
+    }
+
+    /// A config with a fresh permutation, and a challenger using the same permutation.
+    pub fn config(log_blowup: usize, num_queries: usize) -> (MyConfig, Challenger) {
+        let perm = new_perm();
+        let config = config_with_perm(&perm, log_blowup, num_queries);
+        (config, Challenger::new(perm))
+    }
+}
+
+/// A circle FRI configuration over Mersenne31, hashing with Keccak.
+pub mod mersenne_31 {
+    use core::marker::PhantomData;
+
+    use p3_challenger::{HashChallenger, SerializingChallenger32};
+    use p3_circle::CirclePcs;
+    use p3_commit::ExtensionMmcs;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_fri::FriConfig;
+    use p3_keccak::Keccak256Hash;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_mersenne_31::Mersenne31;
+    use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+    use p3_uni_stark::StarkConfig;
+
+    pub type Val = Mersenne31;
+    pub type Challenge = BinomialExtensionField<Val, 3>;
+    pub type ByteHash = Keccak256Hash;
+    pub type FieldHash = SerializingHasher32<ByteHash>;
+    pub type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    pub type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    pub type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+    pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger, ByteHash>;
+
+    /// A config, and a challenger hashing with the same function.
+    pub fn config(log_blowup: usize, num_queries: usize) -> (MyConfig, Challenger) {
+        let byte_hash = ByteHash {};
+        let field_hash = FieldHash::new(byte_hash);
+        let compress = MyCompress::new(byte_hash);
+        let val_mmcs = ValMmcs::new(field_hash, compress);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let fri_config = FriConfig {
+            log_blowup,
+            log_final_poly_len: 0,
+            log_folding_arity: 1,
+            num_queries,
+            proof_of_work_bits: 8,
+            mmcs: challenge_mmcs,
+        };
+        let pcs = Pcs {
+            mmcs: val_mmcs,
+            fri_config,
+            _phantom: PhantomData,
+        };
+        (
+            MyConfig::new(pcs, byte_hash),
+            Challenger::from_hasher(vec![], byte_hash),
+        )
+    }
+}
@@ -0,0 +1,71 @@
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_uni_stark::{export_constraints, Entry, Guard, SymbolicExpression, SymbolicVariable};
+
+mod common;
+
+use common::FlaggedFibonacciAir;
+
+type Val = BabyBear;
+
+#[test]
+fn display_constraints() {
+    let exported = export_constraints::<Val, _>(&FlaggedFibonacciAir, 0, 3);
+    assert_eq!(exported.max_degree(), 2);
+    assert_eq!(
+        exported.to_string(),
+        "6 constraints of degree at most 2
+0: when first row: main[0] - public[0] = 0  (degree 2)
+1: when first row: main[1] - public[1] = 0  (degree 2)
+2: when transition: main[1] - main[0]' = 0  (degree 1)
+3: when transition: main[0] + main[1] - main[1]' = 0  (degree 1)
+4: when last row: main[1] - public[2] = 0  (degree 2)
+5: main[2] * (main[2] - 1) = 0  (degree 2)
+"
+    );
+
+    let named = exported.with_column_names(["a", "b", "flag"]);
+    assert_eq!(
+        named.to_string(),
+        "6 constraints of degree at most 2
+0: when first row: a - public[0] = 0  (degree 2)
+1: when first row: b - public[1] = 0  (degree 2)
+2: when transition: b - a' = 0  (degree 1)
+3: when transition: a + b - b' = 0  (degree 1)
+4: when last row: b - public[2] = 0  (degree 2)
+5: flag * (flag - 1) = 0  (degree 2)
+"
+    );
+}
+
+#[test]
+fn display_expression() {
+    let x =
+        SymbolicExpression::<Val>::Variable(SymbolicVariable::new(Entry::Main { offset: 0 }, 0));
+    let y = SymbolicExpression::Variable(SymbolicVariable::new(Entry::Main { offset: 2 }, 1));
+    let expr = -(x.clone() + y.clone()) * (x.clone() - Val::one()) + Val::neg_one();
+    assert_eq!(
+        expr.to_string(),
+        "(-(main[0] + main[1]'')) * (main[0] - 1) + (-1)"
+    );
+}
+
+#[test]
+fn json_constraints() {
+    let exported = export_constraints::<Val, _>(&FlaggedFibonacciAir, 0, 3);
+    let json: serde_json::Value = serde_json::from_str(&exported.to_json()).unwrap();
+    assert_eq!(json["main_width"], 3);
+    let constraints = json["constraints"].as_array().unwrap();
+    assert_eq!(constraints.len(), 6);
+    assert_eq!(constraints[0]["guards"], serde_json::json!(["FirstRow"]));
+    assert_eq!(constraints[5]["guards"], serde_json::json!([]));
+    assert_eq!(constraints[5]["degree"], 2);
+
+    // The body of the boolean constraint is `flag * (flag - 1)`.
+    let nodes = json["nodes"].as_array().unwrap();
+    let body = &nodes[constraints[5]["body"].as_u64().unwrap() as usize];
+    let [flag, flag_minus_one] = [0, 1].map(|i| body["Mul"][i].as_u64().unwrap() as usize);
+    assert_eq!(nodes[flag]["Variable"]["index"], 2);
+    assert_eq!(nodes[flag_minus_one]["Sub"][0], flag);
+    assert_eq!(exported.constraints[0].guards, vec![Guard::FirstRow]);
+}
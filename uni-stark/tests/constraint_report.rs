@@ -0,0 +1,47 @@
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_uni_stark::{check_constraints_report, ConstraintFailure, SymbolicExpression};
+
+mod common;
+
+use common::{fibonacci_trace, FibonacciAir};
+
+#[test]
+fn report_valid_trace() {
+    let (trace, pis) = fibonacci_trace::<BabyBear>(4);
+    let report = check_constraints_report(&FibonacciAir, &trace, &pis);
+    assert!(report.is_ok());
+    assert_eq!(report.constraints.len(), 5);
+}
+
+#[test]
+fn report_invalid_trace() {
+    let (mut trace, mut pis) = fibonacci_trace::<BabyBear>(4);
+    trace.values[2 * 5 + 1] += BabyBear::one();
+    pis[2] = BabyBear::zero();
+    let report = check_constraints_report(&FibonacciAir, &trace, &pis);
+
+    assert!(!report.is_ok());
+    let failures = report
+        .failures
+        .iter()
+        .map(|f| (f.row, f.constraint_index))
+        .collect::<Vec<_>>();
+    assert_eq!(failures, [(4, 3), (5, 2), (5, 3), (15, 4)]);
+    assert_eq!(
+        report.failures[0],
+        ConstraintFailure {
+            row: 4,
+            constraint_index: 3,
+            value: -BabyBear::one(),
+        }
+    );
+    assert_eq!(report.failing_rows(3).collect::<Vec<_>>(), [4, 5]);
+
+    // The failed last-row constraint is `is_last_row * (local[1] - x)`.
+    let last_row_failure = report.failures.last().unwrap();
+    assert!(matches!(
+        report.constraint(last_row_failure),
+        SymbolicExpression::Mul { x, .. } if matches!(**x, SymbolicExpression::IsLastRow)
+    ));
+}
@@ -0,0 +1,131 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{
+    check_constraints_report, get_log_quotient_degree, get_max_constraint_degree, prove, setup,
+    verify, DegreeReducedAir,
+};
+
+mod common;
+
+use common::baby_bear::{self, Val};
+
+/// Iterates `x -> (x + 1)^7` from the first public value, and checks that the cube of the final
+/// value is the second public value. Without S-box registers, its transition constraint has
+/// degree 7 and its last row constraint degree 4.
+pub struct SboxChainAir;
+
+impl<F> BaseAir<F> for SboxChainAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for SboxChainAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (input, output) = (pis[0], pis[1]);
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let x: AB::Expr = local[0].into();
+
+        builder.when_first_row().assert_eq(x.clone(), input);
+        builder
+            .when_transition()
+            .assert_eq(next[0], (x.clone() + AB::Expr::one()).exp_const_u64::<7>());
+        builder.when_last_row().assert_eq(x.cube(), output);
+    }
+}
+
+fn sbox_chain_trace<F: Field>(log_height: usize, input: F) -> (RowMajorMatrix<F>, Vec<F>) {
+    let mut values = vec![input];
+    for i in 1..1 << log_height {
+        values.push((values[i - 1] + F::one()).exp_const_u64::<7>());
+    }
+    let output = values.last().unwrap().cube();
+    (RowMajorMatrix::new_col(values), vec![input, output])
+}
+
+#[test]
+fn reduce_to_degree_3() {
+    let air = DegreeReducedAir::<_, Val>::new(SboxChainAir, 2, 3);
+    assert_eq!(get_max_constraint_degree::<Val, _>(&SboxChainAir, 0, 2), 7);
+    assert_eq!(get_max_constraint_degree::<Val, _>(&air, 0, 2), 3);
+    assert_eq!(get_log_quotient_degree::<Val, _>(&SboxChainAir, 0, 2, 0), 3);
+    assert_eq!(get_log_quotient_degree::<Val, _>(&air, 0, 2, 0), 1);
+    // With y = x + 1, y^7 is computed as y^3 (y^2)^2, which needs columns for y^2 and y^3, and the
+    // last row constraint needs one for x^3 - output.
+    assert_eq!(air.num_virtual_columns(), 3);
+    assert_eq!(BaseAir::<Val>::width(&air), 1 + air.num_virtual_columns());
+
+    let (trace, public_values) = sbox_chain_trace(4, Val::two());
+    let extended = air.extend_trace(&trace, &public_values);
+    assert_eq!(extended.width(), BaseAir::<Val>::width(&air));
+    assert_eq!(extended.height(), trace.height());
+    assert!(check_constraints_report(&air, &extended, &public_values).is_ok());
+
+    // The virtual columns must match the trace they were computed from.
+    let (other_trace, other_public_values) = sbox_chain_trace(4, Val::one());
+    let width = extended.width();
+    let mut mismatched = extended;
+    for (row, &value) in other_trace.values.iter().enumerate() {
+        mismatched.values[row * width] = value;
+    }
+    assert!(!check_constraints_report(&air, &mismatched, &other_public_values).is_ok());
+}
+
+#[test]
+fn max_degree_2() {
+    let air = DegreeReducedAir::<_, Val>::new(SboxChainAir, 2, 2);
+    assert_eq!(get_max_constraint_degree::<Val, _>(&air, 0, 2), 2);
+
+    let (trace, public_values) = sbox_chain_trace(3, Val::two());
+    let extended = air.extend_trace(&trace, &public_values);
+    assert!(check_constraints_report(&air, &extended, &public_values).is_ok());
+}
+
+fn do_test(valid: bool) -> Result<(), impl Debug> {
+    // The unreduced AIR would need a blowup of 8.
+    let (config, challenger) = baby_bear::config(1, 40);
+
+    let air = DegreeReducedAir::new(SboxChainAir, 2, 3);
+    let (trace, mut public_values) = sbox_chain_trace(6, Val::two());
+    let trace = air.extend_trace(&trace, &public_values);
+    if !valid {
+        public_values[1] += Val::one();
+    }
+
+    let proof = prove(
+        &config,
+        &air,
+        &mut challenger.clone(),
+        trace,
+        &public_values,
+    );
+    let (_, vk) = setup(&config, &air, public_values.len());
+    verify(
+        &config,
+        &vk,
+        &air,
+        &mut challenger.clone(),
+        &proof,
+        &public_values,
+    )
+}
+
+#[test]
+fn prove_reduced() -> Result<(), impl Debug> {
+    do_test(true)
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_reduced_invalid() {
+    assert!(do_test(false).is_err());
+}
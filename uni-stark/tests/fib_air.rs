@@ -8,12 +8,13 @@ use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field, PrimeField64};
 use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_keccak::Keccak256Hash;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark::{prove, verify, StarkConfig};
+use p3_uni_stark::{prove, setup, verify, StarkConfig};
 use rand::thread_rng;
 
 /// For testing the public values feature
@@ -110,7 +111,7 @@ type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type Dft = Radix2DitParallel<Val>;
 type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
-type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
 
 #[test]
 fn test_public_value() {
@@ -127,12 +128,14 @@ fn test_public_value() {
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
     let fri_config = FriConfig {
         log_blowup: 2,
+        log_final_poly_len: 0,
+        log_folding_arity: 1,
         num_queries: 28,
         proof_of_work_bits: 8,
         mmcs: challenge_mmcs,
     };
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
-    let config = MyConfig::new(pcs);
+    let config = MyConfig::new(pcs, Keccak256Hash);
     let mut challenger = Challenger::new(perm.clone());
     let pis = vec![
         BabyBear::from_canonical_u64(0),
@@ -141,7 +144,16 @@ fn test_public_value() {
     ];
     let proof = prove(&config, &FibonacciAir {}, &mut challenger, trace, &pis);
     let mut challenger = Challenger::new(perm);
-    verify(&config, &FibonacciAir {}, &mut challenger, &proof, &pis).expect("verification failed");
+    let (_, vk) = setup(&config, &FibonacciAir {}, pis.len());
+    verify(
+        &config,
+        &vk,
+        &FibonacciAir {},
+        &mut challenger,
+        &proof,
+        &pis,
+    )
+    .expect("verification failed");
 }
 
 #[cfg(debug_assertions)]
@@ -160,13 +172,15 @@ fn test_incorrect_public_value() {
     let dft = Dft::default();
     let fri_config = FriConfig {
         log_blowup: 2,
+        log_final_poly_len: 0,
+        log_folding_arity: 1,
         num_queries: 28,
         proof_of_work_bits: 8,
         mmcs: challenge_mmcs,
     };
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
-    let config = MyConfig::new(pcs);
+    let config = MyConfig::new(pcs, Keccak256Hash);
     let mut challenger = Challenger::new(perm.clone());
     let pis = vec![
         BabyBear::from_canonical_u64(0),
@@ -0,0 +1,61 @@
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_uni_stark::{fuzz_constraints, FuzzConfig, Mutation};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+mod common;
+
+use common::{fibonacci_trace, flagged_fibonacci_trace, FibonacciAir, FlaggedFibonacciAir};
+
+type Val = BabyBear;
+
+#[test]
+fn fully_constrained() {
+    let (trace, public_values) = fibonacci_trace::<Val>(4);
+    let report = fuzz_constraints(
+        &FibonacciAir,
+        &trace,
+        &public_values,
+        &FuzzConfig::default(),
+        &mut StdRng::seed_from_u64(0),
+    );
+    assert!(report.num_mutations > 0);
+    assert!(report.is_ok(), "{:?}", report.survivors);
+}
+
+#[test]
+fn underconstrained() {
+    // The flag column is only checked to be boolean, and the fourth public value isn't used.
+    let (trace, mut public_values) = flagged_fibonacci_trace::<Val>(4);
+    public_values.push(Val::from_canonical_u32(42));
+    let report = fuzz_constraints(
+        &FlaggedFibonacciAir,
+        &trace,
+        &public_values,
+        &FuzzConfig::default(),
+        &mut StdRng::seed_from_u64(0),
+    );
+    assert!(!report.is_ok());
+    // Only flipping the flag keeps it boolean, so random values in its column are caught.
+    assert_eq!(report.underconstrained_columns(), vec![2]);
+    assert_eq!(report.underconstrained_public_values(), vec![3]);
+    assert!(report.survivors.iter().all(|mutation| matches!(
+        mutation,
+        Mutation::FlipBoolean { column: 2, .. } | Mutation::PublicValue { index: 3, .. }
+    )));
+}
+
+#[test]
+#[should_panic(expected = "the trace must satisfy the constraints")]
+fn invalid_trace() {
+    let (mut trace, public_values) = fibonacci_trace::<Val>(4);
+    trace.values[5] += Val::one();
+    fuzz_constraints(
+        &FibonacciAir,
+        &trace,
+        &public_values,
+        &FuzzConfig::default(),
+        &mut StdRng::seed_from_u64(0),
+    );
+}
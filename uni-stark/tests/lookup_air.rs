@@ -0,0 +1,189 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, BaseAir, InteractionBuilder, PairBuilder};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{prove, setup, verify, StarkGenericConfig, Val};
+use rand::{thread_rng, Rng};
+
+mod common;
+
+use common::{baby_bear, mersenne_31};
+
+const RANGE_BUS: usize = 0;
+
+/// Range checks the first main column against a preprocessed table of `0..2^log_height`; the
+/// second main column counts how often each table entry is looked up.
+pub struct RangeCheckAir {
+    log_height: usize,
+}
+
+impl RangeCheckAir {
+    fn generate_trace<F: Field>(&self, valid: bool) -> RowMajorMatrix<F> {
+        let n = 1 << self.log_height;
+        let mut rng = thread_rng();
+        let mut looked_up = (0..n).map(|_| rng.gen_range(0..n)).collect::<Vec<_>>();
+        let mut multiplicities = vec![0; n];
+        for &value in &looked_up {
+            multiplicities[value] += 1;
+        }
+        if !valid {
+            // Out of range, and not accounted for by any multiplicity.
+            looked_up[0] = n;
+        }
+
+        let values = looked_up
+            .into_iter()
+            .zip(multiplicities)
+            .flat_map(|(value, multiplicity)| {
+                [
+                    F::from_canonical_usize(value),
+                    F::from_canonical_usize(multiplicity),
+                ]
+            })
+            .collect();
+        RowMajorMatrix::new(values, 2)
+    }
+}
+
+impl<F: Field> BaseAir<F> for RangeCheckAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..1 << self.log_height)
+                .map(F::from_canonical_usize)
+                .collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder + InteractionBuilder> Air<AB> for RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let table = builder.preprocessed().row_slice(0)[0];
+
+        builder.send(RANGE_BUS, [local[0]], AB::Expr::one());
+        builder.receive(RANGE_BUS, [table], local[1]);
+    }
+}
+
+/// Sends its column over one bus and receives it back over another, which must not balance.
+pub struct CrossBusAir;
+
+impl<F> BaseAir<F> for CrossBusAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: InteractionBuilder> Air<AB> for CrossBusAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        builder.send(RANGE_BUS, [local[0]], AB::Expr::one());
+        builder.receive(RANGE_BUS + 1, [local[0]], AB::Expr::one());
+    }
+}
+
+fn do_test<SC: StarkGenericConfig>(
+    config: SC,
+    air: RangeCheckAir,
+    valid: bool,
+    challenger: SC::Challenger,
+) -> Result<(), impl Debug>
+where
+    SC::Challenger: Clone,
+{
+    let trace = air.generate_trace::<Val<SC>>(valid);
+
+    let mut p_challenger = challenger.clone();
+    let proof = prove(&config, &air, &mut p_challenger, trace, &vec![]);
+
+    let serialized_proof = postcard::to_allocvec(&proof).expect("unable to serialize proof");
+    let deserialized_proof =
+        postcard::from_bytes(&serialized_proof).expect("unable to deserialize proof");
+
+    let mut v_challenger = challenger;
+    let (_, vk) = setup(&config, &air, 0);
+    verify(
+        &config,
+        &vk,
+        &air,
+        &mut v_challenger,
+        &deserialized_proof,
+        &vec![],
+    )
+}
+
+fn do_test_bb_twoadic(log_height: usize, valid: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+    do_test(config, RangeCheckAir { log_height }, valid, challenger)
+}
+
+fn do_test_m31_circle(log_height: usize, valid: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = mersenne_31::config(1, 40);
+    do_test(config, RangeCheckAir { log_height }, valid, challenger)
+}
+
+#[test]
+fn prove_bb_twoadic_range_check() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(6, true)
+}
+
+// In debug builds the prover's constraint check catches the bad lookup before the verifier does.
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_bb_twoadic_out_of_range() {
+    assert!(do_test_bb_twoadic(6, false).is_err());
+}
+
+#[test]
+fn prove_m31_circle_range_check() -> Result<(), impl Debug> {
+    do_test_m31_circle(6, true)
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_m31_circle_out_of_range() {
+    assert!(do_test_m31_circle(6, false).is_err());
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_bb_twoadic_cross_bus() {
+    let (config, challenger) = baby_bear::config(1, 40);
+    let trace = RowMajorMatrix::new_col((0..1 << 6).map(BabyBear::from_canonical_u32).collect());
+    let proof = prove(
+        &config,
+        &CrossBusAir,
+        &mut challenger.clone(),
+        trace,
+        &vec![],
+    );
+    let (_, vk) = setup(&config, &CrossBusAir, 0);
+    assert!(verify(
+        &config,
+        &vk,
+        &CrossBusAir,
+        &mut challenger.clone(),
+        &proof,
+        &vec![]
+    )
+    .is_err());
+}
@@ -0,0 +1,65 @@
+use std::fmt::Debug;
+
+use p3_field::AbstractField;
+use p3_uni_stark::{prove_many, verify_many, verify_multi};
+
+mod common;
+
+use common::baby_bear::{self, Val};
+use common::{fibonacci_trace_from, FibonacciAir};
+
+fn do_test(num_traces: usize, valid: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+
+    // Small traces of a few different heights.
+    let (traces, mut public_values): (Vec<_>, Vec<_>) = (0..num_traces)
+        .map(|i| fibonacci_trace_from::<Val>(2 + i % 4, i as u32))
+        .unzip();
+    if !valid {
+        public_values[num_traces / 2][2] += Val::one();
+    }
+
+    let proof = prove_many(
+        &config,
+        &FibonacciAir,
+        &mut challenger.clone(),
+        traces,
+        &public_values,
+    );
+    assert_eq!(proof.degree_bits().len(), num_traces);
+
+    // The proof is also a valid multi-table proof, with one copy of the AIR per trace.
+    verify_multi(
+        &config,
+        &vec![FibonacciAir; num_traces],
+        &mut challenger.clone(),
+        &proof,
+        &public_values,
+    )?;
+    verify_many(
+        &config,
+        &FibonacciAir,
+        &mut challenger.clone(),
+        &proof,
+        &public_values,
+    )
+}
+
+#[test]
+fn prove_many_fibonacci() -> Result<(), impl Debug> {
+    do_test(50, true)
+}
+
+#[test]
+fn prove_many_single_trace() -> Result<(), impl Debug> {
+    do_test(1, true)
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_many_invalid() {
+    assert!(do_test(10, false).is_err());
+}
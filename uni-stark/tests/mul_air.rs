@@ -21,10 +21,14 @@ use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
 use p3_symmetric::{
     CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, TruncatedPermutation,
 };
-use p3_uni_stark::{prove, verify, StarkConfig, StarkGenericConfig, Val};
+use p3_uni_stark::{prove, setup, verify, StarkConfig, StarkGenericConfig, Val};
 use rand::distributions::{Distribution, Standard};
 use rand::{thread_rng, Rng};
 
+mod common;
+
+use common::baby_bear;
+
 /// How many `a * b = c` operations to do per row in the AIR.
 const REPETITIONS: usize = 20;
 const TRACE_WIDTH: usize = REPETITIONS * 3;
@@ -138,8 +142,10 @@ where
         postcard::from_bytes(&serialized_proof).expect("unable to deserialize proof");
 
     let mut v_challenger = challenger.clone();
+    let (_, vk) = setup(&config, &air, 0);
     verify(
         &config,
+        &vk,
         &air,
         &mut v_challenger,
         &deserialized_proof,
@@ -170,8 +176,8 @@ fn do_test_bb_trivial(degree: u64, log_n: usize) -> Result<(), impl Debug> {
         _phantom: PhantomData,
     };
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
+    let config = MyConfig::new(pcs, Keccak256Hash);
 
     let air = MulAir {
         degree,
@@ -196,7 +202,12 @@ fn prove_bb_trivial_deg4() -> Result<(), impl Debug> {
     do_test_bb_trivial(4, 8)
 }
 
-fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
+fn do_test_bb_twoadic(
+    log_blowup: usize,
+    log_final_poly_len: usize,
+    degree: u64,
+    log_n: usize,
+) -> Result<(), impl Debug> {
     type Val = BabyBear;
     type Challenge = BinomialExtensionField<Val, 4>;
 
@@ -227,6 +238,8 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
 
     let fri_config = FriConfig {
         log_blowup,
+        log_final_poly_len,
+        log_folding_arity: 1,
         num_queries: 40,
         proof_of_work_bits: 8,
         mmcs: challenge_mmcs,
@@ -234,8 +247,8 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
     type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
+    let config = MyConfig::new(pcs, Keccak256Hash);
 
     let air = MulAir {
         degree,
@@ -247,22 +260,33 @@ fn do_test_bb_twoadic(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
 
 #[test]
 fn prove_bb_twoadic_deg2() -> Result<(), impl Debug> {
-    do_test_bb_twoadic(1, 2, 7)
+    do_test_bb_twoadic(1, 0, 2, 7)
 }
 
 #[test]
 fn prove_bb_twoadic_deg3() -> Result<(), impl Debug> {
-    do_test_bb_twoadic(1, 3, 7)
+    do_test_bb_twoadic(1, 0, 3, 7)
 }
 
 #[test]
 fn prove_bb_twoadic_deg4() -> Result<(), impl Debug> {
-    do_test_bb_twoadic(2, 4, 6)
+    do_test_bb_twoadic(2, 0, 4, 6)
 }
 
 #[test]
 fn prove_bb_twoadic_deg5() -> Result<(), impl Debug> {
-    do_test_bb_twoadic(2, 5, 6)
+    do_test_bb_twoadic(2, 0, 5, 6)
+}
+
+#[test]
+fn prove_bb_twoadic_final_poly() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(1, 3, 3, 7)
+}
+
+#[test]
+#[should_panic(expected = "the trace has 4 rows, but the PCS needs at least 8")]
+fn prove_bb_twoadic_trace_shorter_than_final_poly() {
+    let _ = do_test_bb_twoadic(1, 3, 3, 2);
 }
 
 fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<(), impl Debug> {
@@ -287,6 +311,8 @@ fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
 
     let fri_config = FriConfig {
         log_blowup,
+        log_final_poly_len: 0,
+        log_folding_arity: 1,
         num_queries: 40,
         proof_of_work_bits: 8,
         mmcs: challenge_mmcs,
@@ -299,8 +325,8 @@ fn do_test_m31_circle(log_blowup: usize, degree: u64, log_n: usize) -> Result<()
         _phantom: PhantomData,
     };
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
+    let config = MyConfig::new(pcs, Keccak256Hash);
 
     let air = MulAir {
         degree,
@@ -325,3 +351,39 @@ fn prove_m31_circle_deg2() -> Result<(), impl Debug> {
 fn prove_m31_circle_deg3() -> Result<(), impl Debug> {
     do_test_m31_circle(1, 3, 9)
 }
+
+/// An AIR without any constraints, whose constraint degree is zero.
+struct UnconstrainedAir;
+
+impl<F> BaseAir<F> for UnconstrainedAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for UnconstrainedAir {
+    fn eval(&self, _builder: &mut AB) {}
+}
+
+#[test]
+fn prove_bb_twoadic_unconstrained() -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+    let trace = RowMajorMatrix::new_col(BabyBear::zero_vec(1 << 4));
+
+    let proof = prove(
+        &config,
+        &UnconstrainedAir,
+        &mut challenger.clone(),
+        trace,
+        &vec![],
+    );
+    let (_, vk) = setup(&config, &UnconstrainedAir, 0);
+    verify(
+        &config,
+        &vk,
+        &UnconstrainedAir,
+        &mut challenger.clone(),
+        &proof,
+        &vec![],
+    )
+}
@@ -0,0 +1,173 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, InteractionBuilder};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{prove_multi, verify_multi, StarkGenericConfig, Val, VerificationError};
+
+mod common;
+
+use common::{baby_bear, fibonacci_trace, mersenne_31, FibonacciAir};
+
+/// Checks that the third column is the product of the first two, a degree 2 constraint.
+pub struct MulAir;
+
+impl<F> BaseAir<F> for MulAir {
+    fn width(&self) -> usize {
+        3
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for MulAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        builder.assert_eq(local[0] * local[1], local[2]);
+    }
+}
+
+/// Both tables, so that they can be proven together.
+pub enum MyAir {
+    Fibonacci(FibonacciAir),
+    Mul(MulAir),
+}
+
+impl<F> BaseAir<F> for MyAir {
+    fn width(&self) -> usize {
+        match self {
+            MyAir::Fibonacci(air) => <FibonacciAir as BaseAir<F>>::width(air),
+            MyAir::Mul(air) => <MulAir as BaseAir<F>>::width(air),
+        }
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for MyAir {
+    fn eval(&self, builder: &mut AB) {
+        match self {
+            MyAir::Fibonacci(air) => air.eval(builder),
+            MyAir::Mul(air) => air.eval(builder),
+        }
+    }
+}
+
+/// Looks up its column in itself when `lookup` is set, which multi-table proofs don't support.
+pub struct SelfLookupAir {
+    lookup: bool,
+}
+
+impl<F> BaseAir<F> for SelfLookupAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: InteractionBuilder> Air<AB> for SelfLookupAir {
+    fn eval(&self, builder: &mut AB) {
+        if self.lookup {
+            let value = builder.main().row_slice(0)[0];
+            builder.send(0, [value], AB::Expr::one());
+            builder.receive(0, [value], AB::Expr::one());
+        }
+    }
+}
+
+fn mul_trace<F: Field>(log_height: usize, valid: bool) -> RowMajorMatrix<F> {
+    let n = 1 << log_height;
+    let mut values = (0..n)
+        .flat_map(|i| {
+            let (x, y) = (F::from_canonical_usize(i), F::from_canonical_usize(i + 3));
+            [x, y, x * y]
+        })
+        .collect::<Vec<_>>();
+    if !valid {
+        values[2] += F::one();
+    }
+    RowMajorMatrix::new(values, 3)
+}
+
+fn do_test<SC: StarkGenericConfig>(
+    config: SC,
+    challenger: SC::Challenger,
+    valid: bool,
+) -> Result<(), impl Debug>
+where
+    SC::Challenger: Clone,
+{
+    let airs = [MyAir::Fibonacci(FibonacciAir), MyAir::Mul(MulAir)];
+    let (fibonacci_trace, fibonacci_pis) = fibonacci_trace::<Val<SC>>(5);
+    let traces = vec![fibonacci_trace, mul_trace::<Val<SC>>(8, valid)];
+    let public_values = vec![fibonacci_pis, vec![]];
+
+    let mut p_challenger = challenger.clone();
+    let proof = prove_multi(&config, &airs, &mut p_challenger, traces, &public_values);
+
+    let serialized_proof = postcard::to_allocvec(&proof).expect("unable to serialize proof");
+    let deserialized_proof =
+        postcard::from_bytes(&serialized_proof).expect("unable to deserialize proof");
+
+    let mut v_challenger = challenger;
+    verify_multi(
+        &config,
+        &airs,
+        &mut v_challenger,
+        &deserialized_proof,
+        &public_values,
+    )
+}
+
+fn do_test_bb_twoadic(valid: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+    do_test(config, challenger, valid)
+}
+
+fn do_test_m31_circle(valid: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = mersenne_31::config(1, 40);
+    do_test(config, challenger, valid)
+}
+
+#[test]
+fn prove_bb_twoadic_multi() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true)
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "values didn't match"))]
+fn prove_bb_twoadic_multi_invalid() {
+    assert!(do_test_bb_twoadic(false).is_err());
+}
+
+#[test]
+fn prove_m31_circle_multi() -> Result<(), impl Debug> {
+    do_test_m31_circle(true)
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "values didn't match"))]
+fn prove_m31_circle_multi_invalid() {
+    assert!(do_test_m31_circle(false).is_err());
+}
+
+#[test]
+fn reject_multi_with_interactions() {
+    let (config, challenger) = baby_bear::config(1, 40);
+    let trace = RowMajorMatrix::new_col(vec![BabyBear::one(); 8]);
+    let proof = prove_multi(
+        &config,
+        &[SelfLookupAir { lookup: false }],
+        &mut challenger.clone(),
+        vec![trace],
+        &[vec![]],
+    );
+    assert!(matches!(
+        verify_multi(
+            &config,
+            &[SelfLookupAir { lookup: true }],
+            &mut challenger.clone(),
+            &proof,
+            &[vec![]],
+        ),
+        Err(VerificationError::InvalidProofShape)
+    ));
+}
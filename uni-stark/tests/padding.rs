@@ -0,0 +1,89 @@
+use p3_air::{Air, AirBuilder, BaseAir, Padding};
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{pad_trace, prove_padded, setup, verify_padded, VerificationError};
+
+mod common;
+
+use common::baby_bear::{self, Val};
+
+/// A single column alternating between 0 and 1, starting from 0, padded by repeating its last
+/// `repeat` rows.
+pub struct AlternatingAir {
+    repeat: usize,
+}
+
+impl<F> BaseAir<F> for AlternatingAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn padding(&self) -> Padding<F> {
+        Padding::RepeatLast(self.repeat)
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for AlternatingAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+
+        builder.when_first_row().assert_zero(local[0]);
+        builder
+            .when_transition()
+            .assert_eq(next[0], AB::Expr::one() - local[0]);
+        builder.assert_bool(local[0]);
+    }
+}
+
+fn alternating_trace<F: Field>(height: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col((0..height).map(|i| F::from_bool(i % 2 == 1)).collect())
+}
+
+#[test]
+fn prove_padded_trace() {
+    let (config, mut challenger) = baby_bear::config(1, 40);
+    let air = AlternatingAir { repeat: 2 };
+    let trace = alternating_trace::<Val>(22);
+
+    let proof = prove_padded(&config, &air, &mut challenger.clone(), trace, &[]);
+    let (_, vk) = setup(&config, &air, 1);
+    verify_padded(&config, &vk, &air, &mut challenger.clone(), &proof, 22, &[])
+        .expect("verification failed");
+
+    // The padded height must match the row count.
+    assert!(matches!(
+        verify_padded(&config, &vk, &air, &mut challenger, &proof, 33, &[]),
+        Err(VerificationError::InvalidProofShape)
+    ));
+}
+
+#[test]
+fn pad_trace_reports_invalid_padding() {
+    let trace = alternating_trace::<Val>(6);
+    assert_eq!(
+        pad_trace(&AlternatingAir { repeat: 2 }, trace.clone(), &[])
+            .expect("padding should be valid")
+            .values,
+        alternating_trace::<Val>(8).values
+    );
+
+    // Repeating the last row breaks the transition constraint into, and within, the padding.
+    let report = pad_trace(&AlternatingAir { repeat: 1 }, trace, &[]).unwrap_err();
+    assert_eq!(report.failing_rows(1).collect::<Vec<_>>(), [5, 6]);
+    assert_eq!(report.failures.len(), 2);
+}
+
+#[test]
+fn pad_with_row() {
+    let mut trace = RowMajorMatrix::new((0..10).map(Val::from_canonical_u32).collect(), 2);
+    Padding::Row(vec![Val::zero(), Val::one()]).pad(&mut trace);
+    assert_eq!(trace.height(), 8);
+    assert_eq!(&*trace.row_slice(7), [Val::zero(), Val::one()]);
+
+    // Power-of-two traces are left alone, even if they can't be padded.
+    let mut trace = alternating_trace::<Val>(4);
+    Padding::Unsupported.pad(&mut trace);
+    assert_eq!(trace.height(), 4);
+}
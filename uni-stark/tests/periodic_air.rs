@@ -0,0 +1,159 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPeriodicColumns, BaseAir};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{
+    prove, prove_multi, setup, verify, verify_multi, PcsError, StarkGenericConfig, Val,
+    VerificationError,
+};
+
+mod common;
+
+use common::{baby_bear, mersenne_31};
+
+/// Accumulates the products of two round-constant-like periodic columns, with periods 4 and 16:
+/// `acc' = acc + c * d`, where the second column must equal `c * d` on every row.
+#[derive(Clone, Copy)]
+pub struct PeriodicAir;
+
+impl<F: Field> BaseAir<F> for PeriodicAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn periodic_columns(&self) -> Vec<Vec<F>> {
+        vec![
+            (1..=4).map(F::from_canonical_usize).collect(),
+            (0..16)
+                .map(|i| F::from_canonical_usize(3 * i + 7))
+                .collect(),
+        ]
+    }
+}
+
+impl<AB: AirBuilderWithPeriodicColumns> Air<AB> for PeriodicAir
+where
+    AB::F: Field,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let periodic = builder.periodic_values();
+        let product = periodic[0].into() * periodic[1].into();
+
+        builder.when_first_row().assert_zero(local[0]);
+        builder.assert_eq(local[1], product.clone());
+        builder
+            .when_transition()
+            .assert_eq(local[0] + product, next[0]);
+    }
+}
+
+fn periodic_trace<F: Field>(log_height: usize, valid: bool) -> RowMajorMatrix<F> {
+    let n = 1 << log_height;
+    let columns = <PeriodicAir as BaseAir<F>>::periodic_columns(&PeriodicAir);
+    let mut acc = F::zero();
+    let mut values = Vec::with_capacity(2 * n);
+    for i in 0..n {
+        let product = columns[0][i % 4] * columns[1][i % 16];
+        values.extend([acc, product]);
+        acc += product;
+    }
+    if !valid {
+        values[2 * (n / 2) + 1] += F::one();
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+fn do_test<SC: StarkGenericConfig>(
+    config: SC,
+    challenger: SC::Challenger,
+    valid: bool,
+    log_heights: &[usize],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC::Challenger: Clone,
+{
+    let mut p_challenger = challenger.clone();
+    let mut v_challenger = challenger;
+    if let [log_height] = *log_heights {
+        let trace = periodic_trace::<Val<SC>>(log_height, valid);
+        let proof = prove(&config, &PeriodicAir, &mut p_challenger, trace, &vec![]);
+        let (_, vk) = setup(&config, &PeriodicAir, 0);
+        verify(
+            &config,
+            &vk,
+            &PeriodicAir,
+            &mut v_challenger,
+            &proof,
+            &vec![],
+        )
+    } else {
+        // Only the tallest instance may be invalid.
+        let airs = vec![PeriodicAir; log_heights.len()];
+        let traces = log_heights
+            .iter()
+            .map(|&log_height| periodic_trace::<Val<SC>>(log_height, valid || log_height < 7))
+            .collect();
+        let public_values = vec![vec![]; log_heights.len()];
+
+        let proof = prove_multi(&config, &airs, &mut p_challenger, traces, &public_values);
+        verify_multi(&config, &airs, &mut v_challenger, &proof, &public_values)
+    }
+}
+
+fn do_test_bb_twoadic(valid: bool, log_heights: &[usize]) -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+    do_test(config, challenger, valid, log_heights)
+}
+
+fn do_test_m31_circle(valid: bool, log_heights: &[usize]) -> Result<(), impl Debug> {
+    let (config, challenger) = mersenne_31::config(1, 40);
+    do_test(config, challenger, valid, log_heights)
+}
+
+#[test]
+fn prove_bb_twoadic_periodic() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true, &[6])
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "values didn't match"))]
+fn prove_bb_twoadic_periodic_invalid() {
+    assert!(do_test_bb_twoadic(false, &[6]).is_err());
+}
+
+#[test]
+fn prove_bb_twoadic_periodic_multi() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true, &[4, 7])
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "values didn't match"))]
+fn prove_bb_twoadic_periodic_multi_invalid() {
+    assert!(do_test_bb_twoadic(false, &[4, 7]).is_err());
+}
+
+#[test]
+fn prove_m31_circle_periodic() -> Result<(), impl Debug> {
+    do_test_m31_circle(true, &[6])
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "values didn't match"))]
+fn prove_m31_circle_periodic_invalid() {
+    assert!(do_test_m31_circle(false, &[6]).is_err());
+}
+
+#[test]
+fn prove_m31_circle_periodic_multi() -> Result<(), impl Debug> {
+    do_test_m31_circle(true, &[4, 7])
+}
+
+#[test]
+#[should_panic(expected = "periods of periodic columns must be powers of two")]
+fn prove_periodic_too_tall() {
+    let _ = do_test_bb_twoadic(true, &[3]);
+}
@@ -0,0 +1,120 @@
+use p3_air::{Air, BaseAir, ExtensionBuilder, PermutationAirBuilder};
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{prove, setup, verify};
+
+mod common;
+
+use common::baby_bear;
+
+/// Checks that the second main column is a permutation of the first, using a grand product
+/// `z_i = prod_{j <= i} (gamma - a_j) / (gamma - b_j)` in the permutation trace.
+pub struct PermutationCheckAir;
+
+impl<F: Field> BaseAir<F> for PermutationCheckAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn num_permutation_challenges(&self) -> usize {
+        1
+    }
+
+    fn permutation_width(&self) -> usize {
+        1
+    }
+
+    fn permutation_trace<EF>(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &[EF],
+    ) -> Option<RowMajorMatrix<EF>>
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+    {
+        let gamma = challenges[0];
+        let mut z = EF::one();
+        let values = (0..main.height())
+            .map(|i| {
+                let row = main.row_slice(i);
+                z *= (gamma - row[0]) * (gamma - row[1]).inverse();
+                z
+            })
+            .collect();
+        Some(RowMajorMatrix::new_col(values))
+    }
+}
+
+impl<AB: PermutationAirBuilder> Air<AB> for PermutationCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let perm = builder.permutation();
+        let (perm_local, perm_next) = (perm.row_slice(0), perm.row_slice(1));
+        let gamma: AB::ExprEF = builder.permutation_randomness()[0].into();
+
+        let z: AB::ExprEF = perm_local[0].into();
+        let z_next: AB::ExprEF = perm_next[0].into();
+        let num = |a: AB::Var| gamma.clone() - a.into();
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone() * num(local[1]), num(local[0]));
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next * num(next[1]), z.clone() * num(next[0]));
+        builder.when_last_row().assert_one_ext(z);
+    }
+}
+
+fn generate_trace<F: Field>(log_height: usize, is_permutation: bool) -> RowMajorMatrix<F> {
+    let n = 1 << log_height;
+    let values = (0..n)
+        .flat_map(|i| {
+            let b = if is_permutation { n - 1 - i } else { 0 };
+            [F::from_canonical_usize(i), F::from_canonical_usize(b)]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+fn do_test(log_height: usize, is_permutation: bool) {
+    let (config, challenger) = baby_bear::config(1, 28);
+
+    let trace = generate_trace(log_height, is_permutation);
+    let proof = prove(
+        &config,
+        &PermutationCheckAir,
+        &mut challenger.clone(),
+        trace,
+        &vec![],
+    );
+
+    let (_, vk) = setup(&config, &PermutationCheckAir, 0);
+    verify(
+        &config,
+        &vk,
+        &PermutationCheckAir,
+        &mut challenger.clone(),
+        &proof,
+        &vec![],
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn test_permutation() {
+    do_test(5, true);
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+#[cfg_attr(not(debug_assertions), should_panic(expected = "verification failed"))]
+fn test_not_a_permutation() {
+    do_test(5, false);
+}
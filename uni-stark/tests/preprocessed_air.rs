@@ -0,0 +1,108 @@
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{prove, prove_with_key, setup, verify};
+
+mod common;
+
+use common::baby_bear::{config_with_perm, new_perm, Challenger};
+
+/// Checks that the single main column holds `x^2 + 1`, where `x` is a preprocessed column
+/// containing the row index.
+pub struct SquaresAir {
+    log_height: usize,
+}
+
+impl<F: Field> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..1 << self.log_height)
+                .map(F::from_canonical_usize)
+                .collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let preprocessed = builder.preprocessed();
+
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let x = preprocessed.row_slice(0)[0];
+        let y = local[0];
+        let y_next = next[0];
+
+        builder.assert_eq(y, x * x + AB::Expr::one());
+
+        // (x + 1)^2 - x^2 = 2x + 1
+        builder
+            .when_transition()
+            .assert_eq(y_next - y, x * AB::F::two() + AB::Expr::one());
+    }
+}
+
+fn generate_trace<F: Field>(log_height: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col(
+        (0..1 << log_height)
+            .map(|i| F::from_canonical_usize(i * i + 1))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_preprocessed_with_key() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 28);
+    let air = SquaresAir { log_height: 6 };
+
+    let (pk, vk) = setup(&config, &air, 0);
+    assert_eq!(vk.preprocessed.as_ref().unwrap().width, 1);
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove_with_key(
+        &config,
+        &pk,
+        &air,
+        &mut challenger,
+        generate_trace(6),
+        &vec![],
+    );
+
+    let mut challenger = Challenger::new(perm);
+    verify(&config, &vk, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
+
+#[test]
+fn test_preprocessed_without_key() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 28);
+    let air = SquaresAir { log_height: 5 };
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, generate_trace(5), &vec![]);
+
+    let mut challenger = Challenger::new(perm);
+    let (_, vk) = setup(&config, &air, 0);
+    verify(&config, &vk, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+}
+
+#[test]
+fn test_preprocessed_wrong_key() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 28);
+    let air = SquaresAir { log_height: 5 };
+
+    let mut challenger = Challenger::new(perm.clone());
+    let proof = prove(&config, &air, &mut challenger, generate_trace(5), &vec![]);
+
+    // A key for a different preprocessed trace must not verify this proof.
+    let (_, other_vk) = setup(&config, &SquaresAir { log_height: 6 }, 0);
+    let mut challenger = Challenger::new(perm);
+    assert!(verify(&config, &other_vk, &air, &mut challenger, &proof, &vec![]).is_err());
+}
@@ -0,0 +1,201 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_mersenne_31::Mersenne31;
+use p3_uni_stark::{
+    config_fingerprint, prove, prove_multi, setup, verify, MultiProof, Proof, ProofFormatError,
+    PROOF_FORMAT_VERSION,
+};
+use p3_util::binary::{self, BinaryError};
+
+mod common;
+
+use common::baby_bear::{config_with_perm, new_perm, Challenger, MyConfig, Val};
+
+/// A single column of Fibonacci numbers, starting from two public values.
+pub struct FibAir;
+
+impl<F> BaseAir<F> for FibAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn window_size(&self) -> usize {
+        3
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b) = (pis[0], pis[1]);
+        let (local, next, next_next) = (main.row_slice(0), main.row_slice(1), main.row_slice(2));
+
+        builder.when_first_row().assert_eq(local[0], a);
+        builder.when_first_row().assert_eq(next[0], b);
+        builder
+            .when_transition_window(3)
+            .assert_zero(next_next[0] - next[0] - local[0]);
+    }
+}
+
+fn fib_trace<F: Field>(log_height: usize) -> RowMajorMatrix<F> {
+    let mut values = vec![F::zero(), F::one()];
+    for i in 2..1 << log_height {
+        values.push(values[i - 1] + values[i - 2]);
+    }
+    RowMajorMatrix::new_col(values)
+}
+
+#[test]
+fn round_trip() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 40);
+    let public_values = vec![Val::zero(), Val::one()];
+    let proof = prove(
+        &config,
+        &FibAir,
+        &mut Challenger::new(perm.clone()),
+        fib_trace(5),
+        &public_values,
+    );
+
+    let bytes = proof.to_bytes(&config);
+    assert_eq!(&bytes[..4], b"P3UP");
+    assert_eq!(bytes[4..6], PROOF_FORMAT_VERSION.to_le_bytes());
+    assert_eq!(bytes[6..14], config_fingerprint(&config).to_le_bytes());
+
+    let stats = proof.stats(&config);
+    assert_eq!(stats.total(), bytes.len() - 14);
+    assert_eq!(stats.opening_proof.num_queries, 40);
+    // Each query opens the trace and quotient LDEs, of height 64, with one hash of the row and one
+    // compression per level, then folds through commit phase layers of heights 32 down to 2.
+    assert_eq!(
+        stats.verifier_hashes(),
+        40 * (2 * (1 + 6) + (1..=5).map(|log_height| 1 + log_height).sum::<usize>())
+    );
+
+    let decoded = Proof::from_bytes(&config, &bytes).expect("decoding failed");
+    assert_eq!(decoded.degree_bits(), 5);
+    assert_eq!(decoded.opened_values().trace_window().len(), 1);
+    assert_eq!(decoded.to_bytes(&config), bytes);
+    let (_, vk) = setup(&config, &FibAir, public_values.len());
+    verify(
+        &config,
+        &vk,
+        &FibAir,
+        &mut Challenger::new(perm),
+        &decoded,
+        &public_values,
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn multi_proof_round_trip() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 40);
+    let public_values = vec![vec![Val::zero(), Val::one()]; 2];
+    let proof = prove_multi(
+        &config,
+        &[FibAir, FibAir],
+        &mut Challenger::new(perm),
+        vec![fib_trace(3), fib_trace(5)],
+        &public_values,
+    );
+
+    let bytes = proof.to_bytes(&config);
+    let decoded = MultiProof::from_bytes(&config, &bytes).expect("decoding failed");
+    assert_eq!(decoded.degree_bits(), [3, 5]);
+    assert_eq!(decoded.stats(&config).total(), bytes.len() - 14);
+    assert_eq!(decoded.to_bytes(&config), bytes);
+
+    // A multi-proof can't be decoded as a single proof.
+    assert_eq!(
+        Proof::from_bytes(&config, &bytes).err(),
+        Some(ProofFormatError::BadMagic)
+    );
+}
+
+#[test]
+fn reject_invalid_encodings() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 40);
+    let proof = prove(
+        &config,
+        &FibAir,
+        &mut Challenger::new(perm.clone()),
+        fib_trace(4),
+        &vec![Val::zero(), Val::one()],
+    );
+    let bytes = proof.to_bytes(&config);
+    let decode = |bytes: &[u8]| Proof::<MyConfig>::from_bytes(&config, bytes).err();
+
+    let mut wrong_version = bytes.clone();
+    wrong_version[4] += 1;
+    assert_eq!(
+        decode(&wrong_version),
+        Some(ProofFormatError::UnsupportedVersion(
+            PROOF_FORMAT_VERSION + 1
+        ))
+    );
+
+    // Version 1 proofs carried the folding arity of each FRI round, which is now derived from the
+    // configuration.
+    let mut version_1 = bytes.clone();
+    version_1[4..6].copy_from_slice(&1u16.to_le_bytes());
+    assert_eq!(
+        decode(&version_1),
+        Some(ProofFormatError::UnsupportedVersion(1))
+    );
+
+    let other_config = config_with_perm(&perm, 1, 41);
+    assert_eq!(
+        Proof::from_bytes(&other_config, &bytes).err(),
+        Some(ProofFormatError::ConfigMismatch)
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        decode(&trailing),
+        Some(ProofFormatError::Malformed(BinaryError::TrailingBytes))
+    );
+    assert_eq!(
+        decode(&bytes[..bytes.len() - 1]),
+        Some(ProofFormatError::Malformed(BinaryError::UnexpectedEnd))
+    );
+
+    // The header is followed by the trace commitment, an absent permutation commitment, the
+    // quotient commitment, and the length of the opened trace row; then comes its first value.
+    let first_opened_value = 14 + 32 + 1 + 32 + 4;
+    let mut non_canonical = bytes;
+    non_canonical[first_opened_value..first_opened_value + 4]
+        .copy_from_slice(&Val::ORDER_U32.to_le_bytes());
+    assert!(matches!(
+        decode(&non_canonical),
+        Some(ProofFormatError::Malformed(BinaryError::Custom(_)))
+    ));
+}
+
+#[test]
+fn reject_non_canonical_field_elements() {
+    for value in [0, 1, BabyBear::ORDER_U32 - 1] {
+        let bytes = binary::to_bytes(&BabyBear::from_canonical_u32(value)).unwrap();
+        assert_eq!(bytes, value.to_le_bytes());
+        assert_eq!(
+            binary::from_bytes::<BabyBear>(&bytes),
+            Ok(BabyBear::from_canonical_u32(value))
+        );
+    }
+    assert!(binary::from_bytes::<BabyBear>(&BabyBear::ORDER_U32.to_le_bytes()).is_err());
+
+    // Mersenne31 represents zero as either 0 or p, but only encodes it as 0.
+    let p = Mersenne31::ORDER_U32;
+    assert_eq!(binary::to_bytes(&Mersenne31::new(p)).unwrap(), [0; 4]);
+    assert!(binary::from_bytes::<Mersenne31>(&p.to_le_bytes()).is_err());
+    assert!(binary::from_bytes::<Mersenne31>(&u32::MAX.to_le_bytes()).is_err());
+}
@@ -0,0 +1,177 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithRowSelectors, BaseAir, RowSelector};
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{
+    prove, prove_multi, setup, verify, verify_multi, PcsError, StarkGenericConfig, Val,
+    VerificationError,
+};
+
+mod common;
+
+use common::{baby_bear, mersenne_31};
+
+/// Counts `0, 1, 2, 0, 1, 2, ...` down the trace, in blocks of three rows whose last row is picked
+/// out by a selector rather than a committed flag column. A second column counts in blocks of four
+/// rows, whose selector has a step dividing the trace height.
+#[derive(Clone, Copy)]
+pub struct CountAir;
+
+const LAST_IN_BLOCK: RowSelector = RowSelector::Every { step: 3, offset: 2 };
+const LAST_IN_QUAD: RowSelector = RowSelector::Every { step: 4, offset: 3 };
+
+impl<F> BaseAir<F> for CountAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn row_selectors(&self) -> Vec<RowSelector> {
+        vec![
+            RowSelector::Row(0),
+            RowSelector::Row(4),
+            LAST_IN_BLOCK,
+            LAST_IN_QUAD,
+        ]
+    }
+}
+
+impl<AB: AirBuilderWithRowSelectors> Air<AB> for CountAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let last_in_block = builder.row_selector(LAST_IN_BLOCK);
+        let last_in_quad = builder.row_selector(LAST_IN_QUAD);
+
+        builder.when_row(0).assert_zero(local[0]);
+        builder.when_row(4).assert_one(local[0]);
+        builder
+            .when_selected(LAST_IN_BLOCK)
+            .assert_eq(local[0], AB::Expr::two());
+        builder.when_transition().assert_eq(
+            next[0],
+            (local[0] + AB::Expr::one()) * (AB::Expr::one() - last_in_block),
+        );
+
+        builder.when_row(0).assert_zero(local[1]);
+        builder
+            .when_selected(LAST_IN_QUAD)
+            .assert_eq(local[1], AB::Expr::from_canonical_u8(3));
+        builder.when_transition().assert_eq(
+            next[1],
+            (local[1] + AB::Expr::one()) * (AB::Expr::one() - last_in_quad),
+        );
+    }
+}
+
+fn count_trace<F: Field>(log_height: usize, valid: bool) -> RowMajorMatrix<F> {
+    let n = 1 << log_height;
+    let mut values = (0..n)
+        .flat_map(|i| {
+            [
+                F::from_canonical_usize(i % 3),
+                F::from_canonical_usize(i % 4),
+            ]
+        })
+        .collect::<Vec<_>>();
+    if !valid {
+        values[n] += F::one();
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+fn do_test<SC: StarkGenericConfig>(
+    config: SC,
+    challenger: SC::Challenger,
+    valid: bool,
+    log_heights: &[usize],
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC::Challenger: Clone,
+{
+    let mut p_challenger = challenger.clone();
+    let mut v_challenger = challenger;
+    if let [log_height] = *log_heights {
+        let trace = count_trace::<Val<SC>>(log_height, valid);
+        let proof = prove(&config, &CountAir, &mut p_challenger, trace, &vec![]);
+        let (_, vk) = setup(&config, &CountAir, 0);
+        verify(&config, &vk, &CountAir, &mut v_challenger, &proof, &vec![])
+    } else {
+        // Only the tallest instance may be invalid.
+        let airs = vec![CountAir; log_heights.len()];
+        let traces = log_heights
+            .iter()
+            .map(|&log_height| count_trace::<Val<SC>>(log_height, valid || log_height < 7))
+            .collect();
+        let public_values = vec![vec![]; log_heights.len()];
+
+        let proof = prove_multi(&config, &airs, &mut p_challenger, traces, &public_values);
+        verify_multi(&config, &airs, &mut v_challenger, &proof, &public_values)
+    }
+}
+
+fn do_test_bb_twoadic(valid: bool, log_heights: &[usize]) -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+    do_test(config, challenger, valid, log_heights)
+}
+
+fn do_test_m31_circle(valid: bool, log_heights: &[usize]) -> Result<(), impl Debug> {
+    let (config, challenger) = mersenne_31::config(1, 40);
+    do_test(config, challenger, valid, log_heights)
+}
+
+#[test]
+fn prove_bb_twoadic_row_selectors() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true, &[6])
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_bb_twoadic_row_selectors_invalid() {
+    assert!(do_test_bb_twoadic(false, &[6]).is_err());
+}
+
+#[test]
+fn prove_bb_twoadic_row_selectors_multi() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true, &[3, 7])
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_bb_twoadic_row_selectors_multi_invalid() {
+    assert!(do_test_bb_twoadic(false, &[3, 7]).is_err());
+}
+
+#[test]
+fn prove_m31_circle_row_selectors() -> Result<(), impl Debug> {
+    do_test_m31_circle(true, &[6])
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_m31_circle_row_selectors_invalid() {
+    assert!(do_test_m31_circle(false, &[6]).is_err());
+}
+
+#[test]
+fn prove_m31_circle_row_selectors_multi() -> Result<(), impl Debug> {
+    do_test_m31_circle(true, &[3, 7])
+}
+
+#[test]
+#[should_panic(
+    expected = "row selectors must have a positive step and select rows within the trace"
+)]
+fn prove_row_selector_beyond_trace() {
+    let _ = do_test_bb_twoadic(true, &[2]);
+}
@@ -0,0 +1,142 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{prove, setup, statement_digest, verify};
+
+mod common;
+
+use common::baby_bear::{config_with_perm, new_perm, Challenger};
+
+/// Checks that the single column is boolean, as `x^2 - x = 0`.
+pub struct IdempotentAir;
+
+/// Checks that the single column is boolean, as `x (x - 1) = 0`: the same constraint polynomial as
+/// [`IdempotentAir`], expressed differently.
+pub struct BoolAir;
+
+impl<F> BaseAir<F> for IdempotentAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for IdempotentAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let x = main.row_slice(0)[0];
+        builder.assert_zero(x * x - x);
+    }
+}
+
+impl<F> BaseAir<F> for BoolAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for BoolAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        builder.assert_bool(main.row_slice(0)[0]);
+    }
+}
+
+fn bits_trace<F: Field>(log_height: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col(
+        (0..1 << log_height)
+            .map(|i| F::from_bool(i % 3 == 0))
+            .collect(),
+    )
+}
+
+#[test]
+fn digest_covers_statement() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 40);
+    let digest = statement_digest(&config, &IdempotentAir, None, 0);
+
+    assert_eq!(statement_digest(&config, &IdempotentAir, None, 0), digest);
+    assert_eq!(setup(&config, &IdempotentAir, 0).1.statement_digest, digest);
+    assert_ne!(statement_digest(&config, &BoolAir, None, 0), digest);
+    assert_ne!(statement_digest(&config, &IdempotentAir, None, 1), digest);
+
+    let labelled = config_with_perm(&perm, 1, 40).with_label("my application");
+    assert_ne!(statement_digest(&labelled, &IdempotentAir, None, 0), digest);
+
+    let more_queries = config_with_perm(&perm, 1, 41);
+    assert_ne!(
+        statement_digest(&more_queries, &IdempotentAir, None, 0),
+        digest
+    );
+}
+
+#[test]
+fn labelled_proof() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 40).with_label("my application");
+    let proof = prove(
+        &config,
+        &BoolAir,
+        &mut Challenger::new(perm.clone()),
+        bits_trace(5),
+        &vec![],
+    );
+    let (_, vk) = setup(&config, &BoolAir, 0);
+    verify(
+        &config,
+        &vk,
+        &BoolAir,
+        &mut Challenger::new(perm),
+        &proof,
+        &vec![],
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn reject_proof_for_other_air() {
+    let perm = new_perm();
+    let config = config_with_perm(&perm, 1, 40);
+    let proof = prove(
+        &config,
+        &IdempotentAir,
+        &mut Challenger::new(perm.clone()),
+        bits_trace(5),
+        &vec![],
+    );
+    // Both AIRs have the same constraint polynomial, so only the statement digest tells them apart.
+    let (_, vk) = setup(&config, &BoolAir, 0);
+    assert!(verify(
+        &config,
+        &vk,
+        &BoolAir,
+        &mut Challenger::new(perm),
+        &proof,
+        &vec![],
+    )
+    .is_err());
+}
+
+#[test]
+fn reject_proof_with_other_label() {
+    let perm = new_perm();
+    let proof = prove(
+        &config_with_perm(&perm, 1, 40).with_label("application A"),
+        &BoolAir,
+        &mut Challenger::new(perm.clone()),
+        bits_trace(5),
+        &vec![],
+    );
+    let other_config = config_with_perm(&perm, 1, 40).with_label("application B");
+    let (_, vk) = setup(&other_config, &BoolAir, 0);
+    assert!(verify(
+        &other_config,
+        &vk,
+        &BoolAir,
+        &mut Challenger::new(perm),
+        &proof,
+        &vec![],
+    )
+    .is_err());
+}
@@ -0,0 +1,46 @@
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_uni_stark::{Entry, SymbolicDag, SymbolicExpression, SymbolicNode, SymbolicVariable};
+
+type F = BabyBear;
+
+fn main_var(index: usize) -> SymbolicExpression<F> {
+    SymbolicVariable::new(Entry::Main { offset: 0 }, index).into()
+}
+
+#[test]
+fn dag_shares_common_subexpressions() {
+    let x = main_var(0);
+    let y = main_var(1);
+    // Built separately, so they share no `Rc`s.
+    let s_box = |x: SymbolicExpression<F>| x.clone() * x.clone() * x.clone() * x;
+    let constraints = vec![s_box(x.clone()) - y.clone(), y + s_box(x)];
+
+    let dag = SymbolicDag::new(&constraints);
+    // x, x^2, x^3, x^4, y, x^4 - y and x^4 + y.
+    assert_eq!(dag.nodes.len(), 7);
+    assert_eq!(dag.constraints, vec![5, 6]);
+    assert!(matches!(dag.nodes[6], SymbolicNode::Add(3, 4)));
+}
+
+#[test]
+fn constants_are_folded() {
+    let x = main_var(0);
+    let constraints = vec![
+        (x.clone() * F::one() + F::zero()) - F::zero(),
+        x * F::zero() + F::two() * F::two(),
+    ];
+
+    let dag = SymbolicDag::new(&constraints);
+    assert_eq!(
+        dag.nodes,
+        vec![
+            SymbolicNode::Variable {
+                entry: Entry::Main { offset: 0 },
+                index: 0,
+            },
+            SymbolicNode::Constant(F::from_canonical_u32(4)),
+        ]
+    );
+    assert_eq!(dag.constraints, vec![0, 1]);
+}
@@ -0,0 +1,84 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{trace_to_csv, trace_to_json, trace_window};
+
+mod common;
+
+use common::{fibonacci_trace, FibonacciAir};
+
+type Val = BabyBear;
+
+/// Declares a permutation column, which `trace_window` can't evaluate.
+pub struct PermutationAir;
+
+impl<F> BaseAir<F> for PermutationAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn permutation_width(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for PermutationAir {
+    fn eval(&self, builder: &mut AB) {
+        let value = builder.main().row_slice(0)[0];
+        builder.assert_one(value);
+    }
+}
+
+#[test]
+fn window_around_failure() {
+    let (mut trace, public_values) = fibonacci_trace::<Val>(3);
+    trace.values[2 * 4 + 1] = Val::from_canonical_u32(100);
+
+    let window = trace_window(&FibonacciAir, &trace, &public_values, 4, 1);
+    assert_eq!(window.rows, 3..6);
+    assert_eq!(
+        window.failures.iter().map(|f| f.row).collect::<Vec<_>>(),
+        [3, 4, 4]
+    );
+    assert_eq!(
+        window.to_string(),
+        "rows 3..6 of 8
+row  3!   4!  5
+a*    2    3  5
+b*    3  100  8
+failing constraints:
+  row 3, constraint 3 = 2013265826: when transition: a + b - b' = 0  (degree 1)
+  row 4, constraint 2 = 95: when transition: b - a' = 0  (degree 1)
+  row 4, constraint 3 = 95: when transition: a + b - b' = 0  (degree 1)
+"
+    );
+
+    // The window is clamped to the trace, and has no failures on a valid trace.
+    let (trace, public_values) = fibonacci_trace::<Val>(3);
+    let window = trace_window(&FibonacciAir, &trace, &public_values, 0, 2);
+    assert_eq!(window.rows, 0..3);
+    assert!(window.failures.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "permutation columns or interactions aren't supported")]
+fn window_rejects_permutation_columns() {
+    let trace = RowMajorMatrix::new_col(vec![Val::one(); 4]);
+    trace_window(&PermutationAir, &trace, &[], 0, 1);
+}
+
+#[test]
+fn export_trace() {
+    let (trace, _) = fibonacci_trace::<Val>(2);
+    assert_eq!(
+        trace_to_csv(&FibonacciAir, &trace),
+        "row,a,b\n0,0,1\n1,1,1\n2,1,2\n3,2,3\n"
+    );
+    assert_eq!(
+        trace_to_json(&FibonacciAir, &trace),
+        r#"{"columns":["a","b"],"rows":[[0,1],[1,1],[1,2],[2,3]]}"#
+    );
+    assert_eq!(trace.height(), 4);
+}
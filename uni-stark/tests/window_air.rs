@@ -0,0 +1,159 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{
+    prove, prove_multi, setup, verify, verify_multi, PcsError, StarkGenericConfig, Val,
+    VerificationError,
+};
+
+mod common;
+
+use common::{baby_bear, mersenne_31};
+
+/// Computes Tribonacci numbers, which needs a window of four rows; the public values are the
+/// first three numbers. A second column counts the rows, checked over the usual two-row window.
+pub struct TribonacciAir;
+
+impl<F> BaseAir<F> for TribonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn window_size(&self) -> usize {
+        4
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for TribonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, c) = (pis[0], pis[1], pis[2]);
+        let rows = (0..4).map(|i| main.row_slice(i)).collect::<Vec<_>>();
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(rows[0][0], a);
+        when_first_row.assert_eq(rows[1][0], b);
+        when_first_row.assert_eq(rows[2][0], c);
+        when_first_row.assert_zero(rows[0][1]);
+
+        builder
+            .when_transition_window(4)
+            .assert_eq(rows[0][0] + rows[1][0] + rows[2][0], rows[3][0]);
+        builder
+            .when_transition()
+            .assert_eq(rows[0][1] + AB::Expr::one(), rows[1][1]);
+    }
+}
+
+fn tribonacci_trace<F: Field>(log_height: usize, valid: bool) -> (RowMajorMatrix<F>, Vec<F>) {
+    let n = 1 << log_height;
+    let (mut a, mut b, mut c) = (F::zero(), F::one(), F::two());
+    let mut values = Vec::with_capacity(2 * n);
+    for i in 0..n {
+        values.extend([a, F::from_canonical_usize(i)]);
+        (a, b, c) = (b, c, a + b + c);
+    }
+    if !valid {
+        values[2 * (n / 2)] += F::one();
+    }
+    (
+        RowMajorMatrix::new(values, 2),
+        vec![F::zero(), F::one(), F::two()],
+    )
+}
+
+fn do_test<SC: StarkGenericConfig>(
+    config: SC,
+    challenger: SC::Challenger,
+    valid: bool,
+    multi: bool,
+) -> Result<(), VerificationError<PcsError<SC>>>
+where
+    SC::Challenger: Clone,
+{
+    let mut p_challenger = challenger.clone();
+    let mut v_challenger = challenger;
+    if multi {
+        // Two instances of different heights, the larger of which may be invalid.
+        let airs = [TribonacciAir, TribonacciAir];
+        let (small_trace, small_pis) = tribonacci_trace::<Val<SC>>(3, true);
+        let (large_trace, large_pis) = tribonacci_trace::<Val<SC>>(7, valid);
+        let traces = vec![small_trace, large_trace];
+        let public_values = vec![small_pis, large_pis];
+
+        let proof = prove_multi(&config, &airs, &mut p_challenger, traces, &public_values);
+        verify_multi(&config, &airs, &mut v_challenger, &proof, &public_values)
+    } else {
+        let (trace, pis) = tribonacci_trace::<Val<SC>>(6, valid);
+        let proof = prove(&config, &TribonacciAir, &mut p_challenger, trace, &pis);
+
+        let serialized_proof = postcard::to_allocvec(&proof).expect("unable to serialize proof");
+        let deserialized_proof =
+            postcard::from_bytes(&serialized_proof).expect("unable to deserialize proof");
+        let (_, vk) = setup(&config, &TribonacciAir, pis.len());
+        verify(
+            &config,
+            &vk,
+            &TribonacciAir,
+            &mut v_challenger,
+            &deserialized_proof,
+            &pis,
+        )
+    }
+}
+
+fn do_test_bb_twoadic(valid: bool, multi: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = baby_bear::config(1, 40);
+    do_test(config, challenger, valid, multi)
+}
+
+fn do_test_m31_circle(valid: bool, multi: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = mersenne_31::config(1, 40);
+    do_test(config, challenger, valid, multi)
+}
+
+#[test]
+fn prove_bb_twoadic_window() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true, false)
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_bb_twoadic_window_invalid() {
+    assert!(do_test_bb_twoadic(false, false).is_err());
+}
+
+#[test]
+fn prove_bb_twoadic_window_multi() -> Result<(), impl Debug> {
+    do_test_bb_twoadic(true, true)
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_bb_twoadic_window_multi_invalid() {
+    assert!(do_test_bb_twoadic(false, true).is_err());
+}
+
+#[test]
+fn prove_m31_circle_window() -> Result<(), impl Debug> {
+    do_test_m31_circle(true, false)
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_m31_circle_window_invalid() {
+    assert!(do_test_m31_circle(false, false).is_err());
+}
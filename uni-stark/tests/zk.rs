@@ -0,0 +1,212 @@
+use std::fmt::Debug;
+
+use p3_air::{Air, BaseAir, InteractionBuilder, PairBuilder};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractField, Field};
+use p3_fri::{FriConfig, HidingFriPcs};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeHidingMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{
+    prove, prove_with_key, setup, verify, StarkConfig, StarkGenericConfig, VerificationError,
+};
+use rand::rngs::ThreadRng;
+use rand::{thread_rng, Rng};
+
+mod common;
+
+use common::{fibonacci_trace, FibonacciAir};
+
+/// Range checks the main column against a preprocessed table, exercising the preprocessed and
+/// permutation rounds in ZK mode.
+pub struct RangeCheckAir {
+    log_height: usize,
+}
+
+impl<F: Field> BaseAir<F> for RangeCheckAir {
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..1 << self.log_height)
+                .map(F::from_canonical_usize)
+                .collect(),
+        ))
+    }
+}
+
+impl<AB: PairBuilder + InteractionBuilder> Air<AB> for RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let table = builder.preprocessed().row_slice(0)[0];
+
+        builder.send(0, [local[0]], AB::Expr::one());
+        builder.receive(0, [table], local[1]);
+    }
+}
+
+fn range_check_trace<F: Field>(log_height: usize) -> RowMajorMatrix<F> {
+    let n = 1 << log_height;
+    let mut rng = thread_rng();
+    let looked_up = (0..n).map(|_| rng.gen_range(0..n)).collect::<Vec<_>>();
+    let mut multiplicities = vec![0; n];
+    for &value in &looked_up {
+        multiplicities[value] += 1;
+    }
+    let values = looked_up
+        .into_iter()
+        .zip(multiplicities)
+        .flat_map(|(v, m)| [F::from_canonical_usize(v), F::from_canonical_usize(m)])
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = BabyBear;
+type Perm = Poseidon2<Val, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs = MerkleTreeHidingMmcs<
+    <Val as Field>::Packing,
+    <Val as Field>::Packing,
+    MyHash,
+    MyCompress,
+    ThreadRng,
+    8,
+    4,
+>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = HidingFriPcs<Val, Dft, ValMmcs, ChallengeMmcs, ThreadRng>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger, Keccak256Hash>;
+
+fn new_perm() -> Perm {
+    Perm::new_from_rng_128(
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear::default(),
+        &mut thread_rng(),
+    )
+}
+
+fn zk_config_with_perm(perm: &Perm, num_random_codewords: usize) -> MyConfig {
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress, thread_rng());
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        log_folding_arity: 1,
+        num_queries: 28,
+        proof_of_work_bits: 8,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(
+        Dft::default(),
+        val_mmcs,
+        fri_config,
+        num_random_codewords,
+        thread_rng(),
+    );
+    MyConfig::new(pcs, Keccak256Hash)
+}
+
+fn zk_config() -> (MyConfig, Challenger) {
+    let perm = new_perm();
+    (zk_config_with_perm(&perm, 4), Challenger::new(perm))
+}
+
+fn do_test_fibonacci(valid: bool) -> Result<(), impl Debug> {
+    let (config, challenger) = zk_config();
+    assert!(config.is_zk());
+
+    let (trace, mut public_values) = fibonacci_trace::<Val>(6);
+    if !valid {
+        public_values[2] += Val::one();
+    }
+    let proof = prove(
+        &config,
+        &FibonacciAir,
+        &mut challenger.clone(),
+        trace,
+        &public_values,
+    );
+
+    let serialized_proof = postcard::to_allocvec(&proof).expect("unable to serialize proof");
+    let deserialized_proof =
+        postcard::from_bytes(&serialized_proof).expect("unable to deserialize proof");
+
+    let (_, vk) = setup(&config, &FibonacciAir, public_values.len());
+    verify(
+        &config,
+        &vk,
+        &FibonacciAir,
+        &mut challenger.clone(),
+        &deserialized_proof,
+        &public_values,
+    )
+}
+
+#[test]
+fn prove_zk_fibonacci() -> Result<(), impl Debug> {
+    do_test_fibonacci(true)
+}
+
+// In debug builds the prover's constraint check catches the bad output before the verifier does.
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "constraints had nonzero value")
+)]
+fn prove_zk_fibonacci_wrong_output() {
+    assert!(do_test_fibonacci(false).is_err());
+}
+
+#[test]
+fn prove_zk_range_check() -> Result<(), impl Debug> {
+    let (config, challenger) = zk_config();
+    let air = RangeCheckAir { log_height: 6 };
+    // The hiding PCS randomizes the preprocessed commitment too, so the verifier must use the key
+    // from the same setup as the prover.
+    let (pk, vk) = setup(&config, &air, 0);
+
+    let trace = range_check_trace::<Val>(6);
+    let proof = prove_with_key(&config, &pk, &air, &mut challenger.clone(), trace, &vec![]);
+    verify(&config, &vk, &air, &mut challenger.clone(), &proof, &vec![])
+}
+
+#[test]
+fn reject_zk_proof_with_wrong_number_of_random_values() {
+    let perm = new_perm();
+    let (trace, public_values) = fibonacci_trace::<Val>(6);
+    let proof = prove(
+        &zk_config_with_perm(&perm, 4),
+        &FibonacciAir,
+        &mut Challenger::new(perm.clone()),
+        trace,
+        &public_values,
+    );
+
+    let config = zk_config_with_perm(&perm, 3);
+    let (_, vk) = setup(&config, &FibonacciAir, public_values.len());
+    let result = verify(
+        &config,
+        &vk,
+        &FibonacciAir,
+        &mut Challenger::new(perm),
+        &proof,
+        &public_values,
+    );
+    assert!(matches!(result, Err(VerificationError::InvalidProofShape)));
+}
@@ -0,0 +1,735 @@
+//! A canonical binary encoding for serde types, used to store and exchange proofs.
+//!
+//! Unlike general-purpose formats, every value has exactly one encoding, and the decoder rejects
+//! anything else. The format isn't self-describing; a value is decoded by the type it was encoded
+//! from:
+//!
+//! - `bool` is one byte, `0` or `1`.
+//! - Integers are little-endian and of their fixed width; `usize` is encoded by serde as a `u64`.
+//!   `char` is encoded as a `u32`.
+//! - Sequences, maps, strings and byte strings start with their length as a `u32`, followed by
+//!   their elements, entries, or bytes. Strings must be valid UTF-8. Map entries are encoded in
+//!   iteration order, so only ordered maps have a canonical encoding.
+//! - `Option` is the byte `0` for `None`, or the byte `1` followed by the value.
+//! - Tuples, arrays and structs are their fields in order, without a length or field names. Unit
+//!   types are empty, and newtypes are their content.
+//! - Enum variants are their index as a `u32`, followed by their fields.
+//!
+//! Floats and `deserialize_any` are not supported. Field elements are encoded by their own serde
+//! implementations, which use canonical representatives and reject non-canonical ones.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Display;
+
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::{ser, Deserialize, Serialize};
+
+/// An error raised while encoding or decoding a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BinaryError {
+    /// The input ended before the value was fully decoded.
+    UnexpectedEnd,
+    /// Bytes remained after the value was decoded.
+    TrailingBytes,
+    /// A `bool` or `Option` tag other than `0` or `1`.
+    InvalidTag(u8),
+    /// A string which isn't valid UTF-8, or a `char` which isn't a Unicode scalar value.
+    InvalidUtf8,
+    /// A length which doesn't fit in a `u32`.
+    LengthOverflow,
+    /// A sequence or map whose length isn't known before its elements are serialized.
+    UnknownLength,
+    /// A feature of serde the format doesn't support.
+    Unsupported(&'static str),
+    /// An error raised by a `Serialize` or `Deserialize` implementation, such as for a
+    /// non-canonical field element.
+    Custom(String),
+}
+
+impl Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::TrailingBytes => f.write_str("trailing bytes after the value"),
+            Self::InvalidTag(tag) => write!(f, "invalid tag {tag}"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::LengthOverflow => f.write_str("length doesn't fit in a u32"),
+            Self::UnknownLength => f.write_str("length must be known ahead of time"),
+            Self::Unsupported(what) => write!(f, "{what} is not supported"),
+            Self::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl ser::StdError for BinaryError {}
+
+impl ser::Error for BinaryError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl serde::de::Error for BinaryError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Encodes `value`.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, BinaryError> {
+    let mut output = Vec::new();
+    serialize_into(&mut output, value)?;
+    Ok(output)
+}
+
+/// The length of the encoding of `value`.
+///
+/// Panics if `value` can't be encoded.
+pub fn encoded_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    to_bytes(value)
+        .expect("value must be supported by the binary format")
+        .len()
+}
+
+/// Encodes `value`, appending it to `output`.
+pub fn serialize_into<T: Serialize + ?Sized>(
+    output: &mut Vec<u8>,
+    value: &T,
+) -> Result<(), BinaryError> {
+    value.serialize(&mut Serializer { output })
+}
+
+/// Decodes a value which must take up all of `bytes`.
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, BinaryError> {
+    let mut deserializer = Deserializer { input: bytes };
+    let value = T::deserialize(&mut deserializer)?;
+    if deserializer.input.is_empty() {
+        Ok(value)
+    } else {
+        Err(BinaryError::TrailingBytes)
+    }
+}
+
+struct Serializer<'a> {
+    output: &'a mut Vec<u8>,
+}
+
+impl Serializer<'_> {
+    fn write_len(&mut self, len: usize) -> Result<(), BinaryError> {
+        let len = u32::try_from(len).map_err(|_| BinaryError::LengthOverflow)?;
+        self.output.extend(len.to_le_bytes());
+        Ok(())
+    }
+}
+
+impl ser::Serializer for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), BinaryError> {
+        self.output.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), BinaryError> {
+        self.output.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), BinaryError> {
+        self.output.extend(v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), BinaryError> {
+        Err(BinaryError::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), BinaryError> {
+        Err(BinaryError::Unsupported("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), BinaryError> {
+        self.serialize_u32(v.into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), BinaryError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), BinaryError> {
+        self.write_len(v.len())?;
+        self.output.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), BinaryError> {
+        self.output.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), BinaryError> {
+        self.output.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), BinaryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), BinaryError> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        self.serialize_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, BinaryError> {
+        self.write_len(len.ok_or(BinaryError::UnknownLength)?)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, BinaryError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, BinaryError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, BinaryError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, BinaryError> {
+        self.serialize_seq(len)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, BinaryError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, BinaryError> {
+        self.serialize_tuple_variant(name, variant_index, variant, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), BinaryError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Serializer<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+}
+
+struct Deserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    fn take(&mut self, len: usize) -> Result<&'de [u8], BinaryError> {
+        if self.input.len() < len {
+            return Err(BinaryError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(taken)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], BinaryError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn read_tag(&mut self) -> Result<bool, BinaryError> {
+        match self.take_array::<1>()?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(BinaryError::InvalidTag(tag)),
+        }
+    }
+
+    fn read_len(&mut self) -> Result<usize, BinaryError> {
+        Ok(u32::from_le_bytes(self.take_array()?) as usize)
+    }
+
+    fn read_bytes(&mut self) -> Result<&'de [u8], BinaryError> {
+        let len = self.read_len()?;
+        self.take(len)
+    }
+}
+
+/// Deserializes a fixed-width little-endian integer.
+macro_rules! deserialize_int {
+    ($deserialize:ident, $visit:ident, $ty:ty) => {
+        fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+            visitor.$visit(<$ty>::from_le_bytes(self.take_array()?))
+        }
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = BinaryError;
+
+    deserialize_int!(deserialize_i8, visit_i8, i8);
+    deserialize_int!(deserialize_i16, visit_i16, i16);
+    deserialize_int!(deserialize_i32, visit_i32, i32);
+    deserialize_int!(deserialize_i64, visit_i64, i64);
+    deserialize_int!(deserialize_i128, visit_i128, i128);
+    deserialize_int!(deserialize_u8, visit_u8, u8);
+    deserialize_int!(deserialize_u16, visit_u16, u16);
+    deserialize_int!(deserialize_u32, visit_u32, u32);
+    deserialize_int!(deserialize_u64, visit_u64, u64);
+    deserialize_int!(deserialize_u128, visit_u128, u128);
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, BinaryError> {
+        Err(BinaryError::Unsupported("deserialize_any"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        visitor.visit_bool(self.read_tag()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, BinaryError> {
+        Err(BinaryError::Unsupported("f32"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, BinaryError> {
+        Err(BinaryError::Unsupported("f64"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let c = char::from_u32(u32::from_le_bytes(self.take_array()?))
+            .ok_or(BinaryError::InvalidUtf8)?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let s = core::str::from_utf8(self.read_bytes()?).map_err(|_| BinaryError::InvalidUtf8)?;
+        visitor.visit_borrowed_str(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        visitor.visit_borrowed_bytes(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        if self.read_tag()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let len = self.read_len()?;
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_seq(Elements { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, BinaryError> {
+        let len = self.read_len()?;
+        visitor.visit_map(Elements { de: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, BinaryError> {
+        Err(BinaryError::Unsupported("deserialize_identifier"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        Err(BinaryError::Unsupported("deserialize_ignored_any"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The elements of a sequence, or entries of a map, of known length.
+struct Elements<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = BinaryError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, BinaryError> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_, 'de> {
+    type Error = BinaryError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, BinaryError> {
+        self.next_element_seed(seed)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, BinaryError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = BinaryError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), BinaryError> {
+        let index = u32::from_le_bytes(self.take_array()?);
+        let variant = seed.deserialize(index.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = BinaryError;
+
+    fn unit_variant(self) -> Result<(), BinaryError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, BinaryError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        serde::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, BinaryError> {
+        serde::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = (
+            vec![Some(1u32), None],
+            [7u8; 3],
+            (true, 0x0102_0304_0506_0708u64, 'x'),
+            String::from("abc"),
+        );
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(
+            bytes,
+            [
+                2, 0, 0, 0, 1, 1, 0, 0, 0, 0, // vec![Some(1), None]
+                7, 7, 7, // [7; 3]
+                1, 8, 7, 6, 5, 4, 3, 2, 1, b'x', 0, 0, 0, // (true, _, 'x')
+                3, 0, 0, 0, b'a', b'b', b'c', // "abc"
+            ]
+        );
+        assert_eq!(from_bytes::<(_, _, _, String)>(&bytes), Ok(value));
+    }
+
+    #[test]
+    fn reject_non_canonical() {
+        assert_eq!(from_bytes::<bool>(&[2]), Err(BinaryError::InvalidTag(2)));
+        assert_eq!(
+            from_bytes::<Option<u8>>(&[1, 5, 0]),
+            Err(BinaryError::TrailingBytes)
+        );
+        assert_eq!(
+            from_bytes::<Vec<u16>>(&[2, 0, 0, 0, 1, 0]),
+            Err(BinaryError::UnexpectedEnd)
+        );
+        assert_eq!(
+            from_bytes::<String>(&[1, 0, 0, 0, 0xff]),
+            Err(BinaryError::InvalidUtf8)
+        );
+    }
+}
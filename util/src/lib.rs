@@ -12,6 +12,7 @@ use core::mem;
 use core::mem::MaybeUninit;
 
 pub mod array_serialization;
+pub mod binary;
 pub mod linear_map;
 
 /// Computes `ceil(log_2(n))`.